@@ -0,0 +1,321 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::diagnostic::Diagnostic;
+use crate::lexer::{Span, TokenIter};
+use crate::parser::Parser;
+
+// NDJSON files are validated in chunks of this many lines
+const CHUNK_LINES: usize = 1000;
+
+/// A glob with `.gitignore` semantics. `*` and `?` stop at slashes, `**`
+/// crosses them, a pattern without a slash matches the name at any depth,
+/// a trailing slash only matches directories and a leading `!` negates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    glob: Vec<char>,
+    negated: bool,
+    directory: bool,
+    anchored: bool
+}
+
+impl Pattern {
+    /// Reads one line of an ignore file, blank lines and comments give none.
+    pub fn parse(line: &str) -> Option<Pattern> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line))
+        };
+        let (directory, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line)
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(Pattern { glob: line.chars().collect(), negated, directory, anchored })
+    }
+
+    /// Whether `path`, relative to where the pattern applies and separated
+    /// by slashes, matches. Negation is left to the caller.
+    pub fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.directory && !is_dir {
+            return false;
+        }
+        let text: Vec<char> = if self.anchored {
+            path.chars().collect()
+        } else {
+            path.rsplit('/').next().unwrap_or(path).chars().collect()
+        };
+        glob(&self.glob, &text)
+    }
+}
+
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        // Zero or more whole directories
+        ['*', '*', '/', rest @ ..] => glob(rest, text) ||
+            (0..text.len()).any(|idx| text[idx] == '/' && glob(rest, &text[idx + 1..])),
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|idx| glob(rest, &text[idx..])),
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|idx| *idx == 0 || text[idx - 1] != '/')
+            .any(|idx| glob(rest, &text[idx..])),
+        ['?', rest @ ..] => matches!(text.first(), Some(c) if *c != '/') && glob(rest, &text[1..]),
+        ['[', class @ ..] => match class_end(class) {
+            Some(end) => match text.first() {
+                Some(c) if *c != '/' && in_class(&class[..end], *c) => glob(&class[end + 1..], &text[1..]),
+                _ => false
+            },
+            None => text.first() == Some(&'[') && glob(class, &text[1..])
+        },
+        ['\\', c, rest @ ..] | [c, rest @ ..] => text.first() == Some(c) && glob(rest, &text[1..])
+    }
+}
+
+// The index of the `]` closing a character class, which may itself be the
+// first character of the class
+fn class_end(class: &[char]) -> Option<usize> {
+    let first = if matches!(class.first(), Some('!') | Some('^')) { 1 } else { 0 };
+    (first + 1..class.len()).find(|idx| class[*idx] == ']')
+}
+
+fn in_class(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!') | Some('^') => (true, &class[1..]),
+        _ => (false, class)
+    };
+    let mut found = false;
+    let mut idx = 0;
+    while idx < class.len() {
+        if idx + 2 < class.len() && class[idx + 1] == '-' {
+            found |= class[idx] <= c && c <= class[idx + 2];
+            idx += 3;
+        } else {
+            found |= class[idx] == c;
+            idx += 1;
+        }
+    }
+    found != negated
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Which files are picked up from directories, files named explicitly
+    /// are always checked.
+    pub include: Vec<Pattern>,
+    /// Applied before the `.gitignore` files found along the way.
+    pub exclude: Vec<Pattern>,
+    /// Treat every file as newline delimited JSON, not just `.ndjson` and
+    /// `.jsonl` files.
+    pub ndjson: bool,
+    pub jobs: usize
+}
+
+impl Default for BatchOptions {
+    fn default() -> BatchOptions {
+        BatchOptions {
+            include: ["*.json", "*.ndjson", "*.jsonl"].iter().filter_map(|glob| Pattern::parse(glob)).collect(),
+            exclude: Vec::new(),
+            ndjson: false,
+            jobs: thread::available_parallelism().map_or(1, |jobs| jobs.get())
+        }
+    }
+}
+
+/// Expands files and directory trees into the sorted list of files to check,
+/// skipping `.git` and whatever excludes and `.gitignore` files rule out.
+pub fn collect_files(roots: &[PathBuf], options: &BatchOptions) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for root in roots {
+        if root.is_dir() {
+            let mut rules: Vec<(String, Pattern)> = options.exclude.iter()
+                .map(|pattern| (String::new(), pattern.clone()))
+                .collect();
+            walk(root, "", &mut rules, options, &mut files)?;
+        } else {
+            // Surfaces a missing file as an error now, not as a failed check
+            fs::metadata(root)?;
+            files.push(root.clone());
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+// `relative` is the directory's path from the root, with a trailing slash
+fn walk(dir: &Path, relative: &str, rules: &mut Vec<(String, Pattern)>, options: &BatchOptions,
+        files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let inherited = rules.len();
+    if let Ok(ignore) = fs::read_to_string(dir.join(".gitignore")) {
+        rules.extend(ignore.lines().filter_map(Pattern::parse).map(|pattern| (relative.to_string(), pattern)));
+    }
+
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path();
+        let is_dir = entry.file_type()?.is_dir();
+        let entry_relative = format!("{}{}", relative, name);
+        if name == ".git" || ignored(rules, &entry_relative, is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            walk(&path, &format!("{}/", entry_relative), rules, options, files)?;
+        } else if path.is_file() && options.include.iter().any(|pattern| pattern.matches(&entry_relative, false)) {
+            files.push(path);
+        }
+    }
+
+    rules.truncate(inherited);
+    Ok(())
+}
+
+// The last matching rule wins, rules only see paths below their directory
+fn ignored(rules: &[(String, Pattern)], relative: &str, is_dir: bool) -> bool {
+    rules.iter().rev()
+        .find(|(base, pattern)| relative.strip_prefix(base.as_str()).is_some_and(|path| pattern.matches(path, is_dir)))
+        .is_some_and(|(_, pattern)| !pattern.negated)
+}
+
+/// The outcome of checking many files. Everything is sorted, so the report
+/// does not depend on how the work was scheduled.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub files: usize,
+    // Non-blank lines of NDJSON files
+    pub lines: usize,
+    pub invalid_files: usize,
+    // Ordered by file, then position
+    pub diagnostics: Vec<Diagnostic>,
+    // Files that could not be read, with the reason
+    pub unreadable: Vec<(String, String)>
+}
+
+impl BatchReport {
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.is_empty() && self.unreadable.is_empty()
+    }
+}
+
+/// Validates files in parallel. NDJSON files are split into chunks of lines
+/// that are validated concurrently too, each line as its own document.
+pub fn check_files(files: &[PathBuf], options: &BatchOptions) -> BatchReport {
+    let is_ndjson = |path: &PathBuf| options.ndjson ||
+        path.extension().is_some_and(|extension| extension == "ndjson" || extension == "jsonl");
+
+    let read = parallel_map(files, options.jobs, |path| {
+        let name = path.display().to_string();
+        let contents = fs::read_to_string(path).map_err(|err| (name.clone(), err.to_string()))?;
+        if is_ndjson(path) {
+            return Ok((name, Some(contents), Vec::new()));
+        }
+        let tokens = TokenIter::new(&contents).checked();
+        let diagnostics = match Parser::checked(Box::new(tokens)).try_validate() {
+            Ok(()) => Vec::new(),
+            Err(err) => vec![Diagnostic::new(&name, &err)]
+        };
+        Ok((name, None, diagnostics))
+    });
+
+    let mut report = BatchReport { files: files.len(), ..BatchReport::default() };
+    let mut documents = Vec::new();
+    for result in read {
+        match result {
+            Ok((name, Some(contents), _)) => documents.push((name, contents)),
+            Ok((_, None, diagnostics)) => report.diagnostics.extend(diagnostics),
+            Err(unreadable) => report.unreadable.push(unreadable)
+        }
+    }
+
+    let mut chunks = Vec::new();
+    for (idx, (_, contents)) in documents.iter().enumerate() {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for (number, line) in contents.split('\n').enumerate() {
+            lines.push((number + 1, offset, line));
+            offset += line.len() + 1;
+        }
+        chunks.extend(lines.chunks(CHUNK_LINES).map(|chunk| (idx, chunk.to_vec())));
+    }
+    let checked = parallel_map(&chunks, options.jobs, |(idx, lines)| {
+        let mut count = 0;
+        let mut diagnostics = Vec::new();
+        for (number, offset, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            count += 1;
+            let tokens = TokenIter::new(line).checked();
+            if let Err(err) = Parser::checked(Box::new(tokens)).try_validate() {
+                diagnostics.push(in_file(Diagnostic::new(&documents[*idx].0, &err), *number, *offset));
+            }
+        }
+        (count, diagnostics)
+    });
+    for (count, diagnostics) in checked {
+        report.lines += count;
+        report.diagnostics.extend(diagnostics);
+    }
+
+    report.diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    let mut invalid: Vec<&str> = report.diagnostics.iter().map(|diagnostic| diagnostic.file.as_str()).collect();
+    invalid.dedup();
+    report.invalid_files = invalid.len() + report.unreadable.len();
+    report
+}
+
+// Moves a diagnostic for one NDJSON line to its place in the whole file
+fn in_file(mut diagnostic: Diagnostic, line: usize, offset: usize) -> Diagnostic {
+    let shift = |span: Span| Span { start: span.start + offset, end: span.end + offset };
+    diagnostic.line += line - 1;
+    diagnostic.span = shift(diagnostic.span);
+    if let Some(fix) = &mut diagnostic.fix {
+        fix.span = shift(fix.span);
+    }
+    diagnostic
+}
+
+// Workers take the next item until none are left, results keep the order
+// of the items
+fn parallel_map<T: Sync, R: Send>(items: &[T], jobs: usize, work: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                match items.get(idx) {
+                    Some(item) => {
+                        let result = work(item);
+                        results.lock().unwrap().push((idx, result));
+                    },
+                    None => break
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::batch::{check_files, collect_files, BatchOptions, Pattern};
+use crate::lexer::Span;
+
+fn matches(pattern: &str, path: &str) -> bool {
+    Pattern::parse(pattern).unwrap().matches(path, false)
+}
+
+// A scratch directory that is removed again at the end of the test
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(name: &str) -> Scratch {
+        let dir = std::env::temp_dir().join(format!("jsorry-batch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Scratch(dir)
+    }
+
+    fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn relative(&self, files: &[PathBuf]) -> Vec<String> {
+        files.iter().map(|file| file.strip_prefix(&self.0).unwrap().display().to_string()).collect()
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_pattern() {
+    assert!(matches("*.json", "a/b/c.json"));
+    assert!(!matches("*.json", "a.json.bak"));
+    assert!(matches("/build", "build"));
+    assert!(!matches("/build", "src/build"));
+    assert!(matches("src/*.json", "src/a.json"));
+    assert!(!matches("src/*.json", "src/deep/a.json"));
+    assert!(matches("src/**/a.json", "src/a.json"));
+    assert!(matches("src/**/a.json", "src/x/y/a.json"));
+    assert!(matches("**/fixtures", "a/fixtures"));
+    assert!(matches("data-?.json", "data-1.json"));
+    assert!(matches("data-[0-9].json", "data-7.json"));
+    assert!(!matches("data-[!0-9].json", "data-7.json"));
+    assert!(matches("\\#notes.json", "#notes.json"));
+
+    assert_eq!(Pattern::parse("# comment"), None);
+    assert_eq!(Pattern::parse("   "), None);
+    let directory = Pattern::parse("target/").unwrap();
+    assert!(directory.matches("a/target", true));
+    assert!(!directory.matches("a/target", false));
+}
+
+#[test]
+fn test_collect_files() {
+    let scratch = Scratch::new("collect");
+    scratch.write("a.json", "1");
+    scratch.write("notes.txt", "");
+    scratch.write("events.ndjson", "");
+    scratch.write(".gitignore", "build/\n*.generated.json\n");
+    scratch.write("build/out.json", "1");
+    scratch.write("src/b.generated.json", "1");
+    scratch.write("src/c.json", "1");
+    scratch.write("src/vendor/.gitignore", "*.json\n!keep.json\n");
+    scratch.write("src/vendor/drop.json", "1");
+    scratch.write("src/vendor/keep.json", "1");
+    scratch.write(".git/config.json", "1");
+
+    let files = collect_files(std::slice::from_ref(&scratch.0), &BatchOptions::default()).unwrap();
+    assert_eq!(scratch.relative(&files), vec!["a.json", "events.ndjson", "src/c.json", "src/vendor/keep.json"]);
+
+    let options = BatchOptions { exclude: vec![Pattern::parse("src/").unwrap()], ..BatchOptions::default() };
+    let files = collect_files(&[scratch.0.clone(), scratch.0.join("notes.txt")], &options).unwrap();
+    assert_eq!(scratch.relative(&files), vec!["a.json", "events.ndjson", "notes.txt"]);
+
+    let options = BatchOptions { include: vec![Pattern::parse("src/*.json").unwrap()], ..BatchOptions::default() };
+    assert_eq!(scratch.relative(&collect_files(std::slice::from_ref(&scratch.0), &options).unwrap()), vec!["src/c.json"]);
+
+    assert!(collect_files(&[scratch.0.join("missing.json")], &options).is_err());
+}
+
+#[test]
+fn test_check_files() {
+    let scratch = Scratch::new("check");
+    let mut ndjson: Vec<String> = (0..2500).map(|idx| format!("{{\"n\": {}}}", idx)).collect();
+    ndjson[1200] = "{\"n\": 01}".to_string();
+    ndjson[4] = String::new();
+    ndjson[2499] = "[1,]".to_string();
+    let files = vec![
+        scratch.write("valid.json", "{\"a\": [1, 2]}"),
+        scratch.write("invalid.json", "{\"a\": [1 2]}"),
+        scratch.write("lines.ndjson", ndjson.join("\r\n") + "\n"),
+        scratch.write("binary.json", [0xff, 0xfe])
+    ];
+    let name = |file: &str| scratch.0.join(file).display().to_string();
+
+    let report = check_files(&files, &BatchOptions { jobs: 4, ..BatchOptions::default() });
+    assert!(!report.is_ok());
+    assert_eq!(report.files, 4);
+    assert_eq!(report.lines, 2499);
+    assert_eq!(report.invalid_files, 3);
+    assert_eq!(report.unreadable.len(), 1);
+    assert_eq!(report.unreadable[0].0, name("binary.json"));
+
+    let found: Vec<(String, usize, usize)> = report.diagnostics.iter()
+        .map(|diagnostic| (diagnostic.file.clone(), diagnostic.line, diagnostic.column))
+        .collect();
+    assert_eq!(found, vec![(name("invalid.json"), 1, 10), (name("lines.ndjson"), 1201, 7), (name("lines.ndjson"), 2500, 4)]);
+
+    // Spans and fixes point into the whole file
+    let contents = fs::read_to_string(&files[2]).unwrap();
+    let leading_zero = &report.diagnostics[1];
+    assert_eq!(&contents[leading_zero.span.start..leading_zero.span.end + 1], "01");
+    assert_eq!(leading_zero.fix.as_ref().unwrap().span, Span { start: leading_zero.span.start, end: leading_zero.span.end });
+
+    // Scheduling does not change the report
+    let serial = check_files(&files, &BatchOptions { jobs: 1, ..BatchOptions::default() });
+    assert_eq!(serial.diagnostics, report.diagnostics);
+    assert_eq!(serial.lines, report.lines);
+
+    let forced = check_files(&[files[0].clone()], &BatchOptions { ndjson: true, ..BatchOptions::default() });
+    assert!(forced.is_ok());
+    assert_eq!(forced.lines, 1);
+    assert!(check_files(&[], &BatchOptions::default()).is_ok());
+}
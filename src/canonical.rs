@@ -0,0 +1,154 @@
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::serializer::write_string;
+use crate::value::Value;
+
+/// Serializes a value in the JSON Canonicalization Scheme (RFC 8785): no
+/// whitespace, members sorted by the UTF-16 code units of their keys,
+/// numbers written the way ECMAScript does and minimal string escaping.
+/// Semantically equal documents canonicalize to the same text.
+pub fn canonicalize(value: &Value) -> Result<String, String> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), String> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(val) => out.push_str(if *val { "true" } else { "false" }),
+        Value::Number(num) => out.push_str(&format_number(num.as_f64())?),
+        Value::String(val) => write_string(out, val),
+        Value::Array(items) => {
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        },
+        Value::Object(members) => {
+            // The last of duplicate keys wins, like `Value::get`
+            let mut members: Vec<&(String, Value)> = members.iter().enumerate()
+                .filter(|(idx, (key, _))| !members[idx + 1..].iter().any(|(other, _)| other == key))
+                .map(|(_, member)| member)
+                .collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (idx, (key, val)) in members.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, val)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Formats a number like ECMAScript's `Number.prototype.toString`, using
+/// the shortest digits that read back as the same double.
+pub fn format_number(val: f64) -> Result<String, String> {
+    if !val.is_finite() {
+        return Err(format!("number {} can not be represented in canonical JSON", val));
+    }
+    if val == 0.0 {
+        return Ok("0".to_string());
+    }
+
+    let (digits, n) = shortest_digits(val.abs());
+    let k = digits.len() as i32;
+
+    let mut out = String::new();
+    if val < 0.0 {
+        out.push('-');
+    }
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push_str(&format!("e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs()));
+    }
+
+    Ok(out)
+}
+
+// The shortest digits that read back as `val`, and the position of the
+// decimal point relative to them
+fn shortest_digits(val: f64) -> (String, i32) {
+    let split = |sci: String| {
+        let (mantissa, exponent) = sci.split_once('e').unwrap();
+        let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+        (digits, exponent.parse::<i32>().unwrap() + 1)
+    };
+    let (digits, n) = split(format!("{:e}", val));
+
+    // When `val` lies exactly halfway between two candidates ECMAScript
+    // picks the even one, Rust may pick either
+    let (exact, _) = split(format!("{:.800e}", val));
+    let exact = exact.trim_end_matches('0');
+    if exact.len() == digits.len() + 1 && exact.ends_with('5') && digits.ends_with(['1', '3', '5', '7', '9']) {
+        let other = if exact[..digits.len()] == digits {
+            increment(&digits)
+        } else {
+            Some(exact[..digits.len()].to_string())
+        };
+        if let Some(other) = other.filter(|other| other.len() == digits.len()) {
+            let text = format!("{}e{}", other, n - other.len() as i32);
+            if text.parse::<f64>() == Ok(val) {
+                return (other, n);
+            }
+        }
+    }
+
+    (digits, n)
+}
+
+fn increment(digits: &str) -> Option<String> {
+    let mut out: Vec<u8> = digits.bytes().collect();
+    for digit in out.iter_mut().rev() {
+        if *digit == b'9' {
+            *digit = b'0';
+        } else {
+            *digit += 1;
+            return String::from_utf8(out).ok();
+        }
+    }
+    None
+}
+
+/// Hashes the canonical form of a value, returning the digest as hex.
+pub fn digest(value: &Value, algo: &str) -> Result<String, String> {
+    let canonical = canonicalize(value)?;
+    let bytes = match algo {
+        "sha256" => Sha256::digest(canonical.as_bytes()).to_vec(),
+        "sha384" => Sha384::digest(canonical.as_bytes()).to_vec(),
+        "sha512" => Sha512::digest(canonical.as_bytes()).to_vec(),
+        _ => return Err(format!("unknown hash algorithm \"{}\", expected one of: sha256, sha384, sha512", algo))
+    };
+
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use crate::canonical::{canonicalize, digest, format_number};
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+#[test]
+fn test_format_number() {
+    // Samples from RFC 8785 appendix B
+    let data_table = [
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2")
+    ];
+
+    for (bits, expected) in data_table.iter() {
+        assert_eq!(format_number(f64::from_bits(*bits)).unwrap(), *expected, "bits {:x}", bits);
+    }
+    assert!(format_number(f64::INFINITY).is_err());
+}
+
+#[test]
+fn test_canonicalize() {
+    // The example from RFC 8785 section 3.2.2
+    let value = parse("{
+        \"numbers\": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+        \"string\": \"\\u20ac$\\u000F\\u000aA'\\u0042\\u0022\\u005c\\\\\\\"\\/\",
+        \"literals\": [null, true, false]
+    }");
+
+    assert_eq!(canonicalize(&value).unwrap(), concat!(
+        "{\"literals\":[null,true,false],",
+        "\"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],",
+        "\"string\":\"€$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}"
+    ));
+}
+
+#[test]
+fn test_canonicalize_sorts_utf16() {
+    // The sorting example from RFC 8785 section 3.2.3
+    let value = parse("{
+        \"\\u20ac\": \"Euro Sign\",
+        \"\\r\": \"Carriage Return\",
+        \"\\ufb33\": \"Hebrew Letter Dalet With Dagesh\",
+        \"1\": \"One\",
+        \"\\ud83d\\ude00\": \"Emoji: Grinning Face\",
+        \"\\u0080\": \"Control\",
+        \"\\u00f6\": \"Latin Small Letter O With Diaeresis\"
+    }");
+
+    let keys: Vec<String> = match parse(&canonicalize(&value).unwrap()) {
+        Value::Object(members) => members.into_iter().map(|(key, _)| key).collect(),
+        _ => panic!("expected an object")
+    };
+    assert_eq!(keys, ["\r", "1", "\u{80}", "\u{f6}", "\u{20ac}", "\u{1f600}", "\u{fb33}"]);
+}
+
+#[test]
+fn test_digest() {
+    let a = parse("{\"b\": [1.0, 2e0], \"a\": \"x\"}");
+    let b = parse("{ \"a\" : \"x\", \"b\" : [1, 2] }");
+
+    assert_eq!(canonicalize(&a).unwrap(), "{\"a\":\"x\",\"b\":[1,2]}");
+    assert_eq!(digest(&a, "sha256"), digest(&b, "sha256"));
+    assert_eq!(digest(&parse("{}"), "sha256").unwrap(),
+               "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a");
+    assert_eq!(digest(&a, "sha512").unwrap().len(), 128);
+    assert!(digest(&a, "md5").is_err());
+}
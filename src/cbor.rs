@@ -0,0 +1,412 @@
+use crate::value::{Number, Value};
+
+// CBOR major types (RFC 8949 section 3.1)
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+const TAG_POSITIVE_BIGNUM: u64 = 2;
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+const TAG_DECIMAL_FRACTION: u64 = 4;
+
+/// Encodes a value as CBOR (RFC 8949). Integers stay integers, beyond 64 bits
+/// as bignums, and floats use the smallest width that holds them exactly.
+/// Numbers a double can not hold exactly become decimal fractions.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, val: u64) {
+    let major = major << 5;
+    if val < 24 {
+        out.push(major | val as u8);
+    } else if val <= u8::MAX as u64 {
+        out.push(major | 24);
+        out.push(val as u8);
+    } else if val <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(val as u16).to_be_bytes());
+    } else if val <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(val as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&val.to_be_bytes());
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(SIMPLE << 5 | 22),
+        Value::Bool(val) => out.push(SIMPLE << 5 | if *val { 21 } else { 20 }),
+        Value::Number(num) => write_number(out, num),
+        Value::String(val) => {
+            write_head(out, TEXT, val.len() as u64);
+            out.extend_from_slice(val.as_bytes());
+        },
+        Value::Array(items) => {
+            write_head(out, ARRAY, items.len() as u64);
+            for item in items {
+                write_value(out, item);
+            }
+        },
+        Value::Object(members) => {
+            write_head(out, MAP, members.len() as u64);
+            for (key, val) in members {
+                write_head(out, TEXT, key.len() as u64);
+                out.extend_from_slice(key.as_bytes());
+                write_value(out, val);
+            }
+        }
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, num: &Number) {
+    if num.is_integer() {
+        let text = num.as_str();
+        return write_integer(out, text.starts_with('-'), text.trim_start_matches('-'));
+    }
+
+    let (negative, digits, exponent) = decimal(num.as_str());
+    if exact_f64(num.as_f64(), negative, &digits, exponent) {
+        write_float(out, num.as_f64());
+    } else {
+        write_head(out, TAG, TAG_DECIMAL_FRACTION);
+        write_head(out, ARRAY, 2);
+        match exponent {
+            exp if exp < 0 => write_head(out, NEGATIVE, (-1 - exp) as u64),
+            exp => write_head(out, UNSIGNED, exp as u64)
+        }
+        write_integer(out, negative, &digits);
+    }
+}
+
+// Negative integers are stored as -1 - n, bignums as big-endian magnitudes
+fn write_integer(out: &mut Vec<u8>, negative: bool, digits: &str) {
+    let magnitude = match digits.parse::<u64>() {
+        Ok(val) if !negative => return write_head(out, UNSIGNED, val),
+        Ok(val) if val > 0 => return write_head(out, NEGATIVE, val - 1),
+        Ok(_) => return write_head(out, UNSIGNED, 0),
+        Err(_) => to_bytes(digits)
+    };
+
+    let magnitude = if negative { decrement(magnitude) } else { magnitude };
+    write_head(out, TAG, if negative { TAG_NEGATIVE_BIGNUM } else { TAG_POSITIVE_BIGNUM });
+    write_head(out, BYTES, magnitude.len() as u64);
+    out.extend_from_slice(&magnitude);
+}
+
+fn write_float(out: &mut Vec<u8>, val: f64) {
+    if val as f32 as f64 == val {
+        out.push(SIMPLE << 5 | 26);
+        out.extend_from_slice(&(val as f32).to_be_bytes());
+    } else {
+        out.push(SIMPLE << 5 | 27);
+        out.extend_from_slice(&val.to_be_bytes());
+    }
+}
+
+/// Splits number text into its sign, significant digits and the power of ten
+/// they are scaled by, so `-12.50e1` becomes `(true, "125", 0)`.
+fn decimal(text: &str) -> (bool, String, i64) {
+    let negative = text.starts_with('-');
+    let text = text.trim_start_matches(['-', '+']);
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(idx) => (&text[..idx], text[idx + 1..].parse::<i64>().unwrap_or(0)),
+        None => (text, 0)
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let mut digits = format!("{}{}", integer, fraction).trim_start_matches('0').to_string();
+    let mut exponent = exponent - fraction.len() as i64;
+    while digits.ends_with('0') {
+        digits.pop();
+        exponent += 1;
+    }
+    if digits.is_empty() {
+        return (negative, "0".to_string(), 0);
+    }
+    (negative, digits, exponent)
+}
+
+// Rust prints the shortest digits that read back as the same double, if
+// those match the text then the double lost nothing
+fn exact_f64(val: f64, negative: bool, digits: &str, exponent: i64) -> bool {
+    if val == 0.0 {
+        return digits == "0";
+    }
+    val.is_finite() && decimal(&format!("{:e}", val)) == (negative, digits.to_string(), exponent)
+}
+
+fn to_bytes(digits: &str) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for digit in digits.bytes() {
+        let mut carry = (digit - b'0') as u32;
+        for byte in bytes.iter_mut().rev() {
+            let val = *byte as u32 * 10 + carry;
+            *byte = val as u8;
+            carry = val >> 8;
+        }
+        if carry > 0 {
+            bytes.insert(0, carry as u8);
+        }
+    }
+    bytes
+}
+
+fn to_digits(bytes: &[u8]) -> String {
+    let mut bytes: Vec<u8> = bytes.iter().copied().skip_while(|byte| *byte == 0).collect();
+    let mut digits = Vec::new();
+    while !bytes.is_empty() {
+        let mut remainder = 0u32;
+        for byte in bytes.iter_mut() {
+            let val = remainder << 8 | *byte as u32;
+            *byte = (val / 10) as u8;
+            remainder = val % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        bytes = bytes.into_iter().skip_while(|byte| *byte == 0).collect();
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+fn decrement(mut bytes: Vec<u8>) -> Vec<u8> {
+    for byte in bytes.iter_mut().rev() {
+        let (val, borrow) = byte.overflowing_sub(1);
+        *byte = val;
+        if !borrow {
+            break;
+        }
+    }
+    bytes.into_iter().skip_while(|byte| *byte == 0).collect()
+}
+
+fn increment(mut bytes: Vec<u8>) -> Vec<u8> {
+    for byte in bytes.iter_mut().rev() {
+        let (val, carry) = byte.overflowing_add(1);
+        *byte = val;
+        if !carry {
+            return bytes;
+        }
+    }
+    bytes.insert(0, 1);
+    bytes
+}
+
+// Deeper input is rejected before it can overflow the stack
+const MAX_DEPTH: usize = 256;
+
+/// Decodes a single CBOR data item into a value. Byte strings become arrays
+/// of numbers and unknown tags are dropped in favour of the tagged item.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut decoder = Decoder { bytes, pos: 0, depth: 0 };
+    let value = decoder.value()?;
+    if decoder.pos < bytes.len() {
+        return Err(format!("unexpected data after the end of the item at byte {}", decoder.pos));
+    }
+    Ok(value)
+}
+
+enum Item {
+    Value(Value),
+    Break
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    // Items being read, arrays, maps and tags nest
+    depth: usize
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.pos < len {
+            return Err(format!("unexpected end of input at byte {}", self.bytes.len()));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn uint(&mut self, len: usize) -> Result<u64, String> {
+        Ok(self.take(len)?.iter().fold(0, |acc, byte| acc << 8 | *byte as u64))
+    }
+
+    // The argument of a head, None for indefinite lengths
+    fn argument(&mut self, info: u8) -> Result<Option<u64>, String> {
+        match info {
+            0..=23 => Ok(Some(info as u64)),
+            24 => self.uint(1).map(Some),
+            25 => self.uint(2).map(Some),
+            26 => self.uint(4).map(Some),
+            27 => self.uint(8).map(Some),
+            31 => Ok(None),
+            _ => Err(format!("reserved additional information {} at byte {}", info, self.pos - 1))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        match self.item()? {
+            Item::Value(value) => Ok(value),
+            Item::Break => Err(format!("unexpected break at byte {}", start))
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nesting too deep at byte {}", self.pos));
+        }
+        self.depth += 1;
+        let item = self.read_item();
+        self.depth -= 1;
+        item
+    }
+
+    fn read_item(&mut self) -> Result<Item, String> {
+        let start = self.pos;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let argument = self.argument(info)?;
+
+        let value = match (major, argument) {
+            (UNSIGNED, Some(val)) => Value::Number(Number::new(val.to_string())),
+            (NEGATIVE, Some(val)) => Value::Number(Number::new((-1 - val as i128).to_string())),
+            (BYTES, _) => Value::Array(self.bytes(major, argument)?.into_iter()
+                .map(|byte| Value::from(byte as i64))
+                .collect()),
+            (TEXT, _) => {
+                let bytes = self.bytes(major, argument)?;
+                Value::String(String::from_utf8(bytes).map_err(|_| format!("invalid UTF-8 in text string at byte {}", start))?)
+            },
+            (ARRAY, len) => {
+                let mut items = Vec::new();
+                while len.is_none_or(|len| (items.len() as u64) < len) {
+                    match self.item()? {
+                        Item::Value(item) => items.push(item),
+                        Item::Break if len.is_none() => break,
+                        Item::Break => return Err(format!("unexpected break in array at byte {}", self.pos - 1))
+                    }
+                }
+                Value::Array(items)
+            },
+            (MAP, len) => {
+                let mut members = Vec::new();
+                while len.is_none_or(|len| (members.len() as u64) < len) {
+                    let key = match self.item()? {
+                        Item::Value(key) => key,
+                        Item::Break if len.is_none() => break,
+                        Item::Break => return Err(format!("unexpected break in map at byte {}", self.pos - 1))
+                    };
+                    // JSON keys are strings, scalar keys are written out as text
+                    let key = match key {
+                        Value::String(key) => key,
+                        Value::Number(num) => num.to_string(),
+                        Value::Bool(val) => val.to_string(),
+                        Value::Null => "null".to_string(),
+                        _ => return Err(format!("map keys must be scalars at byte {}", start))
+                    };
+                    members.push((key, self.value()?));
+                }
+                Value::Object(members)
+            },
+            (TAG, Some(tag)) => self.tagged(tag)?,
+            (SIMPLE, _) => match info {
+                20 => Value::Bool(false),
+                21 => Value::Bool(true),
+                22 | 23 => Value::Null,
+                25 => float(half(argument.unwrap_or(0) as u16), start)?,
+                26 => float(f32::from_bits(argument.unwrap_or(0) as u32) as f64, start)?,
+                27 => float(f64::from_bits(argument.unwrap_or(0)), start)?,
+                31 => return Ok(Item::Break),
+                _ => return Err(format!("unsupported simple value at byte {}", start))
+            },
+            _ => return Err(format!("indefinite length not allowed at byte {}", start))
+        };
+
+        Ok(Item::Value(value))
+    }
+
+    // Definite strings are read whole, indefinite ones as a run of chunks
+    fn bytes(&mut self, major: u8, len: Option<u64>) -> Result<Vec<u8>, String> {
+        match len {
+            Some(len) => Ok(self.take(len as usize)?.to_vec()),
+            None => {
+                let mut bytes = Vec::new();
+                loop {
+                    let start = self.pos;
+                    let initial = self.take(1)?[0];
+                    if initial == SIMPLE << 5 | 31 {
+                        return Ok(bytes);
+                    }
+                    match (initial >> 5, self.argument(initial & 0x1f)?) {
+                        (chunk, Some(len)) if chunk == major => bytes.extend_from_slice(self.take(len as usize)?),
+                        _ => return Err(format!("invalid chunk in indefinite length string at byte {}", start))
+                    }
+                }
+            }
+        }
+    }
+
+    fn tagged(&mut self, tag: u64) -> Result<Value, String> {
+        let start = self.pos;
+        match tag {
+            TAG_POSITIVE_BIGNUM | TAG_NEGATIVE_BIGNUM => {
+                let initial = self.take(1)?[0];
+                let len = self.argument(initial & 0x1f)?;
+                if initial >> 5 != BYTES {
+                    return Err(format!("bignum must be a byte string at byte {}", start));
+                }
+                let magnitude = self.bytes(BYTES, len)?;
+                Ok(Value::Number(Number::new(match tag {
+                    TAG_POSITIVE_BIGNUM => to_digits(&magnitude),
+                    _ => format!("-{}", to_digits(&increment(magnitude)))
+                })))
+            },
+            TAG_DECIMAL_FRACTION => match self.value()? {
+                Value::Array(parts) => match parts.as_slice() {
+                    [Value::Number(exponent), Value::Number(mantissa)] if exponent.is_integer() && mantissa.is_integer() =>
+                        Ok(Value::Number(Number::new(format!("{}e{}", mantissa, exponent)))),
+                    _ => Err(format!("decimal fraction must hold two integers at byte {}", start))
+                },
+                _ => Err(format!("decimal fraction must be an array at byte {}", start))
+            },
+            _ => self.value()
+        }
+    }
+}
+
+fn float(val: f64, start: usize) -> Result<Value, String> {
+    if val.is_finite() {
+        Ok(Value::Number(Number::new(format!("{:?}", val))))
+    } else {
+        Err(format!("{} can not be represented in JSON at byte {}", val, start))
+    }
+}
+
+fn half(bits: u16) -> f64 {
+    let exponent = (bits >> 10 & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(exponent - 25)
+    };
+    if bits >> 15 == 1 { -magnitude } else { magnitude }
+}
+
+#[cfg(test)]
+mod tests;
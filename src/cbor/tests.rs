@@ -0,0 +1,80 @@
+use crate::cbor::{decode, encode};
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn value(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+fn compact(value: &Value) -> String {
+    Serializer::compact().serialize(value)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Vec<u8> {
+    (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_encode() {
+    // Examples from RFC 8949 appendix A
+    let cases = [
+        ("0", "00"), ("23", "17"), ("24", "1818"), ("1000", "1903e8"), ("1000000000000", "1b000000e8d4a51000"),
+        ("18446744073709551615", "1bffffffffffffffff"), ("18446744073709551616", "c249010000000000000000"),
+        ("-1", "20"), ("-1000", "3903e7"), ("-18446744073709551617", "c349010000000000000000"),
+        ("1.5", "fa3fc00000"), ("100000.0", "fa47c35000"), ("1.1", "fb3ff199999999999a"), ("-4.1", "fbc010666666666666"),
+        ("false", "f4"), ("true", "f5"), ("null", "f6"), ("\"IETF\"", "6449455446"), ("\"\\u00fc\"", "62c3bc"),
+        ("[1, [2, 3], [4, 5]]", "8301820203820405"), ("{\"a\": 1, \"b\": [2, 3]}", "a26161016162820203")
+    ];
+    for (json, expected) in cases {
+        assert_eq!(hex(&encode(&value(json))), expected, "{}", json);
+    }
+}
+
+#[test]
+fn test_decimal_fraction() {
+    let encoded = encode(&value("0.10000000000000000000001"));
+    assert_eq!(hex(&encoded[..3]), "c48236");
+    assert_eq!(decode(&encoded).unwrap(), value("10000000000000000000001e-23"));
+    assert_eq!(compact(&decode(&unhex("c48221196ab3")).unwrap()), "27315e-2");
+}
+
+#[test]
+fn test_round_trip() {
+    let doc = value(&std::fs::read_to_string("testdata.json").unwrap());
+    assert_eq!(decode(&encode(&doc)).unwrap(), doc);
+
+    let numbers = value("[1, 1.0, -0.5, 1e300, 123456789012345678901234567890, -18446744073709551616]");
+    let decoded = decode(&encode(&numbers)).unwrap();
+    assert_eq!(compact(&decoded), "[1,1.0,-0.5,1e300,123456789012345678901234567890,-18446744073709551616]");
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(decode(&unhex("f93c00")).unwrap(), value("1.0"));
+    assert_eq!(compact(&decode(&unhex("f90001")).unwrap()), "5.960464477539063e-8");
+    assert_eq!(decode(&unhex("9f018202039f0405ffff")).unwrap(), value("[1, [2, 3], [4, 5]]"));
+    assert_eq!(decode(&unhex("7f657374726561646d696e67ff")).unwrap(), value("\"streaming\""));
+    assert_eq!(decode(&unhex("a201020304")).unwrap(), value("{\"1\": 2, \"3\": 4}"));
+    assert_eq!(decode(&unhex("c074323031332d30332d32315432303a30343a30305a")).unwrap(), value("\"2013-03-21T20:04:00Z\""));
+    assert_eq!(decode(&unhex("4401020304")).unwrap(), value("[1, 2, 3, 4]"));
+}
+
+#[test]
+fn test_decode_errors() {
+    assert_eq!(decode(&unhex("8301")).unwrap_err(), "unexpected end of input at byte 2");
+    assert_eq!(decode(&unhex("0102")).unwrap_err(), "unexpected data after the end of the item at byte 1");
+    assert_eq!(decode(&unhex("f97e00")).unwrap_err(), "NaN can not be represented in JSON at byte 0");
+    assert_eq!(decode(&unhex("ff")).unwrap_err(), "unexpected break at byte 0");
+    assert_eq!(decode(&unhex("1c")).unwrap_err(), "reserved additional information 28 at byte 0");
+    assert!(decode(&unhex("a18101f6")).is_err());
+    let mut nested = vec![0x81; 255];
+    nested.push(0xf6);
+    assert!(decode(&nested).is_ok());
+    assert_eq!(decode(&[0x81; 200_000]).unwrap_err(), "nesting too deep at byte 256");
+}
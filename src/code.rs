@@ -0,0 +1,249 @@
+use std::fmt;
+
+/// A stable identifier for each kind of lexer and parser failure. Messages
+/// may be reworded, codes never change meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCode {
+    UnterminatedString,
+    MultilineString,
+    UnterminatedComment,
+    UnexpectedCharacter,
+    UnknownKeyword,
+    ByteOrderMark,
+    UnexpectedToken,
+    MismatchedToken,
+    TrailingData,
+    LeadingZero,
+    InvalidEscape,
+    InvalidUnicodeEscape
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 12] = [
+        ErrorCode::UnterminatedString,
+        ErrorCode::MultilineString,
+        ErrorCode::UnterminatedComment,
+        ErrorCode::UnexpectedCharacter,
+        ErrorCode::UnknownKeyword,
+        ErrorCode::ByteOrderMark,
+        ErrorCode::UnexpectedToken,
+        ErrorCode::MismatchedToken,
+        ErrorCode::TrailingData,
+        ErrorCode::LeadingZero,
+        ErrorCode::InvalidEscape,
+        ErrorCode::InvalidUnicodeEscape
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnterminatedString => "E0001",
+            ErrorCode::MultilineString => "E0002",
+            ErrorCode::UnterminatedComment => "E0003",
+            ErrorCode::UnexpectedCharacter => "E0004",
+            ErrorCode::UnknownKeyword => "E0005",
+            ErrorCode::ByteOrderMark => "E0006",
+            ErrorCode::UnexpectedToken => "E0007",
+            ErrorCode::MismatchedToken => "E0008",
+            ErrorCode::TrailingData => "E0009",
+            ErrorCode::LeadingZero => "E0010",
+            ErrorCode::InvalidEscape => "E0011",
+            ErrorCode::InvalidUnicodeEscape => "E0012"
+        }
+    }
+
+    /// Looks up a code like `E0001`, ignoring case.
+    pub fn parse(code: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL.iter().copied().find(|known| known.as_str().eq_ignore_ascii_case(code))
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ErrorCode::UnterminatedString => "unterminated string",
+            ErrorCode::MultilineString => "line break in string",
+            ErrorCode::UnterminatedComment => "unterminated comment",
+            ErrorCode::UnexpectedCharacter => "unexpected character",
+            ErrorCode::UnknownKeyword => "unknown keyword",
+            ErrorCode::ByteOrderMark => "byte order mark",
+            ErrorCode::UnexpectedToken => "expected a value",
+            ErrorCode::MismatchedToken => "unexpected token",
+            ErrorCode::TrailingData => "data after the document",
+            ErrorCode::LeadingZero => "leading zero in number",
+            ErrorCode::InvalidEscape => "invalid escape sequence",
+            ErrorCode::InvalidUnicodeEscape => "invalid unicode escape"
+        }
+    }
+
+    /// What the error means, why JSON rejects it and how to fix it, with
+    /// examples.
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            ErrorCode::UnterminatedString => "\
+A string was opened with a double quote that is never closed before the end
+of the input.
+
+Erroneous example:
+
+    {\"name\": \"jsorry}
+
+Close the string with a double quote:
+
+    {\"name\": \"jsorry\"}
+
+A backslash right before the closing quote escapes it, so `\"C:\\\"` is still
+open. Write the backslash twice, `\"C:\\\\\"`.",
+            ErrorCode::MultilineString => "\
+A string contains a raw line break. JSON strings must fit on a single line.
+
+Erroneous example:
+
+    {\"text\": \"first line
+    second line\"}
+
+Write the line break as the escape `\\n`:
+
+    {\"text\": \"first line\\nsecond line\"}",
+            ErrorCode::UnterminatedComment => "\
+A `/*` block comment is never closed with `*/`.
+
+Erroneous example:
+
+    [1, 2] /* the end
+
+JSON has no comments at all, so the best fix is to remove it:
+
+    [1, 2]
+
+Comments are accepted by some jsorry commands that keep formatting, but they
+still have to be closed.",
+            ErrorCode::UnexpectedCharacter => "\
+A character that can not start any JSON token was found, such as a single
+quote, a stray slash or a symbol outside of a string.
+
+Erroneous examples:
+
+    {'name': 'jsorry'}
+    [1, 2] / 3
+    {\"a\": @b}
+
+JSON strings use double quotes and everything else has to be a number, an
+object, an array, `true`, `false` or `null`:
+
+    {\"name\": \"jsorry\"}
+    {\"a\": \"@b\"}",
+            ErrorCode::UnknownKeyword => "\
+A bare word was found that is not one of JSON's keywords `true`, `false` and
+`null`. Keywords are case sensitive and strings must be quoted.
+
+Erroneous examples:
+
+    {\"ok\": True, \"value\": None}
+    {name: \"jsorry\"}
+
+Use the lowercase keywords and put quotes around keys and strings:
+
+    {\"ok\": true, \"value\": null}
+    {\"name\": \"jsorry\"}",
+            ErrorCode::ByteOrderMark => "\
+The input starts with a UTF-8 byte order mark (U+FEFF). JSON text exchanged
+between systems must not begin with one, even though some editors on Windows
+add it when saving.
+
+Remove the mark, which `jsorry validate --fix` does safely. It is invisible in
+most editors, so check the first bytes with `xxd file.json | head -1`, they
+read `efbb bf` when the mark is there.",
+            ErrorCode::UnexpectedToken => "\
+A value was expected, but something else was found, or the input ended.
+This is what trailing commas and missing values look like.
+
+Erroneous examples:
+
+    [1, 2, ]
+    {\"a\": }
+    {\"a\": 1,}
+
+Remove the trailing comma or fill in the value:
+
+    [1, 2]
+    {\"a\": null}
+    {\"a\": 1}",
+            ErrorCode::MismatchedToken => "\
+A specific token was required, such as a comma, a colon, a closing bracket or
+a quoted key, but a different one was found.
+
+Erroneous examples:
+
+    [1 2]
+    {\"a\" 1}
+    {\"a\": [1, 2}
+
+Separate elements and members with commas, follow keys with a colon and close
+every bracket with its matching one:
+
+    [1, 2]
+    {\"a\": 1}
+    {\"a\": [1, 2]}",
+            ErrorCode::TrailingData => "\
+The document is complete, but more text follows it. A JSON text holds exactly
+one value.
+
+Erroneous examples:
+
+    {\"a\": 1} {\"b\": 2}
+    [1, 2]]
+
+Wrap several values in an array, or remove the extra text:
+
+    [{\"a\": 1}, {\"b\": 2}]
+    [1, 2]
+
+Files with one document per line are newline delimited JSON, which has to be
+read line by line.",
+            ErrorCode::LeadingZero => "\
+A number starts with a zero followed by more digits. JSON does not allow
+leading zeros, partly because other languages read them as octal.
+
+Erroneous example:
+
+    {\"zip\": 01234, \"count\": 007}
+
+Remove the zeros, or keep the digits as a string when they are an identifier
+rather than a quantity:
+
+    {\"zip\": \"01234\", \"count\": 7}
+
+`jsorry validate --fix` removes leading zeros from numbers.",
+            ErrorCode::InvalidEscape => "\
+A backslash in a string is followed by a character that is not a JSON escape.
+The escapes are `\\\"`, `\\\\`, `\\/`, `\\b`, `\\f`, `\\n`, `\\r`, `\\t` and `\\uXXXX`.
+
+Erroneous example:
+
+    {\"path\": \"C:\\Users\\me\"}
+
+Escape the backslash itself:
+
+    {\"path\": \"C:\\\\Users\\\\me\"}",
+            ErrorCode::InvalidUnicodeEscape => "\
+A `\\u` escape is not followed by exactly four hexadecimal digits.
+
+Erroneous examples:
+
+    [\"\\u41\"]
+    [\"\\u{1F600}\"]
+
+Write four digits, and characters outside the basic plane as a surrogate pair:
+
+    [\"\\u0041\"]
+    [\"\\ud83d\\ude00\"]"
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+
+use crate::code::ErrorCode;
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+
+fn code(data: &str) -> ErrorCode {
+    Parser::checked(Box::new(TokenIter::new(data).checked())).try_parse().unwrap_err().code
+}
+
+#[test]
+fn test_codes_are_unique() {
+    let codes: HashSet<&str> = ErrorCode::ALL.iter().map(ErrorCode::as_str).collect();
+    assert_eq!(codes.len(), ErrorCode::ALL.len());
+
+    for code in ErrorCode::ALL {
+        assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+        assert!(!code.explanation().is_empty());
+    }
+    assert_eq!(ErrorCode::parse("e0001"), Some(ErrorCode::UnterminatedString));
+    assert_eq!(ErrorCode::parse("E9999"), None);
+    assert_eq!(ErrorCode::LeadingZero.to_string(), "E0010");
+}
+
+#[test]
+fn test_lexer_codes() {
+    assert_eq!(code("{\"a\": \"open"), ErrorCode::UnterminatedString);
+    assert_eq!(code("[\"C:\\\"]"), ErrorCode::UnterminatedString);
+    assert_eq!(code("[\"two\nlines\"]"), ErrorCode::MultilineString);
+    assert_eq!(code("[1] /* open"), ErrorCode::UnterminatedComment);
+    assert_eq!(code("{'a': 1}"), ErrorCode::UnexpectedCharacter);
+    assert_eq!(code("[1 / 2]"), ErrorCode::UnexpectedCharacter);
+    assert_eq!(code("{\"ok\": True}"), ErrorCode::UnknownKeyword);
+    assert_eq!(code("\u{feff}{}"), ErrorCode::ByteOrderMark);
+}
+
+#[test]
+fn test_parser_codes() {
+    assert_eq!(code("[1, 2, ]"), ErrorCode::UnexpectedToken);
+    assert_eq!(code(""), ErrorCode::UnexpectedToken);
+    assert_eq!(code("[1 2]"), ErrorCode::MismatchedToken);
+    assert_eq!(code("{\"a\" 1}"), ErrorCode::MismatchedToken);
+    assert_eq!(code("[-a]"), ErrorCode::UnknownKeyword);
+    assert_eq!(code("[-]"), ErrorCode::MismatchedToken);
+    assert_eq!(code("{} {}"), ErrorCode::TrailingData);
+    assert_eq!(code("[007]"), ErrorCode::LeadingZero);
+    assert_eq!(code("[\"\\x\"]"), ErrorCode::InvalidEscape);
+    assert_eq!(code("[\"\\u41\"]"), ErrorCode::InvalidUnicodeEscape);
+}
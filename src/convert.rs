@@ -0,0 +1,344 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::path::Path;
+use crate::value::{Number, Value};
+
+/// Text formats a value tree can be converted to and read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+    Cbor,
+    MessagePack
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            "csv" => Ok(Format::Csv),
+            "cbor" => Ok(Format::Cbor),
+            "msgpack" | "mpk" => Ok(Format::MessagePack),
+            _ => Err(format!("unknown format \"{}\", expected one of: json, yaml, toml, csv, cbor, msgpack", name))
+        }
+    }
+
+    /// Guesses the format from a file name, JSON unless the extension says otherwise.
+    pub fn from_path(file_path: &str) -> Format {
+        file_path.rsplit_once('.')
+            .and_then(|(_, ext)| Format::parse(&ext.to_lowercase()).ok())
+            .unwrap_or(Format::Json)
+    }
+
+    pub fn is_binary(self) -> bool {
+        matches!(self, Format::Cbor | Format::MessagePack)
+    }
+
+    pub fn read(self, contents: &[u8]) -> Result<Value, String> {
+        match self {
+            Format::Cbor => return crate::cbor::decode(contents),
+            Format::MessagePack => return crate::msgpack::decode(contents),
+            _ => {}
+        }
+
+        let contents = std::str::from_utf8(contents).map_err(|err| err.to_string())?;
+        match self {
+            Format::Yaml => from_yaml(contents),
+            Format::Toml => from_toml(contents),
+            Format::Csv => from_csv(contents),
+            _ => Parser::checked(Box::new(TokenIter::new(contents).checked())).try_parse().map_err(|err| err.to_string())
+        }
+    }
+
+    pub fn write(self, value: &Value) -> Result<Vec<u8>, String> {
+        match self {
+            Format::Json => Ok(crate::serializer::Serializer::new().serialize(value).into_bytes()),
+            Format::Yaml => to_yaml(value).map(String::into_bytes),
+            Format::Toml => to_toml(value).map(String::into_bytes),
+            Format::Csv => to_csv(value).map(String::into_bytes),
+            Format::Cbor => Ok(crate::cbor::encode(value)),
+            Format::MessagePack => crate::msgpack::encode(value)
+        }
+    }
+}
+
+fn float(val: f64, path: &Path) -> Result<Value, String> {
+    if val.is_finite() {
+        Ok(Value::Number(Number::from(val)))
+    } else {
+        Err(format!("{}: {} can not be represented in JSON", path, val))
+    }
+}
+
+pub fn to_yaml(value: &Value) -> Result<String, String> {
+    serde_yaml::to_string(&yaml_value(value)).map_err(|err| err.to_string())
+}
+
+fn yaml_value(value: &Value) -> serde_yaml::Value {
+    match value {
+        Value::Null => serde_yaml::Value::Null,
+        Value::Bool(val) => serde_yaml::Value::Bool(*val),
+        Value::Number(num) => match (num.as_i64(), num.as_str().parse::<u64>()) {
+            (Some(val), _) => serde_yaml::Value::Number(val.into()),
+            (None, Ok(val)) => serde_yaml::Value::Number(val.into()),
+            _ => serde_yaml::Value::Number(num.as_f64().into())
+        },
+        Value::String(val) => serde_yaml::Value::String(val.clone()),
+        Value::Array(items) => serde_yaml::Value::Sequence(items.iter().map(yaml_value).collect()),
+        Value::Object(members) => serde_yaml::Value::Mapping(members.iter()
+            .map(|(key, val)| (serde_yaml::Value::String(key.clone()), yaml_value(val)))
+            .collect())
+    }
+}
+
+pub fn from_yaml(contents: &str) -> Result<Value, String> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(contents).map_err(|err| err.to_string())?;
+    from_yaml_value(&yaml, &Path::root())
+}
+
+fn from_yaml_value(yaml: &serde_yaml::Value, path: &Path) -> Result<Value, String> {
+    match yaml {
+        serde_yaml::Value::Null => Ok(Value::Null),
+        serde_yaml::Value::Bool(val) => Ok(Value::Bool(*val)),
+        serde_yaml::Value::Number(num) => match (num.as_i64(), num.as_u64(), num.as_f64()) {
+            (Some(val), _, _) => Ok(Value::from(val)),
+            (None, Some(val), _) => Ok(Value::Number(Number::new(val.to_string()))),
+            (None, None, Some(val)) => float(val, path),
+            _ => Err(format!("{}: unsupported number {}", path, num))
+        },
+        serde_yaml::Value::String(val) => Ok(Value::from(val.as_str())),
+        serde_yaml::Value::Sequence(items) => items.iter().enumerate()
+            .map(|(idx, item)| from_yaml_value(item, &path.index(idx)))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut members = Vec::new();
+            for (key, val) in mapping {
+                // JSON keys are strings, scalar keys are written out as text
+                let key = match key {
+                    serde_yaml::Value::String(key) => key.clone(),
+                    serde_yaml::Value::Number(num) => num.to_string(),
+                    serde_yaml::Value::Bool(val) => val.to_string(),
+                    serde_yaml::Value::Null => "null".to_string(),
+                    _ => return Err(format!("{}: mapping keys must be scalars", path))
+                };
+                let val = from_yaml_value(val, &path.key(&key))?;
+                members.push((key, val));
+            }
+            Ok(Value::Object(members))
+        },
+        serde_yaml::Value::Tagged(tagged) => from_yaml_value(&tagged.value, path)
+    }
+}
+
+pub fn to_toml(value: &Value) -> Result<String, String> {
+    match toml_value(value, &Path::root())? {
+        toml::Value::Table(table) => toml::to_string(&table).map_err(|err| err.to_string()),
+        _ => Err("TOML documents must be objects".to_string())
+    }
+}
+
+fn toml_value(value: &Value, path: &Path) -> Result<toml::Value, String> {
+    match value {
+        Value::Null => Err(format!("{}: null can not be represented in TOML", path)),
+        Value::Bool(val) => Ok(toml::Value::Boolean(*val)),
+        Value::Number(num) => match num.as_i64() {
+            Some(val) if num.is_integer() => Ok(toml::Value::Integer(val)),
+            _ => Ok(toml::Value::Float(num.as_f64()))
+        },
+        Value::String(val) => Ok(toml::Value::String(val.clone())),
+        Value::Array(items) => items.iter().enumerate()
+            .map(|(idx, item)| toml_value(item, &path.index(idx)))
+            .collect::<Result<_, _>>()
+            .map(toml::Value::Array),
+        Value::Object(members) => {
+            let mut table = toml::map::Map::new();
+            for (key, val) in members {
+                table.insert(key.clone(), toml_value(val, &path.key(key))?);
+            }
+            Ok(toml::Value::Table(table))
+        }
+    }
+}
+
+pub fn from_toml(contents: &str) -> Result<Value, String> {
+    let table: toml::Table = contents.parse().map_err(|err: toml::de::Error| err.to_string())?;
+    from_toml_value(&toml::Value::Table(table), &Path::root())
+}
+
+fn from_toml_value(toml: &toml::Value, path: &Path) -> Result<Value, String> {
+    match toml {
+        toml::Value::String(val) => Ok(Value::from(val.as_str())),
+        toml::Value::Integer(val) => Ok(Value::from(*val)),
+        toml::Value::Float(val) => float(*val, path),
+        toml::Value::Boolean(val) => Ok(Value::Bool(*val)),
+        toml::Value::Datetime(val) => Ok(Value::String(val.to_string())),
+        toml::Value::Array(items) => items.iter().enumerate()
+            .map(|(idx, item)| from_toml_value(item, &path.index(idx)))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        toml::Value::Table(table) => table.iter()
+            .map(|(key, val)| Ok((key.clone(), from_toml_value(val, &path.key(key))?)))
+            .collect::<Result<_, String>>()
+            .map(Value::Object)
+    }
+}
+
+/// Writes an array of objects as CSV, one row per object. Nested values are
+/// flattened into columns with dotted headers, like `address.town` or
+/// `pets.0`, in the order they are first seen.
+pub fn to_csv(value: &Value) -> Result<String, String> {
+    let rows = match value {
+        Value::Array(rows) => rows,
+        _ => return Err("CSV needs an array of objects".to_string())
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    let mut cells = Vec::new();
+    for (idx, row) in rows.iter().enumerate() {
+        if !matches!(row, Value::Object(_)) {
+            return Err(format!("{}: CSV rows must be objects", Path::root().index(idx)));
+        }
+        let mut flat = Vec::new();
+        flatten(row, "", &mut flat);
+        for (key, _) in &flat {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        cells.push(flat);
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&headers).map_err(|err| err.to_string())?;
+    for flat in cells {
+        let record = headers.iter().map(|header| flat.iter()
+            .find(|(key, _)| key == header)
+            .map(|(_, cell)| cell.as_str())
+            .unwrap_or(""));
+        writer.write_record(record).map_err(|err| err.to_string())?;
+    }
+
+    let bytes = writer.into_inner().map_err(|err| err.to_string())?;
+    String::from_utf8(bytes).map_err(|err| err.to_string())
+}
+
+fn flatten(value: &Value, prefix: &str, out: &mut Vec<(String, String)>) {
+    let child = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+
+    match value {
+        Value::Object(members) if !members.is_empty() => {
+            for (key, val) in members {
+                flatten(val, &child(key), out);
+            }
+        },
+        Value::Array(items) if !items.is_empty() => {
+            for (idx, item) in items.iter().enumerate() {
+                flatten(item, &child(&idx.to_string()), out);
+            }
+        },
+        Value::Null => out.push((prefix.to_string(), "null".to_string())),
+        Value::String(val) => out.push((prefix.to_string(), val.clone())),
+        other => out.push((prefix.to_string(), crate::serializer::Serializer::compact().serialize(other)))
+    }
+}
+
+/// Reads CSV with a header row into an array of objects, the reverse of
+/// `to_csv`. Dotted headers nest, objects keyed `0` to `n - 1` become arrays
+/// and cells holding JSON literals or numbers are read as such, so `true`,
+/// `null` or `12` do not stay strings. Empty cells are left out.
+pub fn from_csv(contents: &str) -> Result<Value, String> {
+    read_csv(contents, cell_value)
+}
+
+/// Like `from_csv`, but every cell stays a string, for columns such as
+/// zip codes or free text where `12` or `null` are just text.
+pub fn from_csv_strings(contents: &str) -> Result<Value, String> {
+    read_csv(contents, |cell| Value::from(cell))
+}
+
+fn read_csv(contents: &str, cell_value: fn(&str) -> Value) -> Result<Value, String> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers = reader.headers().map_err(|err| err.to_string())?.clone();
+
+    let mut rows = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record.map_err(|err| err.to_string())?;
+        let mut row = Value::Object(Vec::new());
+        for (header, cell) in headers.iter().zip(record.iter()) {
+            if !cell.is_empty() {
+                insert_dotted(&mut row, header, cell_value(cell))
+                    .map_err(|err| format!("{}: {}", Path::root().index(idx), err))?;
+            }
+        }
+        rows.push(into_arrays(row));
+    }
+
+    Ok(Value::Array(rows))
+}
+
+fn cell_value(cell: &str) -> Value {
+    match cell {
+        "null" => return Value::Null,
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        "{}" => return Value::Object(Vec::new()),
+        "[]" => return Value::Array(Vec::new()),
+        _ => {}
+    }
+
+    if Number::is_valid(cell) {
+        Value::Number(Number::new(cell.to_string()))
+    } else {
+        Value::from(cell)
+    }
+}
+
+fn insert_dotted(row: &mut Value, header: &str, value: Value) -> Result<(), String> {
+    let mut curr = row;
+    let mut keys = header.split('.').peekable();
+    while let Some(key) = keys.next() {
+        if keys.peek().is_none() {
+            curr.insert(key, value);
+            return Ok(());
+        }
+        if curr.get(key).is_none() {
+            curr.insert(key, Value::Object(Vec::new()));
+        }
+        curr = match curr.get_mut(key) {
+            Some(next @ Value::Object(_)) => next,
+            _ => return Err(format!("column \"{}\" conflicts with a column holding a value at \"{}\"", header, key))
+        };
+    }
+    Ok(())
+}
+
+fn into_arrays(value: Value) -> Value {
+    match value {
+        Value::Object(members) => {
+            let members: Vec<(String, Value)> = members.into_iter()
+                .map(|(key, val)| (key, into_arrays(val)))
+                .collect();
+            let mut indices: Vec<usize> = members.iter()
+                .filter_map(|(key, _)| crate::pointer::array_index(key))
+                .collect();
+            indices.sort_unstable();
+
+            if !members.is_empty() && indices.len() == members.len() && indices.iter().enumerate().all(|(idx, val)| idx == *val) {
+                let mut members = members;
+                members.sort_by_key(|(key, _)| key.parse::<usize>().unwrap_or(0));
+                Value::Array(members.into_iter().map(|(_, val)| val).collect())
+            } else {
+                Value::Object(members)
+            }
+        },
+        other => other
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,86 @@
+use crate::convert::{from_csv, from_csv_strings, from_toml, from_yaml, to_csv, to_toml, to_yaml, Format};
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::value::Value;
+
+fn value(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+#[test]
+fn test_format() {
+    assert_eq!(Format::parse("yml"), Ok(Format::Yaml));
+    assert!(Format::parse("xml").is_err());
+    assert_eq!(Format::from_path("records.CSV"), Format::Csv);
+    assert_eq!(Format::from_path("config.toml"), Format::Toml);
+    assert_eq!(Format::from_path("no-extension"), Format::Json);
+}
+
+#[test]
+fn test_yaml() {
+    let doc = value("{\"name\": \"ops\", \"ports\": [80, 443], \"ratio\": 0.5, \"debug\": null, \"big\": 18446744073709551615}");
+    let yaml = to_yaml(&doc).unwrap();
+
+    assert_eq!(yaml, "name: ops\nports:\n- 80\n- 443\nratio: 0.5\ndebug: null\nbig: 18446744073709551615\n");
+    assert_eq!(from_yaml(&yaml).unwrap(), doc);
+    assert_eq!(from_yaml("1: yes\ntrue: [a, 'b']\n").unwrap(), value("{\"1\": \"yes\", \"true\": [\"a\", \"b\"]}"));
+    assert!(from_yaml("? [1]\n: a\n").is_err());
+    assert!(from_yaml("a: [.nan]").unwrap_err().starts_with("$.a[0]"));
+}
+
+#[test]
+fn test_toml() {
+    let doc = value("{\"title\": \"jsorry\", \"owner\": {\"name\": \"ops\", \"ids\": [1, 2]}, \"ratio\": 0.5}");
+    let toml = to_toml(&doc).unwrap();
+
+    assert_eq!(from_toml(&toml).unwrap(), doc);
+    assert_eq!(from_toml("when = 1979-05-27T07:32:00Z").unwrap(), value("{\"when\": \"1979-05-27T07:32:00Z\"}"));
+    assert_eq!(to_toml(&value("[1]")).unwrap_err(), "TOML documents must be objects");
+    assert_eq!(to_toml(&value("{\"a\": [1, null]}")).unwrap_err(), "$.a[1]: null can not be represented in TOML");
+}
+
+#[test]
+fn test_to_csv_testdata() {
+    let doc = value(&std::fs::read_to_string("testdata.json").unwrap());
+    let csv = to_csv(doc.get("ctRoot").unwrap()).unwrap();
+    let mut lines = csv.lines();
+
+    assert!(lines.next().unwrap().starts_with("_id,name,dob,address.street,address.town,address.postode,telephone,pets.0,pets.1,"));
+    assert!(lines.next().unwrap().starts_with("LB5YD3ASAF445UFC,Coletta Ortiz,2016-10-16,6240 Thornden Street,Godmanchester,RM68 3QP,"));
+    assert_eq!(from_csv(&csv).unwrap().index(0), doc.get("ctRoot").unwrap().index(0));
+}
+
+#[test]
+fn test_csv_round_trip() {
+    let rows = value("[
+        {\"id\": 1, \"tags\": [\"a\", \"b\"], \"meta\": {\"ok\": true, \"note\": \"x, \\\"y\\\"\"}},
+        {\"id\": 2, \"tags\": [], \"meta\": {\"ok\": false}, \"extra\": null}
+    ]");
+    let csv = to_csv(&rows).unwrap();
+
+    assert_eq!(csv, "id,tags.0,tags.1,meta.ok,meta.note,tags,extra\n1,a,b,true,\"x, \"\"y\"\"\",,\n2,,,false,,[],null\n");
+    assert_eq!(from_csv(&csv).unwrap(), rows);
+}
+
+#[test]
+fn test_from_csv_cells() {
+    assert_eq!(from_csv("zip,code,n\n007,1.,-1.5e3\n").unwrap(), value("[{\"zip\": \"007\", \"code\": \"1.\", \"n\": -1.5e3}]"));
+    assert_eq!(from_csv("a,a.b\n1,2\n").unwrap_err(), "$[0]: column \"a.b\" conflicts with a column holding a value at \"a\"");
+    assert_eq!(to_csv(&value("{\"a\": 1}")).unwrap_err(), "CSV needs an array of objects");
+    assert_eq!(to_csv(&value("[{}, 1]")).unwrap_err(), "$[1]: CSV rows must be objects");
+}
+
+#[test]
+fn test_from_csv_strings() {
+    let csv = "id,ok,note,tags.0\n12,true,null,\n";
+    assert_eq!(from_csv(csv).unwrap(), Value::Array(vec![Value::Object(vec![
+        ("id".to_string(), Value::from(12)),
+        ("ok".to_string(), Value::Bool(true)),
+        ("note".to_string(), Value::Null)
+    ])]));
+    assert_eq!(from_csv_strings(csv).unwrap(), Value::Array(vec![Value::Object(vec![
+        ("id".to_string(), Value::from("12")),
+        ("ok".to_string(), Value::from("true")),
+        ("note".to_string(), Value::from("null"))
+    ])]));
+}
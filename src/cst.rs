@@ -0,0 +1,277 @@
+use std::fmt;
+use std::iter::Peekable;
+
+use crate::lexer::{JsonKind, Token, TokenIter};
+use crate::parser::{decode_escapes, ParseError, Parser};
+use crate::pointer::{array_index, Pointer};
+use crate::serializer::Serializer;
+use crate::value::{Number, Value};
+
+/// A lossless syntax tree. Every token of the source is kept, including
+/// whitespace and comments, so printing the tree gives back the input byte
+/// for byte.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub root: Node
+}
+
+/// A value together with the trivia around it. Leading trivia sits between
+/// the previous token and the value, trailing trivia between the value and
+/// the following separator or closing bracket.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub leading: Vec<Token>,
+    pub kind: NodeKind,
+    pub trailing: Vec<Token>
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    /// A string, a literal or the tokens making up a number
+    Scalar(Vec<Token>),
+    /// `dangling` holds the trivia of an empty array
+    Array { open: Token, items: Vec<Element>, dangling: Vec<Token>, close: Token },
+    /// `dangling` holds the trivia of an empty object
+    Object { open: Token, members: Vec<Member>, dangling: Vec<Token>, close: Token }
+}
+
+#[derive(Debug, Clone)]
+pub struct Element {
+    pub value: Node,
+    pub comma: Option<Token>
+}
+
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub leading: Vec<Token>,
+    pub key: Token,
+    pub before_colon: Vec<Token>,
+    pub colon: Token,
+    pub value: Node,
+    pub comma: Option<Token>
+}
+
+impl Document {
+    /// Parses a document, allowing comments wherever whitespace may go.
+    pub fn parse(contents: &str) -> Document {
+        Document::try_parse(contents).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_parse(contents: &str) -> Result<Document, ParseError> {
+        // The strict parser does the validation, the builder can then trust
+        // the token stream to be well formed
+        let tokens = TokenIter::new(contents).checked()
+            .filter(|token| !matches!(token, Ok(token) if token.kind == JsonKind::Comment));
+        Parser::checked(Box::new(tokens)).try_validate()?;
+
+        let mut builder = Builder { tokens: TokenIter::new(contents).peekable() };
+        Ok(Document { root: builder.node() })
+    }
+
+    pub fn value(&self) -> Value {
+        self.root.value()
+    }
+
+    pub fn resolve(&self, pointer: &Pointer) -> Option<&Node> {
+        let mut curr = &self.root;
+        for token in pointer.tokens() {
+            curr = curr.child(token)?;
+        }
+        Some(curr)
+    }
+
+    pub fn resolve_mut(&mut self, pointer: &Pointer) -> Option<&mut Node> {
+        let mut curr = &mut self.root;
+        for token in pointer.tokens() {
+            curr = curr.child_mut(token)?;
+        }
+        Some(curr)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.root.write(&mut out);
+        f.write_str(&out)
+    }
+}
+
+impl Node {
+    pub fn value(&self) -> Value {
+        match &self.kind {
+            NodeKind::Scalar(tokens) => match tokens[0].kind {
+                JsonKind::StringVal => Value::String(string_value(&tokens[0].text)),
+                JsonKind::True => Value::Bool(true),
+                JsonKind::False => Value::Bool(false),
+                JsonKind::Null => Value::Null,
+                _ => Value::Number(Number::new(tokens.iter().map(|token| token.text.as_str()).collect()))
+            },
+            NodeKind::Array { items, .. } => Value::Array(items.iter().map(|item| item.value.value()).collect()),
+            NodeKind::Object { members, .. } => Value::Object(members.iter()
+                .map(|member| (member.name(), member.value.value()))
+                .collect())
+        }
+    }
+
+    /// The member value or array item named by a JSON pointer token. Like
+    /// `Value::get`, the last of duplicate keys wins.
+    pub fn child(&self, token: &str) -> Option<&Node> {
+        match &self.kind {
+            NodeKind::Object { members, .. } => members.iter().rev()
+                .find(|member| member.name() == token)
+                .map(|member| &member.value),
+            NodeKind::Array { items, .. } => items.get(array_index(token)?).map(|item| &item.value),
+            NodeKind::Scalar(_) => None
+        }
+    }
+
+    pub fn child_mut(&mut self, token: &str) -> Option<&mut Node> {
+        match &mut self.kind {
+            NodeKind::Object { members, .. } => members.iter_mut().rev()
+                .find(|member| member.name() == token)
+                .map(|member| &mut member.value),
+            NodeKind::Array { items, .. } => items.get_mut(array_index(token)?).map(|item| &mut item.value),
+            NodeKind::Scalar(_) => None
+        }
+    }
+
+    /// Replaces the value, keeping the trivia around it.
+    pub fn set(&mut self, value: &Value) {
+        self.kind = Document::parse(&Serializer::compact().serialize(value)).root.kind;
+    }
+
+    fn write(&self, out: &mut String) {
+        write_tokens(out, &self.leading);
+        match &self.kind {
+            NodeKind::Scalar(tokens) => write_tokens(out, tokens),
+            NodeKind::Array { open, items, dangling, close } => {
+                out.push_str(&open.text);
+                for item in items {
+                    item.value.write(out);
+                    if let Some(comma) = &item.comma {
+                        out.push_str(&comma.text);
+                    }
+                }
+                write_tokens(out, dangling);
+                out.push_str(&close.text);
+            },
+            NodeKind::Object { open, members, dangling, close } => {
+                out.push_str(&open.text);
+                for member in members {
+                    write_tokens(out, &member.leading);
+                    out.push_str(&member.key.text);
+                    write_tokens(out, &member.before_colon);
+                    out.push_str(&member.colon.text);
+                    member.value.write(out);
+                    if let Some(comma) = &member.comma {
+                        out.push_str(&comma.text);
+                    }
+                }
+                write_tokens(out, dangling);
+                out.push_str(&close.text);
+            }
+        }
+        write_tokens(out, &self.trailing);
+    }
+}
+
+impl Member {
+    pub fn name(&self) -> String {
+        string_value(&self.key.text)
+    }
+}
+
+// Parsed documents only hold valid escapes, a string token put together by
+// hand with a broken one keeps its text as written
+fn string_value(raw: &str) -> String {
+    decode_escapes(raw).unwrap_or_else(|_| raw.strip_prefix('"').and_then(|val| val.strip_suffix('"')).unwrap_or(raw).to_string())
+}
+
+fn write_tokens(out: &mut String, tokens: &[Token]) {
+    for token in tokens {
+        out.push_str(&token.text);
+    }
+}
+
+struct Builder<'a> {
+    tokens: Peekable<TokenIter<'a>>
+}
+
+impl<'a> Builder<'a> {
+    fn next(&mut self) -> Token {
+        self.tokens.next().expect("Unexpected end of document")
+    }
+
+    fn is_kind(&mut self, kind: &JsonKind) -> bool {
+        matches!(self.tokens.peek(), Some(token) if token.kind == *kind)
+    }
+
+    fn trivia(&mut self) -> Vec<Token> {
+        let mut trivia = Vec::new();
+        while let Some(token) = self.tokens.next_if(|token| token.kind.is_trivia()) {
+            trivia.push(token);
+        }
+        trivia
+    }
+
+    fn node(&mut self) -> Node {
+        let leading = self.trivia();
+        self.node_after(leading)
+    }
+
+    fn node_after(&mut self, leading: Vec<Token>) -> Node {
+        let kind = self.kind();
+        let trailing = self.trivia();
+        Node { leading, kind, trailing }
+    }
+
+    fn kind(&mut self) -> NodeKind {
+        let token = self.next();
+
+        match token.kind {
+            JsonKind::BeginArray => {
+                let mut items = Vec::new();
+                let mut leading = self.trivia();
+
+                while !self.is_kind(&JsonKind::EndArray) {
+                    let value = self.node_after(leading);
+                    let comma = self.tokens.next_if(|token| token.kind == JsonKind::ValueSeperator);
+                    leading = self.trivia();
+                    items.push(Element { value, comma });
+                }
+
+                NodeKind::Array { open: token, items, dangling: leading, close: self.next() }
+            },
+            JsonKind::BeginObject => {
+                let mut members = Vec::new();
+                let mut leading = self.trivia();
+
+                while !self.is_kind(&JsonKind::EndObject) {
+                    let key = self.next();
+                    let before_colon = self.trivia();
+                    let colon = self.next();
+                    let value = self.node();
+                    let comma = self.tokens.next_if(|token| token.kind == JsonKind::ValueSeperator);
+                    members.push(Member { leading, key, before_colon, colon, value, comma });
+                    leading = self.trivia();
+                }
+
+                NodeKind::Object { open: token, members, dangling: leading, close: self.next() }
+            },
+            JsonKind::Minus | JsonKind::Digit | JsonKind::Zero => {
+                let mut tokens = vec![token];
+                while let Some(token) = self.tokens.next_if(|token| matches!(token.kind,
+                    JsonKind::Plus | JsonKind::Minus | JsonKind::Digit | JsonKind::Zero |
+                    JsonKind::DecimalPoint | JsonKind::E)) {
+                    tokens.push(token);
+                }
+                NodeKind::Scalar(tokens)
+            },
+            _ => NodeKind::Scalar(vec![token])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
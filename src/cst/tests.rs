@@ -0,0 +1,82 @@
+use crate::cst::{Document, NodeKind};
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::pointer::Pointer;
+use crate::value::Value;
+
+const CONFIG: &str = "// Service config\r
+{\r
+    \"name\" : \"jsorry\",  /* the binary */\r
+    \"version\": \"0.1.0\",\r
+    \"ports\": [ 80,443 , -1.5e+3 ],\r
+    \"empty\": { /* nothing yet */ },\r
+    \"flags\": [\t]\r
+}\r
+";
+
+#[test]
+fn test_round_trip() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    assert_eq!(Document::parse(&contents).to_string(), contents);
+    assert_eq!(Document::parse(CONFIG).to_string(), CONFIG);
+    assert_eq!(Document::parse(" 12 ").to_string(), " 12 ");
+}
+
+#[test]
+fn test_values() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let mut parser = Parser::new(Box::new(TokenIter::new(&contents)));
+    assert_eq!(Document::parse(&contents).value(), parser.parse());
+
+    let mut parser = Parser::new(Box::new(TokenIter::new("{
+        \"name\": \"jsorry\",
+        \"version\": \"0.1.0\",
+        \"ports\": [80, 443, -1.5e+3],
+        \"empty\": {},
+        \"flags\": []
+    }")));
+    assert_eq!(Document::parse(CONFIG).value(), parser.parse());
+}
+
+#[test]
+fn test_trivia() {
+    let doc = Document::parse(CONFIG);
+    assert_eq!(doc.root.leading[0].text, "// Service config");
+
+    let name = doc.resolve(&Pointer::parse("/name").unwrap()).unwrap();
+    assert_eq!(name.leading.len(), 1);
+    assert_eq!(name.trailing.len(), 0);
+
+    let empty = doc.resolve(&Pointer::parse("/empty").unwrap()).unwrap();
+    match &empty.kind {
+        NodeKind::Object { members, dangling, .. } => {
+            assert!(members.is_empty());
+            assert_eq!(dangling[1].text, "/* nothing yet */");
+        },
+        _ => panic!("expected an object")
+    }
+}
+
+#[test]
+fn test_set_keeps_other_bytes() {
+    let mut doc = Document::parse(CONFIG);
+    doc.resolve_mut(&Pointer::parse("/version").unwrap()).unwrap().set(&Value::from("0.2.0"));
+    doc.resolve_mut(&Pointer::parse("/ports/1").unwrap()).unwrap().set(&Value::from(8443));
+
+    assert_eq!(doc.to_string(), CONFIG.replace("0.1.0", "0.2.0").replace("443 ", "8443 "));
+    assert!(doc.resolve(&Pointer::parse("/ports/3").unwrap()).is_none());
+}
+
+#[test]
+#[should_panic(expected = "did not match")]
+fn test_invalid_document() {
+    Document::parse("{ \"a\": 1, }");
+}
+
+#[test]
+fn test_try_parse() {
+    assert_eq!(Document::try_parse("{\"a\": 01}").unwrap_err().code.as_str(), "E0010");
+    assert_eq!(Document::try_parse("[\"\\q\"]").unwrap_err().code.as_str(), "E0011");
+    assert_eq!(Document::try_parse("[1, \"open").unwrap_err().code.as_str(), "E0001");
+    assert_eq!(Document::try_parse("/* kept */ [1]").unwrap().to_string(), "/* kept */ [1]");
+}
@@ -0,0 +1,362 @@
+use std::fmt;
+use std::iter::Peekable;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::lexer::{JsonKind, LexError, Token, TokenIter};
+use crate::parser::decode_escapes;
+
+/// Failure while converting between JSON text and Rust values. Errors
+/// raised from the text carry the line and column they were found at,
+/// both are 0 otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub message: String,
+    pub line: usize,
+    pub column: usize
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} at line {} column {}", self.message, self.line, self.column)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error { message: msg.to_string(), line: 0, column: 0 }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error { message: msg.to_string(), line: 0, column: 0 }
+    }
+}
+
+/// A serde deserializer reading straight from the token stream. Strings
+/// without escapes are borrowed from the input.
+pub struct Deserializer<'de> {
+    input: &'de str,
+    tokens: Peekable<Box<dyn Iterator<Item=Result<Token, LexError>> + 'de>>,
+    // Byte offset of the next token in `input`
+    offset: usize,
+    line: usize,
+    column: usize
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(input: &'de str) -> Deserializer<'de> {
+        Deserializer {
+            input,
+            tokens: (Box::new(TokenIter::new(input).checked()) as Box<dyn Iterator<Item=_>>).peekable(),
+            offset: 0,
+            line: 1,
+            column: 1
+        }
+    }
+
+    /// Checks that nothing but whitespace follows the deserialized value.
+    pub fn end(&mut self) -> Result<(), Error> {
+        match self.peek()? {
+            Some(kind) => Err(self.error(format!("Unexpected token {:#?} after end of document", kind))),
+            None => Ok(())
+        }
+    }
+
+    fn error(&self, message: String) -> Error {
+        Error { message, line: self.line, column: self.column }
+    }
+
+    // Skips whitespace and returns the kind of the next token, or the error
+    // for text that is not a token
+    fn peek(&mut self) -> Result<Option<JsonKind>, Error> {
+        while let Some(token) = self.tokens.peek() {
+            let token = match token {
+                Ok(token) => token,
+                Err(err) => return Err(Error { message: err.message.clone(), line: err.line, column: err.column })
+            };
+            self.line = token.line;
+            self.column = token.column;
+            match token.kind {
+                JsonKind::Space | JsonKind::HorizontalTab | JsonKind::LineFeed | JsonKind::CarriageReturn => {
+                    self.offset += token.text.len();
+                    self.tokens.next();
+                },
+                _ => return Ok(Some(token.kind.clone()))
+            }
+        }
+        Ok(None)
+    }
+
+    // Returns the next token together with its byte offset
+    fn next(&mut self) -> Result<(Token, usize), Error> {
+        self.peek()?;
+        let start = self.offset;
+        match self.tokens.next() {
+            Some(Ok(token)) => {
+                self.offset += token.text.len();
+                Ok((token, start))
+            },
+            _ => Err(self.error("Unexpected end of document".to_string()))
+        }
+    }
+
+    fn must_match(&mut self, kind: JsonKind) -> Result<(), Error> {
+        match self.peek()? {
+            Some(found) if found == kind => self.next().map(|_| ()),
+            Some(found) => Err(self.error(format!("Expected token {:#?} did not match {:#?}", kind, found))),
+            None => Err(self.error(format!("Expected token {:#?} but the document ended", kind)))
+        }
+    }
+
+    fn number<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        let (line, column) = (self.line, self.column);
+        let mut text = String::new();
+        while let Some(Ok(token)) = self.tokens.next_if(|token| matches!(token, Ok(token) if matches!(token.kind,
+            JsonKind::Plus | JsonKind::Minus | JsonKind::Digit | JsonKind::Zero | JsonKind::DecimalPoint | JsonKind::E))) {
+            self.offset += token.text.len();
+            text.push_str(&token.text);
+        }
+
+        let error = |message: &str| Error { message: format!("{} {}", message, text), line, column };
+        let digits = text.strip_prefix('-').unwrap_or(&text);
+        if digits.starts_with('0') && digits[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(error("Illegal leading zero found for number"));
+        }
+        if !is_number(digits) {
+            return Err(error("Invalid number"));
+        }
+
+        if text.contains(['.', 'e', 'E']) {
+            visitor.visit_f64(text.parse().map_err(|_| error("Invalid number"))?)
+        } else if let Ok(val) = text.parse::<u64>() {
+            visitor.visit_u64(val)
+        } else if let Ok(val) = text.parse::<i64>() {
+            visitor.visit_i64(val)
+        } else {
+            visitor.visit_f64(text.parse().map_err(|_| error("Invalid number"))?)
+        }
+    }
+
+    fn string(&mut self) -> Result<(Option<&'de str>, String), Error> {
+        let (token, start) = self.next()?;
+        if token.text.contains('\\') {
+            decode_escapes(&token.text)
+                .map(|decoded| (None, decoded))
+                .map_err(|(_, message)| Error { message, line: token.line, column: token.column })
+        } else {
+            Ok((Some(&self.input[start + 1..start + token.text.len() - 1]), String::new()))
+        }
+    }
+}
+
+// Digits, then an optional fraction and exponent, each with digits
+fn is_number(text: &str) -> bool {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(idx) => (&text[..idx], Some(&text[idx + 1..])),
+        None => (text, None)
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None)
+    };
+    let digits = |val: &str| !val.is_empty() && val.chars().all(|c| c.is_ascii_digit());
+
+    digits(integer) &&
+        fraction.is_none_or(digits) &&
+        exponent.is_none_or(|val| digits(val.strip_prefix(['+', '-']).unwrap_or(val)))
+}
+
+/// Deserializes an instance of `T` from JSON text.
+pub fn from_str<'a, T: de::Deserialize<'a>>(input: &'a str) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(input);
+    let value = T::deserialize(&mut deserializer).map_err(|err| {
+        // Errors raised by the visitor know nothing about the position
+        if err.line == 0 { deserializer.error(err.message) } else { err }
+    })?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.peek()? {
+            Some(JsonKind::Null) => {
+                self.next()?;
+                visitor.visit_unit()
+            },
+            Some(JsonKind::True) => {
+                self.next()?;
+                visitor.visit_bool(true)
+            },
+            Some(JsonKind::False) => {
+                self.next()?;
+                visitor.visit_bool(false)
+            },
+            Some(JsonKind::StringVal) => match self.string()? {
+                (Some(borrowed), _) => visitor.visit_borrowed_str(borrowed),
+                (None, owned) => visitor.visit_string(owned)
+            },
+            Some(JsonKind::Minus) | Some(JsonKind::Digit) | Some(JsonKind::Zero) => self.number(visitor),
+            Some(JsonKind::BeginArray) => {
+                self.next()?;
+                let value = visitor.visit_seq(Entries { de: self, first: true, end: JsonKind::EndArray })?;
+                self.must_match(JsonKind::EndArray)?;
+                Ok(value)
+            },
+            Some(JsonKind::BeginObject) => {
+                self.next()?;
+                let value = visitor.visit_map(Entries { de: self, first: true, end: JsonKind::EndObject })?;
+                self.must_match(JsonKind::EndObject)?;
+                Ok(value)
+            },
+            Some(kind) => Err(self.error(format!("Unexpected token {:#?}", kind))),
+            None => Err(self.error("Unexpected end of document".to_string()))
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.peek()? == Some(JsonKind::Null) {
+            self.next()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Unit variants are plain strings, the others objects with a single
+    // member named after the variant
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str],
+                                         visitor: V) -> Result<V::Value, Error> {
+        match self.peek()? {
+            Some(JsonKind::StringVal) => {
+                let (borrowed, owned) = self.string()?;
+                visitor.visit_enum(borrowed.map(str::to_string).unwrap_or(owned).into_deserializer())
+            },
+            Some(JsonKind::BeginObject) => {
+                self.next()?;
+                let value = visitor.visit_enum(Variant { de: self })?;
+                self.must_match(JsonKind::EndObject)?;
+                Ok(value)
+            },
+            _ => Err(self.error("Expected an enum variant as a string or an object".to_string()))
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Items of an array or members of an object, separated by commas.
+struct Entries<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    first: bool,
+    end: JsonKind
+}
+
+impl<'a, 'de> Entries<'a, 'de> {
+    fn has_next(&mut self) -> Result<bool, Error> {
+        if self.first {
+            self.first = false;
+            return Ok(self.de.peek()? != Some(self.end.clone()));
+        }
+
+        // Without a comma the container must end here
+        if self.de.peek()? != Some(JsonKind::ValueSeperator) {
+            return Ok(false);
+        }
+        self.de.next()?;
+        if self.de.peek()? == Some(self.end.clone()) {
+            return Err(self.de.error("Trailing comma found".to_string()));
+        }
+        Ok(true)
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Entries<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if !self.has_next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Entries<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if !self.has_next()? {
+            return Ok(None);
+        }
+        if self.de.peek()? != Some(JsonKind::StringVal) {
+            return Err(self.de.error("Expected a string as object key".to_string()));
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        self.de.must_match(JsonKind::NameSeperator)?;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// The `{ "Variant": value }` form of an enum.
+struct Variant<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        if self.de.peek()? != Some(JsonKind::StringVal) {
+            return Err(self.de.error("Expected a string as enum variant".to_string()));
+        }
+        let variant = seed.deserialize(&mut *self.de)?;
+        self.de.must_match(JsonKind::NameSeperator)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests;
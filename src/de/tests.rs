@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::de::{from_str, Error};
+use crate::ser::{to_string, to_string_pretty, to_value, to_writer};
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Address {
+    street: String,
+    town: String,
+    postode: String
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    #[serde(rename = "_id")]
+    id: String,
+    name: String,
+    address: Address,
+    pets: Vec<String>,
+    score: f64,
+    verified: bool,
+    salary: u64
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Root {
+    ct_root: Vec<Record>
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Point(i32, i32),
+    Rect { width: u32, height: u32 }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Borrowed<'a> {
+    plain: &'a str,
+    #[serde(borrow)]
+    escaped: Cow<'a, str>
+}
+
+#[test]
+fn test_from_str_testdata() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let root: Root = from_str(&contents).unwrap();
+
+    assert_eq!(root.ct_root[0].name, "Coletta Ortiz");
+    assert_eq!(root.ct_root[0].address.town, "Godmanchester");
+    assert_eq!(root.ct_root[0].pets, vec!["Ziggy", "Sasha"]);
+}
+
+#[test]
+fn test_from_str_values() {
+    assert_eq!(from_str::<Option<i8>>(" null ").unwrap(), None);
+    assert_eq!(from_str::<Vec<i64>>("[-1, 0, 12e0]").ok(), None);
+    assert_eq!(from_str::<Vec<f64>>("[-1, 0.5, 12e-1]").unwrap(), vec![-1.0, 0.5, 1.2]);
+    assert_eq!(from_str::<(bool, String)>("[true, \"a\\u00e9\\n\"]").unwrap(), (true, "aé\n".to_string()));
+    assert_eq!(from_str::<BTreeMap<String, u8>>("{\"b\": 2, \"a\": 1}").unwrap().len(), 2);
+    assert_eq!(from_str::<Vec<Shape>>("[\"Empty\", {\"Circle\": 1.5}, {\"Point\": [1, -2]}, {\"Rect\": {\"width\": 3, \"height\": 4}}]").unwrap(),
+               vec![Shape::Empty, Shape::Circle(1.5), Shape::Point(1, -2), Shape::Rect { width: 3, height: 4 }]);
+}
+
+#[test]
+fn test_from_str_borrows() {
+    let input = "{\"plain\": \"no escapes\", \"escaped\": \"tab\\t\"}";
+    let borrowed: Borrowed = from_str(input).unwrap();
+
+    assert_eq!(borrowed.plain, "no escapes");
+    assert_eq!(borrowed.escaped, "tab\t");
+    assert!(matches!(borrowed.escaped, Cow::Owned(_)));
+}
+
+#[test]
+fn test_from_str_errors() {
+    let error = |input: &str| from_str::<Vec<u8>>(input).unwrap_err();
+
+    assert_eq!(error("[1,\n 2,\n 3,]"), Error { message: "Trailing comma found".to_string(), line: 3, column: 4 });
+    assert_eq!(error("[1 2]"), Error { message: "Expected token EndArray did not match Digit".to_string(), line: 1, column: 4 });
+    assert_eq!(error("[1] 2").message, "Unexpected token Digit after end of document");
+    assert_eq!(error("[01]").message, "Illegal leading zero found for number 01");
+    assert_eq!(error("[300]").line, 1);
+    assert_eq!(error("[\n{}]").to_string(), "invalid type: map, expected u8 at line 2 column 1");
+
+    // Text the lexer rejects is an error too, not a panic
+    assert_eq!(from_str::<bool>("True"), Err(Error { message: "Unknown keyword detected: True".to_string(), line: 1, column: 1 }));
+    assert_eq!(from_str::<String>("\"open").unwrap_err().line, 1);
+    assert_eq!(from_str::<String>(" \"\\q\"").unwrap_err(),
+               Error { message: "Invalid escape sequence \\q in string \"\\q\"".to_string(), line: 1, column: 2 });
+    assert_eq!(error("[1, 2, @]").column, 8);
+}
+
+#[test]
+fn test_to_string() {
+    let record = Record {
+        id: "LB5YD3ASAF445UFC".to_string(),
+        name: "Coletta \"Ortiz\"".to_string(),
+        address: Address { street: "6240 Thornden Street".to_string(), town: "Godmanchester".to_string(), postode: "RM68 3QP".to_string() },
+        pets: vec!["Ziggy".to_string()],
+        score: 4.0,
+        verified: false,
+        salary: 18_446_744_073_709_551_615
+    };
+
+    let text = to_string(&record).unwrap();
+    assert_eq!(text, concat!(
+        "{\"_id\":\"LB5YD3ASAF445UFC\",\"name\":\"Coletta \\\"Ortiz\\\"\",",
+        "\"address\":{\"street\":\"6240 Thornden Street\",\"town\":\"Godmanchester\",\"postode\":\"RM68 3QP\"},",
+        "\"pets\":[\"Ziggy\"],\"score\":4.0,\"verified\":false,\"salary\":18446744073709551615}"
+    ));
+    assert_eq!(from_str::<Record>(&text).unwrap(), record);
+    assert_eq!(from_str::<Record>(&to_string_pretty(&record).unwrap()).unwrap(), record);
+}
+
+#[test]
+fn test_to_value() {
+    let shapes = vec![Shape::Empty, Shape::Point(1, 2), Shape::Rect { width: 3, height: 4 }];
+    assert_eq!(to_string(&shapes).unwrap(), "[\"Empty\",{\"Point\":[1,2]},{\"Rect\":{\"width\":3,\"height\":4}}]");
+
+    let mut map = BTreeMap::new();
+    map.insert(1, f64::NAN);
+    assert_eq!(to_value(&map).unwrap(), Value::Object(vec![("1".to_string(), Value::Null)]));
+    assert!(to_value(&BTreeMap::from([(vec![1], 1)])).is_err());
+}
+
+#[test]
+fn test_to_string_matches_serializer() {
+    let shapes = vec![Shape::Empty, Shape::Circle(0.5), Shape::Point(1, 2), Shape::Rect { width: 3, height: 4 }];
+    let nested = (Vec::<u8>::new(), BTreeMap::<String, u8>::new(), vec![Some("a\n"), None], BTreeMap::from([(true, vec![1.5])]));
+
+    assert_eq!(to_string_pretty(&shapes).unwrap(), Serializer::new().serialize(&to_value(&shapes).unwrap()));
+    assert_eq!(to_string_pretty(&nested).unwrap(), Serializer::new().serialize(&to_value(&nested).unwrap()));
+    assert_eq!(to_string(&nested).unwrap(), "[[],{},[\"a\\n\",null],{\"true\":[1.5]}]");
+    assert!(to_string(&BTreeMap::from([(vec![1], 1)])).is_err());
+
+    let mut out = Vec::new();
+    to_writer(&mut out, &shapes).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "[\"Empty\",{\"Circle\":0.5},{\"Point\":[1,2]},{\"Rect\":{\"width\":3,\"height\":4}}]");
+}
@@ -0,0 +1,171 @@
+use crate::code::ErrorCode;
+use crate::hint::Fix;
+use crate::lexer::Span;
+use crate::parser::ParseError;
+use crate::value::Value;
+
+/// A parse error as a self-contained record, for tools that read results
+/// instead of people.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub code: ErrorCode,
+    pub message: String,
+    pub path: String,
+    pub hint: Option<String>,
+    pub fix: Option<Fix>
+}
+
+impl Diagnostic {
+    pub fn new(file: &str, err: &ParseError) -> Diagnostic {
+        Diagnostic {
+            file: file.to_string(),
+            line: err.line,
+            column: err.column,
+            span: err.span,
+            code: err.code,
+            message: err.message.clone(),
+            path: err.path.to_string(),
+            hint: err.hint.as_ref().map(|hint| hint.message.clone()),
+            fix: err.fix().cloned()
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut members = vec![
+            member("file", Value::from(self.file.as_str())),
+            member("line", Value::from(self.line as i64)),
+            member("column", Value::from(self.column as i64)),
+            member("span", span(self.span)),
+            member("code", Value::from(self.code.as_str())),
+            member("title", Value::from(self.code.title())),
+            member("message", Value::from(self.message.as_str())),
+            member("path", Value::from(self.path.as_str()))
+        ];
+        if let Some(hint) = &self.hint {
+            members.push(member("hint", Value::from(hint.as_str())));
+        }
+        if let Some(fix) = &self.fix {
+            members.push(member("fix", Value::Object(vec![
+                member("span", span(fix.span)),
+                member("replacement", Value::from(fix.replacement.as_str())),
+                member("safe", Value::from(fix.safe))
+            ])));
+        }
+        Value::Object(members)
+    }
+
+    /// A GitHub Actions workflow command, which shows up as an annotation on
+    /// the file.
+    pub fn github(&self) -> String {
+        format!("::error file={},line={},col={},title={}::{}",
+                escape_property(&self.file), self.line, self.column, self.code,
+                escape_data(&self.message_with_hint()))
+    }
+
+    fn message_with_hint(&self) -> String {
+        match &self.hint {
+            Some(hint) => format!("{} at {} (hint: {})", self.message, self.path, hint),
+            None => format!("{} at {}", self.message, self.path)
+        }
+    }
+}
+
+pub fn to_json(diagnostics: &[Diagnostic]) -> Value {
+    Value::Array(diagnostics.iter().map(Diagnostic::to_value).collect())
+}
+
+/// A SARIF 2.1.0 log with a single run. Regions are given in bytes as well
+/// as lines and columns, and known fixes are included.
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> Value {
+    let mut codes: Vec<ErrorCode> = diagnostics.iter().map(|diagnostic| diagnostic.code).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    let rules = codes.into_iter().map(|code| Value::Object(vec![
+        member("id", Value::from(code.as_str())),
+        member("shortDescription", text(code.title())),
+        member("fullDescription", text(code.explanation()))
+    ])).collect();
+
+    let results = diagnostics.iter().map(|diagnostic| {
+        let artifact = || member("artifactLocation", Value::Object(vec![member("uri", Value::from(diagnostic.file.as_str()))]));
+        let mut start = vec![
+            member("startLine", Value::from(diagnostic.line as i64)),
+            member("startColumn", Value::from(diagnostic.column as i64))
+        ];
+        start.extend(region(diagnostic.span));
+
+        let mut members = vec![
+            member("ruleId", Value::from(diagnostic.code.as_str())),
+            member("level", Value::from("error")),
+            member("message", text(&diagnostic.message_with_hint())),
+            member("locations", Value::Array(vec![Value::Object(vec![
+                member("physicalLocation", Value::Object(vec![artifact(), member("region", Value::Object(start))]))
+            ])]))
+        ];
+        if let Some(fix) = &diagnostic.fix {
+            members.push(member("fixes", Value::Array(vec![Value::Object(vec![
+                member("description", text(diagnostic.hint.as_deref().unwrap_or(&diagnostic.message))),
+                member("artifactChanges", Value::Array(vec![Value::Object(vec![
+                    artifact(),
+                    member("replacements", Value::Array(vec![Value::Object(vec![
+                        member("deletedRegion", Value::Object(region(fix.span))),
+                        member("insertedContent", text(&fix.replacement))
+                    ])]))
+                ])]))
+            ])])));
+        }
+        Value::Object(members)
+    }).collect();
+
+    Value::Object(vec![
+        member("$schema", Value::from("https://json.schemastore.org/sarif-2.1.0.json")),
+        member("version", Value::from("2.1.0")),
+        member("runs", Value::Array(vec![Value::Object(vec![
+            member("tool", Value::Object(vec![
+                member("driver", Value::Object(vec![
+                    member("name", Value::from("jsorry")),
+                    member("rules", Value::Array(rules))
+                ]))
+            ])),
+            member("results", Value::Array(results))
+        ])]))
+    ])
+}
+
+fn member(key: &str, value: Value) -> (String, Value) {
+    (key.to_string(), value)
+}
+
+fn span(span: Span) -> Value {
+    Value::Object(vec![
+        member("start", Value::from(span.start as i64)),
+        member("end", Value::from(span.end as i64))
+    ])
+}
+
+fn region(span: Span) -> Vec<(String, Value)> {
+    vec![
+        member("byteOffset", Value::from(span.start as i64)),
+        member("byteLength", Value::from((span.end - span.start) as i64))
+    ]
+}
+
+fn text(text: &str) -> Value {
+    Value::Object(vec![member("text", Value::from(text))])
+}
+
+// Workflow commands end at a newline, so these are percent encoded
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(property: &str) -> String {
+    escape_data(property).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests;
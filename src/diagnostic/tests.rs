@@ -0,0 +1,69 @@
+use crate::diagnostic::{to_json, to_sarif, Diagnostic};
+use crate::lexer::{Span, TokenIter};
+use crate::parser::Parser;
+use crate::pointer::Pointer;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn diagnostic(file: &str, data: &str) -> Diagnostic {
+    let err = Parser::checked(Box::new(TokenIter::new(data).checked())).try_parse().unwrap_err();
+    Diagnostic::new(file, &err)
+}
+
+fn at<'a>(value: &'a Value, pointer: &str) -> &'a Value {
+    Pointer::parse(pointer).unwrap().resolve(value).unwrap()
+}
+
+#[test]
+fn test_new() {
+    let diagnostic = diagnostic("a.json", "{\"a\": [1,\n  2,]}");
+    assert_eq!(diagnostic.line, 2);
+    assert_eq!(diagnostic.column, 5);
+    assert_eq!(diagnostic.span, Span { start: 14, end: 15 });
+    assert_eq!(diagnostic.path, "$.a[2]");
+    assert_eq!(diagnostic.hint.as_deref(), Some("trailing commas are not allowed, remove this comma"));
+    assert_eq!(diagnostic.fix.unwrap().span, Span { start: 13, end: 14 });
+}
+
+#[test]
+fn test_json() {
+    let value = to_json(&[diagnostic("a.json", "[1 2]")]);
+    assert_eq!(Serializer::compact().serialize(at(&value, "/0/span")), "{\"start\":3,\"end\":4}");
+    assert_eq!(at(&value, "/0/file").as_str(), Some("a.json"));
+    assert_eq!(at(&value, "/0/code").as_str(), Some("E0008"));
+    assert_eq!(at(&value, "/0/title").as_str(), Some("unexpected token"));
+    assert_eq!(at(&value, "/0/hint").as_str(), Some("elements must be separated by commas"));
+    assert_eq!(at(&value, "/0/fix/replacement").as_str(), Some(","));
+    assert_eq!(at(&value, "/0/fix/safe"), &Value::Bool(false));
+
+    let value = to_json(&[diagnostic("a.json", "[1")]);
+    assert!(at(&value, "/0").get("hint").is_none());
+    assert!(at(&value, "/0").get("fix").is_none());
+}
+
+#[test]
+fn test_sarif() {
+    let value = to_sarif(&[diagnostic("a.json", "[1,]"), diagnostic("b.json", "[1")]);
+    assert_eq!(at(&value, "/version").as_str(), Some("2.1.0"));
+    assert_eq!(at(&value, "/runs/0/tool/driver/rules/0/id").as_str(), Some("E0007"));
+    assert_eq!(at(&value, "/runs/0/tool/driver/rules/1/shortDescription/text").as_str(), Some("unexpected token"));
+    assert_eq!(at(&value, "/runs/0/results/1/ruleId").as_str(), Some("E0008"));
+
+    let result = at(&value, "/runs/0/results/0");
+    assert_eq!(Serializer::compact().serialize(at(result, "/locations/0/physicalLocation")),
+               "{\"artifactLocation\":{\"uri\":\"a.json\"},\
+               \"region\":{\"startLine\":1,\"startColumn\":4,\"byteOffset\":3,\"byteLength\":1}}");
+    assert_eq!(Serializer::compact().serialize(at(result, "/fixes/0/artifactChanges/0/replacements/0")),
+               "{\"deletedRegion\":{\"byteOffset\":2,\"byteLength\":1},\"insertedContent\":{\"text\":\"\"}}");
+    assert!(at(&value, "/runs/0/results/1").get("fixes").is_none());
+
+    assert_eq!(Serializer::compact().serialize(at(&to_sarif(&[]), "/runs/0/results")), "[]");
+}
+
+#[test]
+fn test_github() {
+    assert_eq!(diagnostic("dir,1/a:b.json", "[1 2]").github(),
+               "::error file=dir%2C1/a%3Ab.json,line=1,col=4,title=E0008::\
+               Expected token EndArray did not match Digit at $ (hint: elements must be separated by commas)");
+    assert_eq!(diagnostic("a.json", "[\"100%\n\"]").github().split("::").nth(2).map(|data| data.contains('\n')), Some(false));
+}
@@ -0,0 +1,213 @@
+use crate::path::Path;
+use crate::value::Value;
+
+/// A single semantic difference between two documents. Paths of removed
+/// values refer to the old document, paths of added values to the new one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    Added { path: Path, value: &'a Value },
+    Removed { path: Path, value: &'a Value },
+    Changed { from_path: Path, to_path: Path, old: &'a Value, new: &'a Value }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Compare arrays as multisets instead of sequences.
+    pub ignore_array_order: bool
+}
+
+/// Compares two documents, ignoring formatting and the order of object keys.
+pub fn diff<'a>(from: &'a Value, to: &'a Value, options: &DiffOptions) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    diff_values(&Path::root(), &Path::root(), from, to, options, &mut changes);
+    changes
+}
+
+fn diff_values<'a>(from_path: &Path, to_path: &Path, from: &'a Value, to: &'a Value,
+                   options: &DiffOptions, out: &mut Vec<Change<'a>>) {
+    if from == to {
+        return;
+    }
+
+    match (from, to) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, val) in old {
+                if to.get(key).is_none() {
+                    out.push(Change::Removed { path: from_path.key(key), value: val });
+                }
+            }
+            for (key, val) in new {
+                match from.get(key) {
+                    Some(old_val) => diff_values(&from_path.key(key), &to_path.key(key), old_val, val, options, out),
+                    None => out.push(Change::Added { path: to_path.key(key), value: val })
+                }
+            }
+        },
+        (Value::Array(old), Value::Array(new)) if options.ignore_array_order => {
+            let mut matched = vec![false; new.len()];
+            for (i, item) in old.iter().enumerate() {
+                let found = (0..new.len()).find(|j| !matched[*j] && new[*j] == *item);
+                match found {
+                    Some(j) => matched[j] = true,
+                    None => out.push(Change::Removed { path: from_path.index(i), value: item })
+                }
+            }
+            for (j, item) in new.iter().enumerate() {
+                if !matched[j] {
+                    out.push(Change::Added { path: to_path.index(j), value: item });
+                }
+            }
+        },
+        (Value::Array(old), Value::Array(new)) => {
+            for hunk in hunks(old, new) {
+                let changes = hunk.deleted.len().min(hunk.inserted.len());
+                for (i, j) in hunk.deleted.iter().zip(hunk.inserted.iter()) {
+                    diff_values(&from_path.index(*i), &to_path.index(*j), &old[*i], &new[*j], options, out);
+                }
+                for i in &hunk.deleted[changes..] {
+                    out.push(Change::Removed { path: from_path.index(*i), value: &old[*i] });
+                }
+                for j in &hunk.inserted[changes..] {
+                    out.push(Change::Added { path: to_path.index(*j), value: &new[*j] });
+                }
+            }
+        },
+        _ => out.push(Change::Changed {
+            from_path: from_path.clone(),
+            to_path: to_path.clone(),
+            old: from,
+            new: to
+        })
+    }
+}
+
+/// A run of elements that differ between two kept elements of an alignment.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Hunk {
+    pub deleted: Vec<usize>,
+    pub inserted: Vec<usize>
+}
+
+// Past this many edits the middle of two arrays is no longer aligned element
+// by element but replaced as a whole, which bounds time and memory
+const MAX_EDITS: usize = 1000;
+
+/// One step of an edit script turning one sequence into another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Step {
+    Same,
+    Deleted,
+    Inserted
+}
+
+/// Aligns two arrays on a shortest edit script and returns the indices that
+/// fall outside of it, grouped into hunks in document order.
+pub(crate) fn hunks(old: &[Value], new: &[Value]) -> Vec<Hunk> {
+    // Common ends are cheap to skip and usually most of the array
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old_middle, new_middle) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let steps = edit_script(old_middle, new_middle, MAX_EDITS).unwrap_or_else(|| {
+        old_middle.iter().map(|_| Step::Deleted).chain(new_middle.iter().map(|_| Step::Inserted)).collect()
+    });
+
+    let mut hunks = Vec::new();
+    let mut hunk = Hunk::default();
+    let (mut i, mut j) = (prefix, prefix);
+    for step in steps {
+        match step {
+            Step::Same => {
+                if !hunk.deleted.is_empty() || !hunk.inserted.is_empty() {
+                    hunks.push(std::mem::take(&mut hunk));
+                }
+                i += 1;
+                j += 1;
+            },
+            Step::Deleted => {
+                hunk.deleted.push(i);
+                i += 1;
+            },
+            Step::Inserted => {
+                hunk.inserted.push(j);
+                j += 1;
+            }
+        }
+    }
+
+    if !hunk.deleted.is_empty() || !hunk.inserted.is_empty() {
+        hunks.push(hunk);
+    }
+
+    hunks
+}
+
+/// A shortest edit script from `old` to `new` by Myers' algorithm, or None
+/// when it takes more than `limit` edits. Step `d` keeps the furthest
+/// position reached on each of its `2d + 1` diagonals, which is all the
+/// backtracking needs.
+pub(crate) fn edit_script<T: PartialEq>(old: &[T], new: &[T], limit: usize) -> Option<Vec<Step>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut found = false;
+
+    // The furthest x on diagonal `k` after `d - 1` edits
+    fn furthest(previous: &[isize], d: isize, k: isize) -> isize {
+        previous[(k + d - 1) as usize]
+    }
+    fn from_above(previous: &[isize], d: isize, k: isize) -> bool {
+        k == -d || (k != d && furthest(previous, d, k - 1) < furthest(previous, d, k + 1))
+    }
+
+    'search: for d in 0..=(n + m).min(limit as isize) {
+        let mut reached = vec![0isize; 2 * d as usize + 1];
+        for k in (-d..=d).step_by(2) {
+            let mut x = match trace.last() {
+                None => 0,
+                Some(previous) if from_above(previous, d, k) => furthest(previous, d, k + 1),
+                Some(previous) => furthest(previous, d, k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            reached[(k + d) as usize] = x;
+            found = x >= n && y >= m;
+            if found {
+                trace.push(reached);
+                break 'search;
+            }
+        }
+        trace.push(reached);
+    }
+    if !found {
+        return None;
+    }
+
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let k = x - y;
+        let previous_k = if from_above(previous, d, k) { k + 1 } else { k - 1 };
+        let previous_x = furthest(previous, d, previous_k);
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            steps.push(Step::Same);
+            x -= 1;
+            y -= 1;
+        }
+        steps.push(if x == previous_x { Step::Inserted } else { Step::Deleted });
+        x = previous_x;
+        y = previous_y;
+    }
+    steps.extend((0..x).map(|_| Step::Same));
+
+    steps.reverse();
+    Some(steps)
+}
+
+#[cfg(test)]
+mod tests;
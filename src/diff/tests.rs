@@ -0,0 +1,108 @@
+use crate::diff::{diff, edit_script, Change, DiffOptions, Step};
+use crate::lexer::TokenIter;
+use crate::parser::{Parser, Position};
+use crate::path::Path;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+fn describe(changes: &[Change]) -> Vec<String> {
+    changes.iter().map(|change| match change {
+        Change::Added { path, .. } => format!("+ {}", path),
+        Change::Removed { path, .. } => format!("- {}", path),
+        Change::Changed { from_path, to_path, .. } => format!("~ {} {}", from_path, to_path)
+    }).collect()
+}
+
+#[test]
+fn test_ignores_formatting_and_key_order() {
+    let from = parse("{\"a\": 1, \"b\": [true, null]}");
+    let to = parse("{\n\t\"b\": [ true, null ],\n\t\"a\": 1.0\n}");
+
+    assert!(diff(&from, &to, &DiffOptions::default()).is_empty());
+}
+
+#[test]
+fn test_object_changes() {
+    let from = parse("{\"name\": \"Coletta Ortiz\", \"address\": {\"town\": \"Godmanchester\"}, \"score\": 4.1}");
+    let to = parse("{\"name\": \"Coletta Ortiz\", \"address\": {\"town\": \"Huntingdon\"}, \"verified\": false}");
+
+    assert_eq!(describe(&diff(&from, &to, &DiffOptions::default())), vec![
+        "- $.score",
+        "~ $.address.town $.address.town",
+        "+ $.verified",
+    ]);
+}
+
+#[test]
+fn test_array_changes() {
+    let from = parse("[\"Ziggy\", \"Sasha\", \"Rex\"]");
+    let to = parse("[\"Milo\", \"Ziggy\", \"Rex\", \"Bo\"]");
+
+    assert_eq!(describe(&diff(&from, &to, &DiffOptions::default())), vec![
+        "+ $[0]",
+        "- $[1]",
+        "+ $[3]",
+    ]);
+
+    // Large arrays are aligned without a table of every pair of elements
+    let from = Value::Array((0..50_000i64).map(Value::from).collect());
+    let mut items: Vec<Value> = (0..50_000i64).map(Value::from).collect();
+    items.remove(20_000);
+    items.insert(30_000, Value::from("new"));
+    assert_eq!(describe(&diff(&from, &Value::Array(items), &DiffOptions::default())), vec!["- $[20000]", "+ $[30000]"]);
+
+    // Past the edit limit the differing middle is compared position by position
+    let to = Value::Array((0..50_000i64).map(|idx| Value::from(-idx - 1)).collect());
+    let changes = diff(&from, &to, &DiffOptions::default());
+    assert_eq!(changes.len(), 50_000);
+    assert_eq!(describe(&changes[..1]), vec!["~ $[0] $[0]"]);
+
+    let from = parse("[{\"id\": 1, \"n\": \"a\"}, {\"id\": 2}]");
+    let to = parse("[{\"id\": 1, \"n\": \"b\"}, {\"id\": 2}]");
+    assert_eq!(describe(&diff(&from, &to, &DiffOptions::default())), vec!["~ $[0].n $[0].n"]);
+}
+
+#[test]
+fn test_ignore_array_order() {
+    let from = parse("[1, 2, 2, 3]");
+    let to = parse("[3, 2, 1, 4]");
+    let options = DiffOptions { ignore_array_order: true };
+
+    let changes = diff(&from, &to, &options);
+
+    assert_eq!(describe(&changes), vec!["- $[2]", "+ $[3]"]);
+    assert_eq!(changes[1], Change::Added { path: Path::root().index(3), value: &Value::from(4) });
+}
+
+#[test]
+fn test_positions_of_changes() {
+    let data = "{\n  \"a\": [\n    1,\n    {\"b\": 2}\n  ]\n}";
+    let mut parser = Parser::new(Box::new(TokenIter::new(data))).with_positions();
+    parser.parse();
+
+    let positions = parser.positions();
+
+    assert_eq!(positions.get(&Path::root()), Some(&Position { line: 1, column: 1 }));
+    assert_eq!(positions.get(&Path::root().key("a").index(0)), Some(&Position { line: 3, column: 5 }));
+    assert_eq!(positions.get(&Path::root().key("a").index(1).key("b")), Some(&Position { line: 4, column: 11 }));
+
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse();
+    assert!(parser.positions().is_empty());
+}
+
+#[test]
+fn test_edit_script() {
+    let (old, new): (Vec<char>, Vec<char>) = ("abcabba".chars().collect(), "cbabac".chars().collect());
+    let steps = edit_script(&old, &new, 10).unwrap();
+    assert_eq!(steps.iter().filter(|step| **step != Step::Same).count(), 5);
+    assert_eq!(steps.iter().filter(|step| **step != Step::Inserted).count(), old.len());
+    assert_eq!(steps.iter().filter(|step| **step != Step::Deleted).count(), new.len());
+
+    assert_eq!(edit_script(&old, &new, 4), None);
+    assert_eq!(edit_script::<u8>(&[], &[], 0), Some(Vec::new()));
+}
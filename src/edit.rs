@@ -0,0 +1,377 @@
+use crate::cst::{Document, Element, Member, Node, NodeKind};
+use crate::lexer::{JsonKind, Token, TokenIter};
+use crate::pointer::{array_index, Pointer};
+use crate::serializer::{write_string, Serializer};
+use crate::value::Value;
+
+/// How new entries are laid out, inferred from the existing document.
+struct Style {
+    // One level of indentation, or none when everything sits on one line
+    indent: Option<String>,
+    newline: String
+}
+
+impl Document {
+    /// Sets the value at `pointer`. Existing values are replaced in place,
+    /// otherwise a member is added to the end of its object, or an item to
+    /// the end of its array when the last token is `-` or the array length.
+    pub fn set(&mut self, pointer: &Pointer, value: &Value) -> Result<(), String> {
+        let style = self.style();
+        if let Some((node, indent)) = locate(&mut self.root, pointer) {
+            node.kind = format_kind(value, &style, &indent);
+            return Ok(());
+        }
+
+        let (parent, last) = pointer.split_last()
+            .ok_or_else(|| "the document root always exists".to_string())?;
+        let (node, indent) = locate(&mut self.root, &parent)
+            .ok_or_else(|| format!("parent of \"{}\" does not exist", pointer))?;
+
+        match &mut node.kind {
+            NodeKind::Object { members, dangling, .. } => {
+                let idx = members.len();
+                insert_member(members, dangling, idx, last, value, &style, &indent);
+                Ok(())
+            },
+            NodeKind::Array { items, dangling, .. } if last == "-" || array_index(last) == Some(items.len()) => {
+                let spacing = items.first().and_then(|item| spaces(&item.value.leading)).unwrap_or_default();
+                let (sep, item_indent) = separator(items.iter().map(|item| &item.value.leading), spacing, &style, &indent);
+                let item = Element {
+                    value: Node { leading: Vec::new(), kind: format_kind(value, &style, &item_indent), trailing: Vec::new() },
+                    comma: None
+                };
+                let idx = items.len();
+                insert_entry(items, dangling, idx, item, sep, &style, &indent);
+                Ok(())
+            },
+            NodeKind::Array { .. } => Err(format!("invalid array index \"{}\" in \"{}\"", last, pointer)),
+            NodeKind::Scalar(_) => Err(format!("parent of \"{}\" is not a container", pointer))
+        }
+    }
+
+    /// Adds a member named `key` right after the member at `sibling`.
+    pub fn insert_after(&mut self, sibling: &Pointer, key: &str, value: &Value) -> Result<(), String> {
+        let style = self.style();
+        let (parent, name) = sibling.split_last()
+            .ok_or_else(|| "the document root has no siblings".to_string())?;
+        let (node, indent) = locate(&mut self.root, &parent)
+            .ok_or_else(|| format!("path \"{}\" does not exist", parent))?;
+
+        match &mut node.kind {
+            NodeKind::Object { members, dangling, .. } => {
+                if members.iter().any(|member| member.name() == key) {
+                    return Err(format!("member \"{}\" already exists in \"{}\"", key, parent));
+                }
+                let idx = members.iter().rposition(|member| member.name() == name)
+                    .ok_or_else(|| format!("path \"{}\" does not exist", sibling))?;
+                insert_member(members, dangling, idx + 1, key, value, &style, &indent);
+                Ok(())
+            },
+            _ => Err(format!("parent of \"{}\" is not an object", sibling))
+        }
+    }
+
+    /// Removes a member or array item, returning its value.
+    pub fn remove(&mut self, pointer: &Pointer) -> Result<Value, String> {
+        let missing = || format!("path \"{}\" does not exist", pointer);
+        let (parent, last) = pointer.split_last()
+            .ok_or_else(|| "can not remove the document root".to_string())?;
+        let (node, _) = locate(&mut self.root, &parent).ok_or_else(missing)?;
+
+        match &mut node.kind {
+            NodeKind::Object { members, dangling, .. } => {
+                let idx = members.iter().rposition(|member| member.name() == last).ok_or_else(missing)?;
+                Ok(remove_entry(members, dangling, idx).value.value())
+            },
+            NodeKind::Array { items, dangling, .. } => {
+                let idx = array_index(last).filter(|idx| *idx < items.len()).ok_or_else(missing)?;
+                Ok(remove_entry(items, dangling, idx).value.value())
+            },
+            NodeKind::Scalar(_) => Err(missing())
+        }
+    }
+
+    /// Renames the member at `pointer`, keeping its value and position.
+    pub fn rename(&mut self, pointer: &Pointer, key: &str) -> Result<(), String> {
+        let missing = || format!("path \"{}\" does not exist", pointer);
+        let (parent, last) = pointer.split_last()
+            .ok_or_else(|| "can not rename the document root".to_string())?;
+        let (node, _) = locate(&mut self.root, &parent).ok_or_else(missing)?;
+
+        match &mut node.kind {
+            NodeKind::Object { members, .. } => {
+                if last != key && members.iter().any(|member| member.name() == key) {
+                    return Err(format!("member \"{}\" already exists in \"{}\"", key, parent));
+                }
+                let member = members.iter_mut().rev().find(|member| member.name() == last).ok_or_else(missing)?;
+                member.key = key_token(key);
+                Ok(())
+            },
+            _ => Err(format!("parent of \"{}\" is not an object", pointer))
+        }
+    }
+
+    fn style(&self) -> Style {
+        let newline = if self.to_string().contains("\r\n") { "\r\n" } else { "\n" };
+        Style { indent: find_indent(&self.root, ""), newline: newline.to_string() }
+    }
+}
+
+// Members and array items both sit between a leading run of trivia and a
+// trailing one, followed by an optional comma.
+trait Entry {
+    fn leading(&mut self) -> &mut Vec<Token>;
+    fn trailing(&mut self) -> &mut Vec<Token>;
+    fn comma(&mut self) -> &mut Option<Token>;
+}
+
+impl Entry for Member {
+    fn leading(&mut self) -> &mut Vec<Token> {
+        &mut self.leading
+    }
+
+    fn trailing(&mut self) -> &mut Vec<Token> {
+        &mut self.value.trailing
+    }
+
+    fn comma(&mut self) -> &mut Option<Token> {
+        &mut self.comma
+    }
+}
+
+impl Entry for Element {
+    fn leading(&mut self) -> &mut Vec<Token> {
+        &mut self.value.leading
+    }
+
+    fn trailing(&mut self) -> &mut Vec<Token> {
+        &mut self.value.trailing
+    }
+
+    fn comma(&mut self) -> &mut Option<Token> {
+        &mut self.comma
+    }
+}
+
+/// Finds a node along with the indentation of the line it starts on.
+fn locate<'n>(root: &'n mut Node, pointer: &Pointer) -> Option<(&'n mut Node, String)> {
+    let mut curr = root;
+    let mut indent = String::new();
+
+    for token in pointer.tokens() {
+        let leading = match &curr.kind {
+            NodeKind::Object { members, .. } => members.iter().rev()
+                .find(|member| member.name() == *token)
+                .map(|member| &member.leading),
+            NodeKind::Array { items, .. } => array_index(token)
+                .and_then(|idx| items.get(idx))
+                .map(|item| &item.value.leading),
+            NodeKind::Scalar(_) => None
+        }?;
+        if let Some(val) = line_indent(leading) {
+            indent = val;
+        }
+        curr = curr.child_mut(token)?;
+    }
+
+    Some((curr, indent))
+}
+
+/// The first step in indentation between a container and its entries.
+fn find_indent(node: &Node, indent: &str) -> Option<String> {
+    let children: Vec<(&Vec<Token>, &Node)> = match &node.kind {
+        NodeKind::Object { members, .. } => members.iter().map(|member| (&member.leading, &member.value)).collect(),
+        NodeKind::Array { items, .. } => items.iter().map(|item| (&item.value.leading, &item.value)).collect(),
+        NodeKind::Scalar(_) => return None
+    };
+
+    for (leading, child) in children {
+        let child_indent = line_indent(leading).unwrap_or_else(|| indent.to_string());
+        if child_indent.len() > indent.len() && child_indent.starts_with(indent) {
+            return Some(child_indent[indent.len()..].to_string());
+        }
+        if let Some(unit) = find_indent(child, &child_indent) {
+            return Some(unit);
+        }
+    }
+
+    None
+}
+
+/// The spaces and tabs after the last line feed, if there is one.
+fn line_indent(trivia: &[Token]) -> Option<String> {
+    let idx = trivia.iter().rposition(|token| token.kind == JsonKind::LineFeed)?;
+    Some(trivia[idx + 1..].iter()
+        .take_while(|token| matches!(token.kind, JsonKind::Space | JsonKind::HorizontalTab))
+        .map(|token| token.text.as_str())
+        .collect())
+}
+
+/// Splits trivia into what is left on the current line and the rest, which
+/// starts with a line break. Trivia without a line break is all rest.
+fn split_line(mut trivia: Vec<Token>) -> (Vec<Token>, Vec<Token>) {
+    match trivia.iter().position(|token| matches!(token.kind, JsonKind::LineFeed | JsonKind::CarriageReturn)) {
+        Some(idx) => {
+            let rest = trivia.split_off(idx);
+            (trivia, rest)
+        },
+        None => (Vec::new(), trivia)
+    }
+}
+
+fn has_line_break(trivia: &[Token]) -> bool {
+    trivia.iter().any(|token| token.kind == JsonKind::LineFeed)
+}
+
+fn tokens(text: &str) -> Vec<Token> {
+    TokenIter::new(text).collect()
+}
+
+fn key_token(key: &str) -> Token {
+    let mut quoted = String::new();
+    write_string(&mut quoted, key);
+    tokens(&quoted).remove(0)
+}
+
+/// Lays out a new value the way the rest of the document is laid out.
+fn format_kind(value: &Value, style: &Style, indent: &str) -> NodeKind {
+    let text = match &style.indent {
+        Some(unit) => Serializer::new().indent(unit).serialize(value)
+            .replace('\n', &format!("{}{}", style.newline, indent)),
+        None => Serializer::compact().serialize(value)
+    };
+    Document::parse(&text).root.kind
+}
+
+/// The trivia to put in front of a new entry, and the indentation of its
+/// line, copied from its siblings where there are any. `spacing` is used
+/// after the comma when no sibling follows one yet.
+fn separator<'t>(leadings: impl Iterator<Item=&'t Vec<Token>>, spacing: Vec<Token>, style: &Style,
+                 indent: &str) -> (Vec<Token>, String) {
+    let leadings: Vec<&Vec<Token>> = leadings.collect();
+
+    if let Some(val) = leadings.iter().find_map(|leading| line_indent(leading)) {
+        return (tokens(&format!("{}{}", style.newline, val)), val);
+    }
+    if let Some(leading) = leadings.get(1) {
+        let spaces = leading.iter().filter(|token| token.kind == JsonKind::Space).cloned().collect();
+        return (spaces, indent.to_string());
+    }
+
+    match (&style.indent, leadings.is_empty()) {
+        (Some(unit), true) => {
+            let val = format!("{}{}", indent, unit);
+            (tokens(&format!("{}{}", style.newline, val)), val)
+        },
+        _ => (spacing, indent.to_string())
+    }
+}
+
+/// The spaces of trivia that stays on one line, if it has any.
+fn spaces(trivia: &[Token]) -> Option<Vec<Token>> {
+    let spaces: Vec<Token> = trivia.iter().filter(|token| token.kind == JsonKind::Space).cloned().collect();
+    (!has_line_break(trivia) && !spaces.is_empty()).then_some(spaces)
+}
+
+fn insert_member(members: &mut Vec<Member>, dangling: &mut Vec<Token>, idx: usize, key: &str,
+                 value: &Value, style: &Style, indent: &str) {
+    // A lone member shows its spacing after the brace or the colon, as in
+    // `{ "a": 1 }` or `{"a": 1}`
+    let spacing = members.first()
+        .and_then(|member| spaces(&member.leading).or_else(|| spaces(&member.value.leading)))
+        .unwrap_or_default();
+    let (sep, member_indent) = separator(members.iter().map(|member| &member.leading), spacing, style, indent);
+
+    // Copy the spacing around the colon from a sibling
+    let spacing = |trivia: &Vec<Token>| if has_line_break(trivia) { None } else { Some(trivia.clone()) };
+    let default = if style.indent.is_some() { tokens(" ") } else { Vec::new() };
+    let sibling = members.get(idx.saturating_sub(1));
+    let before_colon = sibling.and_then(|member| spacing(&member.before_colon)).unwrap_or_default();
+    let after_colon = sibling.and_then(|member| spacing(&member.value.leading)).unwrap_or(default);
+
+    let member = Member {
+        leading: Vec::new(),
+        key: key_token(key),
+        before_colon,
+        colon: tokens(":").remove(0),
+        value: Node { leading: after_colon, kind: format_kind(value, style, &member_indent), trailing: Vec::new() },
+        comma: None
+    };
+    insert_entry(members, dangling, idx, member, sep, style, indent);
+}
+
+// `indent` is that of the line the container starts on
+fn insert_entry<E: Entry>(entries: &mut Vec<E>, dangling: &mut Vec<Token>, idx: usize, mut entry: E,
+                          sep: Vec<Token>, style: &Style, indent: &str) {
+    let comma = || tokens(",").remove(0);
+
+    if entries.is_empty() {
+        // Comments in an empty container stay in front of the new entry
+        let mut kept = std::mem::take(dangling);
+        while kept.last().is_some_and(|token| token.kind.is_trivia() && token.kind != JsonKind::Comment) {
+            kept.pop();
+        }
+        *entry.leading() = join(kept, sep, style);
+        if style.indent.is_some() {
+            *entry.trailing() = tokens(&format!("{}{}", style.newline, indent));
+        }
+        entries.push(entry);
+    } else if idx == entries.len() {
+        // Comments on the line of the old last entry stay there, after the comma
+        let last = entries.last_mut().unwrap();
+        let (same_line, rest) = split_line(std::mem::take(last.trailing()));
+        *last.comma() = Some(comma());
+        *entry.leading() = join(same_line, sep, style);
+        *entry.trailing() = rest;
+        entries.push(entry);
+    } else {
+        let next = &mut entries[idx];
+        if has_line_break(next.leading()) {
+            let (same_line, rest) = split_line(std::mem::take(next.leading()));
+            *next.leading() = rest;
+            *entry.leading() = join(same_line, sep, style);
+        } else {
+            *entry.leading() = next.leading().clone();
+        }
+        *entry.comma() = Some(comma());
+        entries.insert(idx, entry);
+    }
+}
+
+fn remove_entry<E: Entry>(entries: &mut Vec<E>, dangling: &mut Vec<Token>, idx: usize) -> E {
+    let mut removed = entries.remove(idx);
+    let leading = std::mem::take(removed.leading());
+    let (same_line, _) = split_line(leading.clone());
+
+    if entries.is_empty() {
+        dangling.clear();
+    } else if idx == entries.len() {
+        let last = entries.last_mut().unwrap();
+        *last.comma() = None;
+        last.trailing().extend(same_line);
+        last.trailing().append(removed.trailing());
+    } else {
+        let next = &mut entries[idx];
+        if has_line_break(next.leading()) {
+            let (_, rest) = split_line(std::mem::take(next.leading()));
+            *next.leading() = same_line.into_iter().chain(rest).collect();
+        } else {
+            *next.leading() = leading;
+        }
+    }
+
+    removed
+}
+
+/// Appends trivia, making sure a line comment is not left without its line break.
+fn join(mut first: Vec<Token>, second: Vec<Token>, style: &Style) -> Vec<Token> {
+    let ends_line_comment = first.last().is_some_and(|token| token.text.starts_with("//"));
+    if ends_line_comment && !second.first().is_some_and(|token| matches!(token.kind, JsonKind::LineFeed | JsonKind::CarriageReturn)) {
+        first.extend(tokens(&style.newline));
+    }
+    first.extend(second);
+    first
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,148 @@
+use crate::cst::Document;
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::pointer::Pointer;
+use crate::value::Value;
+
+fn pointer(val: &str) -> Pointer {
+    Pointer::parse(val).unwrap()
+}
+
+fn value(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+const CONFIG: &str = "{
+    // Bumped by the release script
+    \"version\": \"0.1.0\",
+    \"ports\": [80, 443], // public
+    \"tags\": [],
+    \"owner\": {
+        \"name\": \"ops\"
+    }
+}
+";
+
+#[test]
+fn test_set_existing() {
+    let mut doc = Document::parse(CONFIG);
+    doc.set(&pointer("/version"), &Value::from("0.2.0")).unwrap();
+    assert_eq!(doc.to_string(), CONFIG.replace("0.1.0", "0.2.0"));
+
+    doc.set(&pointer("/owner/name"), &value("{\"team\": \"ops\", \"ids\": [1]}")).unwrap();
+    assert_eq!(doc.to_string(), CONFIG.replace("0.1.0", "0.2.0").replace("\"name\": \"ops\"", "\"name\": {
+            \"team\": \"ops\",
+            \"ids\": [
+                1
+            ]
+        }"));
+}
+
+#[test]
+fn test_set_new_member() {
+    let mut doc = Document::parse(CONFIG);
+    doc.set(&pointer("/owner/email"), &Value::from("ops@example.com")).unwrap();
+    doc.set(&pointer("/debug"), &Value::from(true)).unwrap();
+
+    assert_eq!(doc.to_string(), "{
+    // Bumped by the release script
+    \"version\": \"0.1.0\",
+    \"ports\": [80, 443], // public
+    \"tags\": [],
+    \"owner\": {
+        \"name\": \"ops\",
+        \"email\": \"ops@example.com\"
+    },
+    \"debug\": true
+}
+");
+}
+
+#[test]
+fn test_set_new_items() {
+    let mut doc = Document::parse(CONFIG);
+    doc.set(&pointer("/ports/-"), &Value::from(8080)).unwrap();
+    doc.set(&pointer("/tags/0"), &Value::from("web")).unwrap();
+
+    assert_eq!(doc.to_string(), CONFIG.replace("[80, 443]", "[80, 443, 8080]").replace("[]", "[
+        \"web\"
+    ]"));
+    assert!(doc.set(&pointer("/ports/7"), &Value::Null).is_err());
+    assert!(doc.set(&pointer("/missing/key"), &Value::Null).is_err());
+}
+
+#[test]
+fn test_insert_after() {
+    let mut doc = Document::parse(CONFIG);
+    doc.insert_after(&pointer("/ports"), "hosts", &value("[\"a\"]")).unwrap();
+
+    assert_eq!(doc.to_string(), CONFIG.replace("\"ports\": [80, 443], // public\n", "\"ports\": [80, 443], // public
+    \"hosts\": [
+        \"a\"
+    ],
+"));
+    assert!(doc.insert_after(&pointer("/ports"), "tags", &Value::Null).is_err());
+}
+
+#[test]
+fn test_insert_into_compact() {
+    let mut doc = Document::parse("{\"a\": 1, \"b\": 2}");
+    doc.insert_after(&pointer("/a"), "c", &value("{\"d\": [1, 2]}")).unwrap();
+    doc.set(&pointer("/e"), &Value::Null).unwrap();
+
+    assert_eq!(doc.to_string(), "{\"a\": 1, \"c\": {\"d\":[1,2]}, \"b\": 2, \"e\": null}");
+}
+
+#[test]
+fn test_insert_after_lone_entry() {
+    let cases = [
+        ("{\"a\": 1}", "/b", "{\"a\": 1, \"b\": 2}"),
+        ("{\"a\":1}", "/b", "{\"a\":1,\"b\":2}"),
+        ("{ \"a\": 1 }", "/b", "{ \"a\": 1, \"b\": 2 }"),
+        ("[ 1 ]", "/-", "[ 1, 2 ]"),
+        ("[1]", "/-", "[1,2]")
+    ];
+    for (data, at, expected) in cases {
+        let mut doc = Document::parse(data);
+        doc.set(&pointer(at), &Value::from(2)).unwrap();
+        assert_eq!(doc.to_string(), expected, "{}", data);
+    }
+}
+
+#[test]
+fn test_remove() {
+    let mut doc = Document::parse(CONFIG);
+    assert_eq!(doc.remove(&pointer("/ports/0")).unwrap(), Value::from(80));
+    assert_eq!(doc.to_string(), CONFIG.replace("[80, 443]", "[443]"));
+
+    doc.remove(&pointer("/owner")).unwrap();
+    doc.remove(&pointer("/version")).unwrap();
+    assert_eq!(doc.to_string(), "{
+    \"ports\": [443], // public
+    \"tags\": []
+}
+");
+
+    assert!(doc.remove(&pointer("/ports/3")).is_err());
+    assert!(doc.remove(&Pointer::root()).is_err());
+}
+
+#[test]
+fn test_remove_last_keeps_comment() {
+    let mut doc = Document::parse("[\r\n  1, // one\r\n  2\r\n]");
+    doc.remove(&pointer("/1")).unwrap();
+    assert_eq!(doc.to_string(), "[\r\n  1 // one\r\n]");
+
+    doc.set(&pointer("/-"), &Value::from(3)).unwrap();
+    assert_eq!(doc.to_string(), "[\r\n  1, // one\r\n  3\r\n]");
+}
+
+#[test]
+fn test_rename() {
+    let mut doc = Document::parse(CONFIG);
+    doc.rename(&pointer("/owner/name"), "team").unwrap();
+    assert_eq!(doc.to_string(), CONFIG.replace("\"name\"", "\"team\""));
+
+    assert!(doc.rename(&pointer("/tags"), "ports").is_err());
+    assert!(doc.rename(&pointer("/ports/0"), "first").is_err());
+}
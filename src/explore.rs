@@ -0,0 +1,581 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::path::{Path, PathElement};
+use crate::pointer::Pointer;
+use crate::serializer::{write_string, Serializer};
+use crate::value::Value;
+
+/// A key press, decoded from the bytes a terminal sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Esc,
+    Char(char)
+}
+
+/// Decodes what one read from a raw mode terminal returned. Escape sequences
+/// arrive whole, so a lone escape byte is the escape key itself.
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek() == Some(&'[') || chars.peek() == Some(&'O') => {
+                chars.next();
+                match chars.next() {
+                    Some('A') => Key::Up,
+                    Some('B') => Key::Down,
+                    Some('C') => Key::Right,
+                    Some('D') => Key::Left,
+                    Some('H') => Key::Home,
+                    Some('F') => Key::End,
+                    Some(digit @ '1'..='8') => {
+                        // Sequences like `ESC [ 5 ~`
+                        if chars.peek() == Some(&'~') {
+                            chars.next();
+                        }
+                        match digit {
+                            '1' | '7' => Key::Home,
+                            '4' | '8' => Key::End,
+                            '5' => Key::PageUp,
+                            '6' => Key::PageDown,
+                            _ => continue
+                        }
+                    },
+                    _ => continue
+                }
+            },
+            '\x1b' => Key::Esc,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            c => Key::Char(c)
+        };
+        keys.push(key);
+    }
+
+    keys
+}
+
+/// One visible line of the tree.
+#[derive(Debug)]
+pub struct Row<'v> {
+    pub depth: usize,
+    pub value: &'v Value,
+    pub expanded: bool,
+    // The row of the enclosing container and the place in it
+    parent: Option<usize>,
+    position: usize
+}
+
+/// The state of the explorer, kept apart from the terminal so it can be
+/// driven by key presses in tests. Rows are only built for expanded
+/// containers and kept between key presses, expanding or collapsing splices
+/// them in or out, so large collapsed subtrees cost nothing.
+pub struct Explorer<'v> {
+    root: &'v Value,
+    // Nodes of the tree never move, so they are told apart by address
+    expanded: HashSet<*const Value>,
+    rows: Vec<Row<'v>>,
+    cursor: usize,
+    top: usize,
+    // The search being typed, shown instead of the status line
+    prompt: Option<String>,
+    query: Option<String>,
+    message: String,
+    // Text to put on the clipboard, taken by the terminal loop
+    clipboard: Option<String>
+}
+
+impl<'v> Explorer<'v> {
+    /// Starts with only the root expanded.
+    pub fn new(root: &'v Value) -> Explorer<'v> {
+        let mut expanded = HashSet::new();
+        expanded.insert(root as *const Value);
+        let mut explorer = Explorer {
+            root,
+            expanded,
+            rows: Vec::new(),
+            cursor: 0,
+            top: 0,
+            prompt: None,
+            query: None,
+            message: String::new(),
+            clipboard: None
+        };
+        explorer.rebuild();
+        explorer
+    }
+
+    pub fn rows(&self) -> &[Row<'v>] {
+        &self.rows
+    }
+
+    fn rebuild(&mut self) {
+        let mut rows = Vec::new();
+        self.collect(self.root, None, 0, 0, 0, &mut rows);
+        self.rows = rows;
+    }
+
+    // Rows for `value` and its expanded descendants, numbered as if `rows`
+    // started at row `first`
+    fn collect(&self, value: &'v Value, parent: Option<usize>, position: usize, depth: usize, first: usize,
+               rows: &mut Vec<Row<'v>>) {
+        let expanded = is_container(value) && self.expanded.contains(&(value as *const Value));
+        let row = first + rows.len();
+        rows.push(Row { depth, value, expanded, parent, position });
+        if !expanded {
+            return;
+        }
+
+        for position in 0..len(value) {
+            self.collect(child(value, position), Some(row), position, depth + 1, first, rows);
+        }
+    }
+
+    /// The path of the node shown on a row.
+    pub fn path(&self, row: usize) -> Path {
+        let mut elements = Vec::new();
+        let mut curr = &self.rows[row];
+        while let Some(parent) = curr.parent {
+            elements.push(element(self.rows[parent].value, curr.position));
+            curr = &self.rows[parent];
+        }
+
+        let mut path = Path::root();
+        for element in elements.into_iter().rev() {
+            path.push(element);
+        }
+        path
+    }
+
+    /// The path of the focused node.
+    pub fn focused(&self) -> Path {
+        self.path(self.cursor)
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn take_clipboard(&mut self) -> Option<String> {
+        self.clipboard.take()
+    }
+
+    /// Handles a key press, returns false once the explorer should close.
+    pub fn key(&mut self, key: Key, height: usize) -> bool {
+        if let Some(prompt) = &mut self.prompt {
+            match key {
+                Key::Enter => {
+                    let query = self.prompt.take().unwrap();
+                    if !query.is_empty() {
+                        self.query = Some(query);
+                    }
+                    self.next_match();
+                },
+                Key::Esc => self.prompt = None,
+                Key::Backspace => {
+                    prompt.pop();
+                },
+                Key::Char(c) if !c.is_control() => prompt.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        self.message.clear();
+        let page = height.saturating_sub(1).max(1);
+        match key {
+            Key::Char('q') | Key::Char('\x03') | Key::Esc => return false,
+            Key::Up | Key::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down | Key::Char('j') => self.move_to(self.cursor + 1),
+            Key::PageUp => self.cursor = self.cursor.saturating_sub(page),
+            Key::PageDown => self.move_to(self.cursor + page),
+            Key::Home | Key::Char('g') => self.cursor = 0,
+            Key::End | Key::Char('G') => self.move_to(usize::MAX),
+            Key::Right | Key::Char('l') => self.expand(),
+            Key::Left | Key::Char('h') => self.collapse(),
+            Key::Enter | Key::Char(' ') => self.toggle(),
+            Key::Char('/') => self.prompt = Some(String::new()),
+            Key::Char('n') => self.next_match(),
+            Key::Char('y') => self.copy(self.focused().to_string()),
+            Key::Char('p') => self.copy(Pointer::from(&self.focused()).to_string()),
+            _ => {}
+        }
+        true
+    }
+
+    fn move_to(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.rows.len() - 1);
+    }
+
+    fn expand(&mut self) {
+        let row = &self.rows[self.cursor];
+        if !is_container(row.value) || row.expanded {
+            return;
+        }
+        let value = row.value;
+        self.expanded.insert(value as *const Value);
+
+        let mut children = Vec::new();
+        for position in 0..len(value) {
+            self.collect(child(value, position), Some(self.cursor), position, row.depth + 1, self.cursor + 1 + children.len(), &mut children);
+        }
+        let (cursor, added) = (self.cursor, children.len());
+        for row in &mut self.rows[cursor + 1..] {
+            row.parent = row.parent.map(|parent| if parent > cursor { parent + added } else { parent });
+        }
+        self.rows.splice(self.cursor + 1..self.cursor + 1, children);
+        self.rows[self.cursor].expanded = true;
+    }
+
+    // Collapses the focused container, or moves up to its parent
+    fn collapse(&mut self) {
+        let row = &self.rows[self.cursor];
+        if !row.expanded {
+            if let Some(parent) = row.parent {
+                self.cursor = parent;
+            }
+            return;
+        }
+        self.expanded.remove(&(row.value as *const Value));
+
+        let depth = row.depth;
+        let end = self.rows[self.cursor + 1..].iter().position(|other| other.depth <= depth)
+            .map_or(self.rows.len(), |idx| self.cursor + 1 + idx);
+        let removed = end - self.cursor - 1;
+        self.rows.drain(self.cursor + 1..end);
+        let cursor = self.cursor;
+        for row in &mut self.rows[cursor + 1..] {
+            row.parent = row.parent.map(|parent| if parent > cursor { parent - removed } else { parent });
+        }
+        self.rows[self.cursor].expanded = false;
+    }
+
+    fn toggle(&mut self) {
+        if self.rows[self.cursor].expanded {
+            self.collapse();
+        } else {
+            self.expand();
+        }
+    }
+
+    fn copy(&mut self, text: String) {
+        self.message = format!("copied {}", text);
+        self.clipboard = Some(text);
+    }
+
+    // Searches the whole document, not just the visible rows, in document
+    // order after the focused node and wrapping around at the end. Nodes are
+    // visited one at a time from the focused one, stopping at the first match.
+    fn next_match(&mut self) {
+        let query = match &self.query {
+            Some(query) => query.to_lowercase(),
+            None => return
+        };
+
+        // The containers above the focused node, with the place in each
+        let mut stack = Vec::new();
+        let mut row = self.cursor;
+        while let Some(parent) = self.rows[row].parent {
+            stack.push((self.rows[parent].value, self.rows[row].position));
+            row = parent;
+        }
+        stack.reverse();
+
+        let start = self.rows[self.cursor].value;
+        let mut node = start;
+        loop {
+            node = next_in_order(self.root, &mut stack, node);
+            let key = match stack.last() {
+                Some((Value::Object(members), position)) => Some(members[*position].0.as_str()),
+                _ => None
+            };
+            if matches(key, node, &query) {
+                break;
+            }
+            if std::ptr::eq(node, start) {
+                self.message = format!("no match for {}", query);
+                return;
+            }
+        }
+
+        // Reveal the match by expanding everything above it
+        let mut changed = false;
+        for (container, _) in &stack {
+            changed |= self.expanded.insert(*container as *const Value);
+        }
+        if changed {
+            self.rebuild();
+        }
+        let mut row = 0;
+        for (_, position) in &stack {
+            row += 1 + self.rows[row + 1..].iter()
+                .position(|other| other.parent == Some(row) && other.position == *position)
+                .unwrap();
+        }
+        self.cursor = row;
+    }
+
+    /// Renders a screen of `height` lines, the last one being the status
+    /// line. The focused line starts with `>`.
+    pub fn view(&mut self, width: usize, height: usize) -> Vec<String> {
+        let visible = height.saturating_sub(1).max(1);
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if self.cursor >= self.top + visible {
+            self.top = self.cursor + 1 - visible;
+        }
+
+        let mut lines: Vec<String> = (self.top..self.rows.len().min(self.top + visible))
+            .map(|row| truncate(&self.line(row), width))
+            .collect();
+        lines.resize(visible, String::new());
+
+        let status = match &self.prompt {
+            Some(prompt) => format!("/{}", prompt),
+            None => {
+                let focused = self.focused();
+                let mut status = format!("{}  {}  ({}/{})", focused, Pointer::from(&focused), self.cursor + 1, self.rows.len());
+                if !self.message.is_empty() {
+                    status.push_str("  ");
+                    status.push_str(&self.message);
+                }
+                status
+            }
+        };
+        lines.push(truncate(&status, width));
+        lines
+    }
+
+    fn line(&self, idx: usize) -> String {
+        let row = &self.rows[idx];
+        let mut out = String::from(if idx == self.cursor { "> " } else { "  " });
+        out.push_str(&"  ".repeat(row.depth));
+        out.push_str(match (is_container(row.value), row.expanded) {
+            (true, true) => "▾ ",
+            (true, false) => "▸ ",
+            _ => "  "
+        });
+
+        match row.parent.map(|parent| self.rows[parent].value) {
+            Some(Value::Object(members)) => {
+                write_string(&mut out, &members[row.position].0);
+                out.push_str(": ");
+            },
+            Some(_) => out.push_str(&format!("[{}]: ", row.position)),
+            None => {}
+        }
+
+        match row.value {
+            Value::Array(items) => out.push_str(&format!("[{} {}]", items.len(), if items.len() == 1 { "item" } else { "items" })),
+            Value::Object(members) => out.push_str(&format!("{{{} {}}}", members.len(), if members.len() == 1 { "key" } else { "keys" })),
+            scalar => out.push_str(&Serializer::compact().serialize(scalar))
+        }
+        out
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(width.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+fn len(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => items.len(),
+        Value::Object(members) => members.len(),
+        _ => 0
+    }
+}
+
+fn child(value: &Value, position: usize) -> &Value {
+    match value {
+        Value::Array(items) => &items[position],
+        Value::Object(members) => &members[position].1,
+        _ => unreachable!("scalars have no children")
+    }
+}
+
+fn element(container: &Value, position: usize) -> PathElement {
+    match container {
+        Value::Object(members) => PathElement::Key(members[position].0.clone()),
+        _ => PathElement::Index(position)
+    }
+}
+
+// Steps to the node after `node` in document order, wrapping around to the
+// root, and keeps `stack` pointing at its containers
+fn next_in_order<'v>(root: &'v Value, stack: &mut Vec<(&'v Value, usize)>, node: &'v Value) -> &'v Value {
+    if len(node) > 0 {
+        stack.push((node, 0));
+        return child(node, 0);
+    }
+    while let Some((container, position)) = stack.last_mut() {
+        if *position + 1 < len(container) {
+            *position += 1;
+            return child(container, *position);
+        }
+        stack.pop();
+    }
+    root
+}
+
+// Keys and scalars match, strings without their quotes
+fn matches(key: Option<&str>, value: &Value, query: &str) -> bool {
+    key.is_some_and(|key| key.to_lowercase().contains(query)) || match value {
+        Value::String(val) => val.to_lowercase().contains(query),
+        Value::Array(_) | Value::Object(_) => false,
+        scalar => Serializer::compact().serialize(scalar).contains(query)
+    }
+}
+
+/// Explores `value` on the controlling terminal until the user quits. The
+/// terminal is used directly, so the document may come from stdin.
+pub fn run(value: &Value) -> io::Result<()> {
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    let saved = stty(&tty, &["-g"])?;
+    stty(&tty, &["raw", "-echo"])?;
+    let _restore = Restore { tty: tty.try_clone()?, saved };
+    write!(tty, "\x1b[?1049h\x1b[?25l")?;
+
+    explore(&mut tty, value)
+}
+
+// Puts the terminal back the way it was, also when exploring fails or panics
+struct Restore {
+    tty: File,
+    saved: String
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let _ = write!(self.tty, "\x1b[?25h\x1b[?1049l");
+        let _ = stty(&self.tty, &[self.saved.trim()]);
+    }
+}
+
+// Set when the terminal is resized, so its size is only asked for again then
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+fn watch_resize() {
+    extern "C" fn resized(_signal: libc::c_int) {
+        RESIZED.store(true, Ordering::Relaxed);
+    }
+
+    // Without SA_RESTART a resize interrupts waiting for a key, so the
+    // screen is redrawn right away
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = resized as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(not(unix))]
+fn watch_resize() {}
+
+fn explore(tty: &mut File, value: &Value) -> io::Result<()> {
+    let mut explorer = Explorer::new(value);
+    let mut buffer = [0; 64];
+    watch_resize();
+    let mut screen_size = size(tty);
+
+    loop {
+        if RESIZED.swap(false, Ordering::Relaxed) {
+            screen_size = size(tty);
+        }
+        let (height, width) = screen_size;
+        let mut screen = String::from("\x1b[H");
+        for (idx, line) in explorer.view(width, height).iter().enumerate() {
+            // The focused line and the status line are drawn in reverse video
+            if line.starts_with('>') || idx + 1 == height {
+                screen.push_str(&format!("\x1b[7m{}\x1b[0m", line));
+            } else {
+                screen.push_str(line);
+            }
+            screen.push_str("\x1b[K");
+            if idx + 1 < height {
+                screen.push_str("\r\n");
+            }
+        }
+        tty.write_all(screen.as_bytes())?;
+        tty.flush()?;
+
+        let read = match tty.read(&mut buffer) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            read => read?
+        };
+        for key in parse_keys(&buffer[..read]) {
+            if !explorer.key(key, height) {
+                return Ok(());
+            }
+        }
+        // Terminals that support OSC 52 put the text on the system clipboard
+        if let Some(text) = explorer.take_clipboard() {
+            write!(tty, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+        }
+    }
+}
+
+fn stty(tty: &File, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::from(tty.try_clone()?)).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Rows and columns, falling back to the classic 24 by 80
+fn size(tty: &File) -> (usize, usize) {
+    let size = stty(tty, &["size"]).unwrap_or_default();
+    let mut numbers = size.split_whitespace().filter_map(|number| number.parse().ok());
+    match (numbers.next(), numbers.next()) {
+        (Some(rows), Some(columns)) if rows > 0 && columns > 0 => (rows, columns),
+        _ => (24, 80)
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (idx, byte)| group | (*byte as u32) << (16 - 8 * idx));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                out.push(ALPHABET[(group >> (18 - 6 * idx) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,123 @@
+use crate::explore::{base64, parse_keys, Explorer, Key};
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+fn press(explorer: &mut Explorer, keys: &str) {
+    for key in parse_keys(keys.as_bytes()) {
+        assert!(explorer.key(key, 10));
+    }
+}
+
+#[test]
+fn test_parse_keys() {
+    assert_eq!(parse_keys(b"\x1b[A\x1b[B\x1bOC\x1b[6~"), vec![Key::Up, Key::Down, Key::Right, Key::PageDown]);
+    assert_eq!(parse_keys(b"\x1b"), vec![Key::Esc]);
+    assert_eq!(parse_keys("/é\r\x7f".as_bytes()), vec![Key::Char('/'), Key::Char('é'), Key::Enter, Key::Backspace]);
+}
+
+#[test]
+fn test_expand_and_collapse() {
+    let value = parse("{\"a\": [1, {\"b\": null}], \"c\": \"x\"}");
+    let mut explorer = Explorer::new(&value);
+    assert_eq!(explorer.view(40, 4), vec![
+        "> ▾ {2 keys}",
+        "    ▸ \"a\": [2 items]",
+        "      \"c\": \"x\"",
+        "$    (1/3)"
+    ]);
+
+    // Collapsed containers are not walked
+    press(&mut explorer, "jl");
+    assert_eq!(explorer.rows().len(), 5);
+    press(&mut explorer, "jj ");
+    assert_eq!(explorer.focused().to_string(), "$.a[1]");
+    assert_eq!(explorer.rows().len(), 6);
+
+    // Left on a leaf moves to its parent, then collapses it
+    press(&mut explorer, "jhh");
+    assert_eq!(explorer.focused().to_string(), "$.a[1]");
+    assert_eq!(explorer.rows().len(), 5);
+    press(&mut explorer, "hh");
+    assert_eq!(explorer.focused().to_string(), "$.a");
+    assert_eq!(explorer.rows().len(), 3);
+    assert!(!explorer.key(Key::Char('q'), 10));
+}
+
+#[test]
+fn test_rows_kept_between_keys() {
+    let value = parse("[[1, 2], [3], {\"k\": [4]}]");
+    let mut explorer = Explorer::new(&value);
+
+    // Rows after an expanded or collapsed container still know their place
+    press(&mut explorer, "jljjjljjjl");
+    assert_eq!(explorer.focused().to_string(), "$[2]");
+    assert_eq!(explorer.rows().len(), 8);
+    press(&mut explorer, "gjh");
+    assert_eq!(explorer.rows().len(), 6);
+    press(&mut explorer, "GG");
+    assert_eq!(explorer.focused().to_string(), "$[2].k");
+    press(&mut explorer, "l");
+    assert_eq!(explorer.rows().len(), 7);
+    press(&mut explorer, "j");
+    assert_eq!(explorer.focused().to_string(), "$[2].k[0]");
+    press(&mut explorer, "hh");
+    assert_eq!(explorer.focused().to_string(), "$[2].k");
+}
+
+#[test]
+fn test_view_scrolls() {
+    let value = parse("[1, 2, 3, 4, 5, 6]");
+    let mut explorer = Explorer::new(&value);
+    press(&mut explorer, "GG");
+    let view = explorer.view(12, 4);
+    assert_eq!(view, vec!["      [3]: 4", "      [4]: 5", ">     [5]: 6", "$[5]  /5  (…"]);
+    press(&mut explorer, "\x1b[5~");
+    assert_eq!(explorer.focused().to_string(), "$");
+}
+
+#[test]
+fn test_search() {
+    let value = parse("{\"users\": [{\"name\": \"Ann\"}, {\"name\": \"Bob\", \"admin\": true}], \"Bobby\": 1}");
+    let mut explorer = Explorer::new(&value);
+
+    // Values and keys match in document order, revealing collapsed nodes
+    press(&mut explorer, "/bob\r");
+    assert_eq!(explorer.focused().to_string(), "$.users[1].name");
+    assert_eq!(explorer.rows().len(), 7);
+    press(&mut explorer, "n");
+    assert_eq!(explorer.focused().to_string(), "$.Bobby");
+    press(&mut explorer, "n");
+    assert_eq!(explorer.focused().to_string(), "$.users[1].name");
+
+    press(&mut explorer, "/true\r");
+    assert_eq!(explorer.focused().to_string(), "$.users[1].admin");
+    press(&mut explorer, "/nothing\r");
+    assert_eq!(explorer.message(), "no match for nothing");
+    press(&mut explorer, "/ab\x1b");
+    assert_eq!(explorer.focused().to_string(), "$.users[1].admin");
+}
+
+#[test]
+fn test_copy_path() {
+    let value = parse("{\"a/b\": {\"c d\": 1}}");
+    let mut explorer = Explorer::new(&value);
+    press(&mut explorer, "jljy");
+    assert_eq!(explorer.take_clipboard().as_deref(), Some("$[\"a/b\"][\"c d\"]"));
+    assert_eq!(explorer.message(), "copied $[\"a/b\"][\"c d\"]");
+    press(&mut explorer, "p");
+    assert_eq!(explorer.take_clipboard().as_deref(), Some("/a~1b/c d"));
+    assert_eq!(explorer.take_clipboard(), None);
+}
+
+#[test]
+fn test_base64() {
+    assert_eq!(base64(b""), "");
+    assert_eq!(base64(b"f"), "Zg==");
+    assert_eq!(base64(b"fo"), "Zm8=");
+    assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+}
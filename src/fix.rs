@@ -0,0 +1,153 @@
+use crate::diff::{edit_script, Step};
+use crate::lexer::{Span, TokenIter};
+use crate::parser::{ParseError, Parser};
+
+/// Applies the safe fixes suggested by the parser one error at a time, until
+/// the document parses or an error comes without a safe fix. Returns the
+/// fixed text along with the errors that were fixed, in the order they were
+/// found and positioned in `contents`.
+pub fn apply_safe_fixes(contents: &str) -> (String, Vec<ParseError>) {
+    let mut fixed = contents.to_string();
+    let mut applied = Vec::new();
+    // Where each fix left the text it changed and how many bytes it removed
+    let mut edits: Vec<(usize, usize)> = Vec::new();
+
+    loop {
+        let result = Parser::checked(Box::new(TokenIter::new(&fixed).checked())).try_validate();
+        // Safe fixes only ever remove text, so this runs out eventually
+        let mut err = match result {
+            Ok(()) => break,
+            Err(err) => err
+        };
+        let fix = match err.fix() {
+            Some(fix) if fix.safe => fix.clone(),
+            _ => break
+        };
+        fixed.replace_range(fix.span.start..fix.span.end, &fix.replacement);
+
+        let original = |offset: usize| edits.iter().rev().fold(offset, |offset, (end, removed)| {
+            if offset >= *end { offset + removed } else { offset }
+        });
+        err.span = Span { start: original(err.span.start), end: original(err.span.end) };
+        if let Some(fix) = err.hint.as_mut().and_then(|hint| hint.fix.as_mut()) {
+            fix.span = Span { start: original(fix.span.start), end: original(fix.span.end) };
+        }
+        let (line, column) = locate(contents, err.span.start);
+        err.line = line;
+        err.column = column;
+
+        edits.push((fix.span.start + fix.replacement.len(), fix.span.end - fix.span.start - fix.replacement.len()));
+        applied.push(err);
+    }
+
+    (fixed, applied)
+}
+
+// Line and column of a byte offset, counted the way the lexer counts them
+fn locate(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = match before.rfind('\n') {
+        Some(idx) => before[idx + 1..].chars().count() + 1,
+        None => before.chars().count() + 1
+    };
+    (line, column)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str)
+}
+
+// Lines of unchanged text kept around each change
+const CONTEXT: usize = 3;
+
+/// Renders the change from `old` to `new` as a unified diff of `file_path`,
+/// empty when nothing changed.
+pub fn unified_diff(old: &str, new: &str, file_path: &str) -> String {
+    // Lines keep their line feed, so a last line without one differs from
+    // the same line with it
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let lines = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = (0..lines.len())
+        .filter(|idx| !matches!(lines[*idx], Line::Same(_)))
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Changes close enough to share their context go in the same hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for idx in changes {
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + 1 + CONTEXT).min(lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end))
+        }
+    }
+
+    let file_path = file_path.trim_start_matches('/');
+    let mut out = format!("--- a/{}\n+++ b/{}\n", file_path, file_path);
+    for (start, end) in hunks {
+        let is_old = |line: &&Line| !matches!(line, Line::Added(_));
+        let is_new = |line: &&Line| !matches!(line, Line::Removed(_));
+        let old_start = lines[..start].iter().filter(is_old).count();
+        let new_start = lines[..start].iter().filter(is_new).count();
+        let old_len = lines[start..end].iter().filter(is_old).count();
+        let new_len = lines[start..end].iter().filter(is_new).count();
+
+        out.push_str(&format!("@@ -{} +{} @@\n", range(old_start, old_len), range(new_start, new_len)));
+        for line in &lines[start..end] {
+            let (prefix, text) = match line {
+                Line::Same(text) => (' ', text),
+                Line::Removed(text) => ('-', text),
+                Line::Added(text) => ('+', text)
+            };
+            out.push(prefix);
+            out.push_str(text);
+            if !text.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+// An empty range points at the line before it, as diff and patch expect
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len)
+    }
+}
+
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    let steps = edit_script(old, new, old.len() + new.len()).expect("never more edits than lines");
+    let (mut x, mut y) = (0, 0);
+    steps.into_iter()
+        .map(|step| match step {
+            Step::Same => {
+                x += 1;
+                y += 1;
+                Line::Same(old[x - 1])
+            },
+            Step::Deleted => {
+                x += 1;
+                Line::Removed(old[x - 1])
+            },
+            Step::Inserted => {
+                y += 1;
+                Line::Added(new[y - 1])
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,114 @@
+use crate::fix::{apply_safe_fixes, unified_diff};
+
+fn fixed(data: &str) -> String {
+    apply_safe_fixes(data).0
+}
+
+#[test]
+fn test_valid_input_unchanged() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let (fixed, applied) = apply_safe_fixes(&contents);
+
+    assert_eq!(fixed, contents);
+    assert!(applied.is_empty());
+}
+
+#[test]
+fn test_safe_fixes() {
+    assert_eq!(fixed("[1, 2, ]"), "[1, 2 ]");
+    assert_eq!(fixed("{\"a\": [1,], \"b\": {\"c\": 2,},}"), "{\"a\": [1], \"b\": {\"c\": 2}}");
+    assert_eq!(fixed("[007, 0.5, -01]"), "[7, 0.5, -1]");
+    assert_eq!(fixed("\u{feff}{\"a\": 1}"), "{\"a\": 1}");
+
+    let (_, applied) = apply_safe_fixes("[00, 1,]");
+    let messages: Vec<&str> = applied.iter().map(|err| err.message.as_str()).collect();
+    assert_eq!(messages, vec!["Illegal leading zero found for number", "Unexpected token EndArray"]);
+}
+
+#[test]
+fn test_fixes_positioned_in_original() {
+    let contents = format!("[{}\n{}]", "[1,], ".repeat(3), "[2,]");
+    let (fixed, applied) = apply_safe_fixes(&contents);
+    assert_eq!(fixed, "[[1], [1], [1], \n[2]]");
+
+    let positions: Vec<(usize, usize, usize)> = applied.iter().map(|err| (err.line, err.column, err.span.start)).collect();
+    assert_eq!(positions, vec![(1, 5, 4), (1, 11, 10), (1, 17, 16), (2, 4, 23)]);
+    for err in &applied {
+        let fix = err.fix().unwrap();
+        assert_eq!(&contents[fix.span.start..fix.span.end], ",");
+    }
+}
+
+#[test]
+fn test_stops_at_unsafe_error() {
+    // The trailing comma comes after the missing one, which needs a review
+    assert_eq!(fixed("[1 2, 3,]"), "[1 2, 3,]");
+    assert_eq!(fixed("[1,] // done"), "[1] // done");
+    assert_eq!(fixed("{a: 1,}"), "{a: 1,}");
+}
+
+#[test]
+fn test_unified_diff() {
+    assert_eq!(unified_diff("[1]\n", "[1]\n", "same.json"), "");
+
+    let old = "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3,\n  \"d\": 4,\n  \"e\": [1, 2,],\n}\n";
+    let new = fixed(old);
+    assert_eq!(unified_diff(old, &new, "data.json"), "\
+--- a/data.json
++++ b/data.json
+@@ -3,5 +3,5 @@
+   \"b\": 2,
+   \"c\": 3,
+   \"d\": 4,
+-  \"e\": [1, 2,],
++  \"e\": [1, 2]
+ }
+");
+}
+
+#[test]
+fn test_unified_diff_hunks() {
+    let old: String = (1..=20).map(|line| format!("{}\n", line)).collect();
+    let new = old.replacen("2\n", "two\n", 1).replace("18\n", "").replace("20\n", "20\n21\n");
+    assert_eq!(unified_diff(&old, &new, "lines"), "\
+--- a/lines
++++ b/lines
+@@ -1,5 +1,5 @@
+ 1
+-2
++two
+ 3
+ 4
+ 5
+@@ -15,6 +15,6 @@
+ 15
+ 16
+ 17
+-18
+ 19
+ 20
++21
+");
+    assert_eq!(unified_diff("", "a\n", "new"), "--- a/new\n+++ b/new\n@@ -0,0 +1 @@\n+a\n");
+}
+
+#[test]
+fn test_unified_diff_no_newline() {
+    assert_eq!(unified_diff("[1,]", "[1]", "data.json"), "\
+--- a/data.json
++++ b/data.json
+@@ -1 +1 @@
+-[1,]
+\\ No newline at end of file
++[1]
+\\ No newline at end of file
+");
+    assert_eq!(unified_diff("a", "a\n", "end"), "\
+--- a/end
++++ b/end
+@@ -1 +1 @@
+-a
+\\ No newline at end of file
++a
+");
+}
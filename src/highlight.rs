@@ -0,0 +1,172 @@
+use std::cell::RefCell;
+
+use crate::lexer::{JsonKind, Token, TokenIter};
+use crate::parser::Parser;
+
+/// How a piece of highlighted output is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Key,
+    String,
+    Number,
+    Literal,
+    Punctuation,
+    Whitespace
+}
+
+/// Pretty prints a document straight from its tokens, laid out like the
+/// pretty serializer, and splits the output into pieces by style. The tokens
+/// go through the parser on the way, so invalid JSON panics like it does.
+pub fn highlight(contents: &str) -> Vec<(Style, String)> {
+    let layout = RefCell::new(Layout { pieces: Vec::new(), containers: Vec::new(), expect_key: false, open: false });
+
+    let tokens = TokenIter::new(contents).inspect(|token| layout.borrow_mut().token(token));
+    Parser::new(Box::new(tokens)).validate();
+
+    layout.into_inner().pieces
+}
+
+/// Highlighted output for a terminal, using ANSI escape codes.
+pub fn ansi(contents: &str) -> String {
+    let mut out = String::with_capacity(contents.len() * 2);
+    for (style, text) in highlight(contents) {
+        let code = match style {
+            Style::Key => "1;34",
+            Style::String => "32",
+            Style::Number => "36",
+            Style::Literal => "35",
+            Style::Punctuation => "1",
+            Style::Whitespace => {
+                out.push_str(&text);
+                continue;
+            }
+        };
+        out.push_str(&format!("\x1b[{}m{}\x1b[0m", code, text));
+    }
+    out
+}
+
+/// A standalone HTML page showing the highlighted document.
+pub fn html(contents: &str, title: &str) -> String {
+    let mut body = String::with_capacity(contents.len() * 3);
+    for (style, text) in highlight(contents) {
+        let class = match style {
+            Style::Key => "key",
+            Style::String => "string",
+            Style::Number => "number",
+            Style::Literal => "literal",
+            Style::Punctuation => "punctuation",
+            Style::Whitespace => {
+                body.push_str(&text);
+                continue;
+            }
+        };
+        body.push_str(&format!("<span class=\"{}\">{}</span>", class, escape_html(&text)));
+    }
+
+    format!("\
+<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>
+pre {{ font-family: ui-monospace, Menlo, Consolas, monospace; }}
+.key {{ color: #1f4fb4; font-weight: bold; }}
+.string {{ color: #207a2a; }}
+.number {{ color: #0b7a8a; }}
+.literal {{ color: #8a2a9e; }}
+.punctuation {{ color: #555555; }}
+</style>
+</head>
+<body>
+<pre>{}</pre>
+</body>
+</html>
+", escape_html(title), body)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Lays tokens out one at a time. An opened container waits for its first
+// token before breaking the line, so empty ones stay on a single line.
+struct Layout {
+    pieces: Vec<(Style, String)>,
+    // Whether each enclosing container is an object
+    containers: Vec<bool>,
+    expect_key: bool,
+    open: bool
+}
+
+impl Layout {
+    fn token(&mut self, token: &Token) {
+        match token.kind {
+            JsonKind::BeginObject | JsonKind::BeginArray => {
+                self.value();
+                self.push(Style::Punctuation, &token.text);
+                let is_object = token.kind == JsonKind::BeginObject;
+                self.containers.push(is_object);
+                self.expect_key = is_object;
+                self.open = true;
+            },
+            JsonKind::EndObject | JsonKind::EndArray => {
+                self.containers.pop();
+                if !self.open {
+                    self.newline();
+                }
+                self.open = false;
+                self.push(Style::Punctuation, &token.text);
+            },
+            JsonKind::ValueSeperator => {
+                self.push(Style::Punctuation, &token.text);
+                self.newline();
+                self.expect_key = self.containers.last() == Some(&true);
+            },
+            JsonKind::NameSeperator => {
+                self.push(Style::Punctuation, &token.text);
+                self.push(Style::Whitespace, " ");
+            },
+            JsonKind::StringVal => {
+                self.value();
+                let style = if self.expect_key { Style::Key } else { Style::String };
+                self.push(style, &token.text);
+                self.expect_key = false;
+            },
+            JsonKind::True | JsonKind::False | JsonKind::Null => {
+                self.value();
+                self.push(Style::Literal, &token.text);
+            },
+            JsonKind::Plus | JsonKind::Minus | JsonKind::Digit | JsonKind::Zero | JsonKind::DecimalPoint | JsonKind::E => {
+                self.value();
+                self.push(Style::Number, &token.text);
+            },
+            // The parser rejects comments, whitespace is replaced by the layout
+            _ => {}
+        }
+    }
+
+    // Breaks the line after an opening bracket once it turns out not to be empty
+    fn value(&mut self) {
+        if self.open {
+            self.open = false;
+            self.newline();
+        }
+    }
+
+    fn newline(&mut self) {
+        self.push(Style::Whitespace, &format!("\n{}", "  ".repeat(self.containers.len())));
+    }
+
+    // Numbers arrive a character at a time, so neighbours of one style merge
+    fn push(&mut self, style: Style, text: &str) {
+        match self.pieces.last_mut() {
+            Some((last, piece)) if *last == style && style != Style::Punctuation => piece.push_str(text),
+            _ => self.pieces.push((style, text.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
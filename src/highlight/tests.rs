@@ -0,0 +1,56 @@
+use crate::highlight::{ansi, highlight, html, Style};
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+
+fn plain(contents: &str) -> String {
+    highlight(contents).into_iter().map(|(_, text)| text).collect()
+}
+
+#[test]
+fn test_matches_pretty_serializer() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let value = Parser::new(Box::new(TokenIter::new(&contents))).parse();
+
+    assert_eq!(plain(&contents), Serializer::new().serialize(&value));
+    assert_eq!(plain("{\"a\":{ },\"b\":[\n]}"), "{\n  \"a\": {},\n  \"b\": []\n}");
+}
+
+#[test]
+fn test_styles() {
+    let pieces = highlight("{\"a\": [\"b\", -1.5e3, true, null], \"c\": {\"d\": \"e\"}}");
+    let styled: Vec<(Style, &str)> = pieces.iter()
+        .filter(|(style, _)| *style != Style::Whitespace)
+        .map(|(style, text)| (*style, text.as_str()))
+        .collect();
+
+    assert_eq!(styled, vec![
+        (Style::Punctuation, "{"), (Style::Key, "\"a\""), (Style::Punctuation, ":"), (Style::Punctuation, "["),
+        (Style::String, "\"b\""), (Style::Punctuation, ","), (Style::Number, "-1.5e3"), (Style::Punctuation, ","),
+        (Style::Literal, "true"), (Style::Punctuation, ","), (Style::Literal, "null"), (Style::Punctuation, "]"),
+        (Style::Punctuation, ","), (Style::Key, "\"c\""), (Style::Punctuation, ":"), (Style::Punctuation, "{"),
+        (Style::Key, "\"d\""), (Style::Punctuation, ":"), (Style::String, "\"e\""), (Style::Punctuation, "}"),
+        (Style::Punctuation, "}")
+    ]);
+}
+
+#[test]
+fn test_ansi() {
+    assert_eq!(ansi("{\"a\":[1]}"), "\x1b[1m{\x1b[0m\n  \x1b[1;34m\"a\"\x1b[0m\x1b[1m:\x1b[0m \x1b[1m[\x1b[0m\n    \
+                                    \x1b[36m1\x1b[0m\n  \x1b[1m]\x1b[0m\n\x1b[1m}\x1b[0m");
+}
+
+#[test]
+fn test_html() {
+    let page = html("[\"<b> & co\"]", "a<b>.json");
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("<title>a&lt;b&gt;.json</title>"));
+    assert!(page.contains("<pre><span class=\"punctuation\">[</span>\n  \
+                           <span class=\"string\">&quot;&lt;b&gt; &amp; co&quot;</span>\n<span class=\"punctuation\">]</span></pre>"));
+}
+
+#[test]
+#[should_panic(expected = "Expected token")]
+fn test_invalid() {
+    highlight("[1 2]");
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use crate::lexer::Span;
+
+/// A suggested edit, replacing the text in `span` with `replacement`. Safe
+/// fixes keep the meaning of the document and can be applied unreviewed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub span: Span,
+    pub replacement: String,
+    pub safe: bool
+}
+
+/// Explains the likely mistake behind an error, with an edit that fixes it
+/// when one is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub message: String,
+    pub fix: Option<Fix>
+}
+
+impl Hint {
+    fn new(message: &str, span: Span, replacement: &str) -> Hint {
+        Hint {
+            message: message.to_string(),
+            fix: Some(Fix { span, replacement: replacement.to_string(), safe: false })
+        }
+    }
+
+    fn safe(message: &str, span: Span, replacement: &str) -> Hint {
+        Hint {
+            message: message.to_string(),
+            fix: Some(Fix { span, replacement: replacement.to_string(), safe: true })
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Words from other languages that stand in for JSON keywords.
+pub fn keyword(word: &str, span: Span) -> Option<Hint> {
+    let suggestion = match word.to_lowercase().as_str() {
+        "true" => "true",
+        "false" => "false",
+        "null" | "none" | "nil" | "undefined" => "null",
+        "nan" | "infinity" => return Some(Hint {
+            message: format!("{} is not a JSON number, use null or a string instead", word),
+            fix: None
+        }),
+        _ => return None
+    };
+
+    Some(Hint::new(&format!("did you mean `{}`?", suggestion), span, suggestion))
+}
+
+const UNQUOTED: &str = "strings must be wrapped in double quotes";
+
+/// A bare word, most likely a string or key missing its quotes.
+pub fn unquoted(word: &str, span: Span) -> Hint {
+    Hint::new(UNQUOTED, span, &format!("\"{}\"", word))
+}
+
+/// Narrows an unquoted word hint when the parser was expecting a key, other
+/// hints are left as they are.
+pub fn unquoted_key(hint: &Hint) -> Hint {
+    match hint.message.as_str() {
+        UNQUOTED => Hint { message: "object keys must be wrapped in double quotes".to_string(), ..hint.clone() },
+        _ => hint.clone()
+    }
+}
+
+/// A single quoted string, `text` holding everything between the quotes
+/// when the closing one was found.
+pub fn single_quotes(text: Option<&str>, span: Span) -> Hint {
+    let message = "JSON strings use double quotes";
+    match text {
+        Some(text) => Hint::new(message, span, &double_quote(text)),
+        None => Hint { message: message.to_string(), fix: None }
+    }
+}
+
+pub fn trailing_comma(comma: Span) -> Hint {
+    Hint::safe("trailing commas are not allowed, remove this comma", comma, "")
+}
+
+/// One zero too many in front of a number, removed one at a time.
+pub fn leading_zero(zero: Span) -> Hint {
+    Hint::safe("numbers can not start with a zero, remove it", zero, "")
+}
+
+pub fn byte_order_mark(span: Span) -> Hint {
+    Hint::safe("JSON text must not start with a byte order mark, remove it", span, "")
+}
+
+/// A value follows the previous one directly, `at` being where the comma goes.
+pub fn missing_comma(at: usize, container: &str) -> Hint {
+    Hint::new(&format!("{} must be separated by commas", container), Span { start: at, end: at }, ",")
+}
+
+/// A key followed directly by its value, `at` being where the colon goes.
+pub fn missing_colon(at: usize) -> Hint {
+    Hint::new("keys must be followed by a colon", Span { start: at, end: at }, ":")
+}
+
+pub fn comment(span: Span) -> Hint {
+    Hint::new("JSON does not allow comments", span, "")
+}
+
+// Escapes are kept as written, except that quotes swap which one needs escaping
+fn double_quote(text: &str) -> String {
+    let mut out = String::from("\"");
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => out.push('\''),
+                Some(next) => {
+                    out.push('\\');
+                    out.push(next);
+                },
+                None => out.push('\\')
+            },
+            '"' => out.push_str("\\\""),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests;
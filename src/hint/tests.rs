@@ -0,0 +1,74 @@
+use crate::hint::Hint;
+use crate::lexer::TokenIter;
+use crate::parser::{ParseError, Parser};
+
+fn parse_error(data: &str) -> ParseError {
+    Parser::checked(Box::new(TokenIter::new(data).checked())).try_parse().unwrap_err()
+}
+
+fn hint(data: &str) -> Hint {
+    *parse_error(data).hint.unwrap()
+}
+
+// Applies the suggested fix, which must leave valid JSON behind
+fn fixed(data: &str) -> String {
+    let fix = hint(data).fix.unwrap();
+    let out = format!("{}{}{}", &data[..fix.span.start], fix.replacement, &data[fix.span.end..]);
+    Parser::checked(Box::new(TokenIter::new(&out).checked())).try_parse().unwrap();
+    out
+}
+
+#[test]
+fn test_keywords() {
+    assert_eq!(hint("{\"a\": True}").message, "did you mean `true`?");
+    assert_eq!(fixed("[TRUE, false]"), "[true, false]");
+    assert_eq!(fixed("{\"a\": None}"), "{\"a\": null}");
+    assert_eq!(fixed("[1, undefined]"), "[1, null]");
+    assert_eq!(fixed("[nil]"), "[null]");
+    assert_eq!(hint("[NaN]").fix, None);
+}
+
+#[test]
+fn test_quotes() {
+    assert_eq!(hint("['a']").message, "JSON strings use double quotes");
+    assert_eq!(fixed("{\"ü\": 'it\\'s \"x\"'}"), "{\"ü\": \"it's \\\"x\\\"\"}");
+    assert_eq!(hint("['open\n]").fix, None);
+
+    assert_eq!(hint("{user_id: 1}").message, "object keys must be wrapped in double quotes");
+    assert_eq!(fixed("{user_id2: 1}"), "{\"user_id2\": 1}");
+    assert_eq!(fixed("[1, abc]"), "[1, \"abc\"]");
+}
+
+#[test]
+fn test_commas() {
+    assert_eq!(hint("[1, 2, ]").message, "trailing commas are not allowed, remove this comma");
+    assert_eq!(fixed("[1, 2, ]"), "[1, 2 ]");
+    assert_eq!(fixed("{\"a\": {\"b\": 1,\n}}"), "{\"a\": {\"b\": 1\n}}");
+
+    assert_eq!(hint("{\"a\": 1\n \"b\": 2}").message, "members must be separated by commas");
+    assert_eq!(fixed("{\"a\": 1\n \"b\": 2}"), "{\"a\": 1,\n \"b\": 2}");
+    assert_eq!(fixed("[[1] {}]"), "[[1], {}]");
+}
+
+#[test]
+fn test_colons_and_comments() {
+    assert_eq!(fixed("{\"a\" 1}"), "{\"a\": 1}");
+    assert_eq!(hint("[1, /* two */ 2]").message, "JSON does not allow comments");
+    assert_eq!(fixed("[1 // one\n]"), "[1 \n]");
+}
+
+#[test]
+fn test_safe_fixes() {
+    assert!(hint("[1, 2, ]").fix.unwrap().safe);
+    assert_eq!(hint("{\"a\": [1, 01]}").message, "numbers can not start with a zero, remove it");
+    assert_eq!(fixed("{\"a\": [1, 01]}"), "{\"a\": [1, 1]}");
+    assert_eq!(fixed("\u{feff}[1]"), "[1]");
+    assert!(hint("\u{feff}[1]").fix.unwrap().safe);
+    assert!(!hint("[1 2]").fix.unwrap().safe);
+    assert!(!hint("[True]").fix.unwrap().safe);
+}
+
+#[test]
+fn test_no_hint() {
+    assert_eq!(parse_error("[1").hint, None);
+}
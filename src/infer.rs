@@ -0,0 +1,168 @@
+use crate::schema::check_format;
+use crate::value::{Number, Value};
+
+// Formats worth detecting, most specific first
+const FORMATS: [&str; 4] = ["date-time", "date", "email", "uri"];
+
+/// Everything seen at one location across all samples.
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    number: Option<NumberShape>,
+    string: Option<StringShape>,
+    array: Option<Box<Shape>>,
+    object: Option<ObjectShape>
+}
+
+#[derive(Debug)]
+struct NumberShape {
+    min: Number,
+    max: Number,
+    integer: bool
+}
+
+#[derive(Debug)]
+struct StringShape {
+    formats: Vec<&'static str>
+}
+
+#[derive(Debug, Default)]
+struct ObjectShape {
+    count: usize,
+    properties: Vec<(String, usize, Shape)>
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(num) => match &mut self.number {
+                Some(shape) => {
+                    if num.as_f64() < shape.min.as_f64() {
+                        shape.min = num.clone();
+                    }
+                    if num.as_f64() > shape.max.as_f64() {
+                        shape.max = num.clone();
+                    }
+                    shape.integer &= num.is_integer();
+                },
+                None => self.number = Some(NumberShape { min: num.clone(), max: num.clone(), integer: num.is_integer() })
+            },
+            Value::String(val) => {
+                let shape = self.string.get_or_insert_with(|| StringShape { formats: FORMATS.to_vec() });
+                shape.formats.retain(|format| check_format(format, val));
+            },
+            Value::Array(items) => {
+                let shape = self.array.get_or_insert_with(Box::default);
+                for item in items {
+                    shape.add(item);
+                }
+            },
+            Value::Object(members) => {
+                let shape = self.object.get_or_insert_with(ObjectShape::default);
+                shape.count += 1;
+                for (idx, (key, val)) in members.iter().enumerate() {
+                    // Only the last of duplicate keys counts, like `Value::get`
+                    if members[idx + 1..].iter().any(|(other, _)| other == key) {
+                        continue;
+                    }
+                    match shape.properties.iter_mut().find(|(name, _, _)| name == key) {
+                        Some((_, count, property)) => {
+                            *count += 1;
+                            property.add(val);
+                        },
+                        None => {
+                            let mut property = Shape::default();
+                            property.add(val);
+                            shape.properties.push((key.clone(), 1, property));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut types = Vec::new();
+        let mut schema = Value::Object(Vec::new());
+
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if let Some(shape) = &self.number {
+            types.push(if shape.integer { "integer" } else { "number" });
+            schema.insert("minimum", Value::Number(shape.min.clone()));
+            schema.insert("maximum", Value::Number(shape.max.clone()));
+        }
+        if let Some(shape) = &self.string {
+            types.push("string");
+            if let Some(format) = shape.formats.first() {
+                schema.insert("format", Value::from(*format));
+            }
+        }
+        if let Some(items) = &self.array {
+            types.push("array");
+            if !items.is_empty() {
+                schema.insert("items", items.to_schema());
+            }
+        }
+        if let Some(shape) = &self.object {
+            types.push("object");
+            let properties = shape.properties.iter()
+                .map(|(key, _, property)| (key.clone(), property.to_schema()))
+                .collect();
+            let required = shape.properties.iter()
+                .filter(|(_, count, _)| *count == shape.count)
+                .map(|(key, _, _)| Value::from(key.as_str()))
+                .collect();
+            schema.insert("properties", Value::Object(properties));
+            schema.insert("required", Value::Array(required));
+        }
+
+        let kind = match types.as_slice() {
+            [] => return schema,
+            [kind] => Value::from(*kind),
+            _ => Value::Array(types.into_iter().map(Value::from).collect())
+        };
+        match schema {
+            Value::Object(mut members) => {
+                members.insert(0, ("type".to_string(), kind));
+                Value::Object(members)
+            },
+            _ => unreachable!()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.null && !self.boolean && self.number.is_none() && self.string.is_none() &&
+            self.array.is_none() && self.object.is_none()
+    }
+}
+
+/// Infers a JSON Schema (draft 2020-12) that every sample conforms to.
+/// Object keys present in every sample become required, numbers get the
+/// observed range and strings a format when all of them share one.
+pub fn infer_schema(samples: &[Value]) -> Value {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.add(sample);
+    }
+
+    let mut schema = vec![(
+        "$schema".to_string(),
+        Value::from("https://json-schema.org/draft/2020-12/schema")
+    )];
+    if let Value::Object(members) = shape.to_schema() {
+        schema.extend(members);
+    }
+
+    Value::Object(schema)
+}
+
+#[cfg(test)]
+mod tests;
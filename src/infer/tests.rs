@@ -0,0 +1,95 @@
+use crate::infer::infer_schema;
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::schema::Schema;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+fn infer(samples: &[&str]) -> Value {
+    let samples: Vec<Value> = samples.iter().map(|sample| parse(sample)).collect();
+    infer_schema(&samples)
+}
+
+#[test]
+fn test_infer_scalars() {
+    let schema = infer(&["1", "-4", "12"]);
+    assert_eq!(schema, parse("{
+        \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",
+        \"type\": \"integer\", \"minimum\": -4, \"maximum\": 12
+    }"));
+
+    let schema = infer(&["1.5", "2", "null"]);
+    assert_eq!(schema.get("type"), Some(&parse("[\"null\", \"number\"]")));
+    assert_eq!(schema.get("minimum"), Some(&parse("1.5")));
+
+    let schema = infer(&["true", "\"text\""]);
+    assert_eq!(schema.get("type"), Some(&parse("[\"boolean\", \"string\"]")));
+    assert_eq!(schema.get("format"), None);
+}
+
+#[test]
+fn test_infer_formats() {
+    let format = |sample: &str| infer(&[sample]).get("format").cloned();
+
+    assert_eq!(format("\"2016-10-16\""), Some(Value::from("date")));
+    assert_eq!(format("\"2016-10-16T10:20:30Z\""), Some(Value::from("date-time")));
+    assert_eq!(format("\"hsiu.gaytan3350@magnificent.com\""), Some(Value::from("email")));
+    assert_eq!(format("\"http://www.coordinate.com\""), Some(Value::from("uri")));
+    assert_eq!(format("\"Godmanchester\""), None);
+
+    let schema = infer(&["\"2016-10-16\"", "\"yesterday\""]);
+    assert_eq!(schema.get("format"), None);
+}
+
+#[test]
+fn test_infer_formats_non_ascii() {
+    // Multibyte characters land on every byte offset a format check splits at
+    let chars = ["€", "é", "😀", "a", "+", "-", ":", "T", "@", "."];
+    let mut samples = vec![Value::from("2020-01-01T€a€")];
+    for first in chars.iter() {
+        for second in chars.iter() {
+            for len in 0..8 {
+                let val = format!("2020-01-01T{}{}{}", first, "0".repeat(len), second);
+                samples.push(Value::from(val.as_str()));
+                samples.push(Value::from(&val[11..]));
+            }
+        }
+    }
+
+    for sample in &samples {
+        assert_eq!(infer_schema(std::slice::from_ref(sample)).get("format"), None, "{:?}", sample);
+    }
+}
+
+#[test]
+fn test_infer_objects() {
+    let schema = infer(&[
+        "{ \"name\": \"a\", \"tags\": [\"x\"], \"address\": { \"town\": \"b\" } }",
+        "{ \"name\": \"c\", \"tags\": [], \"age\": 3 }"
+    ]);
+
+    assert_eq!(Serializer::compact().serialize(&schema), concat!(
+        "{\"$schema\":\"https://json-schema.org/draft/2020-12/schema\",",
+        "\"type\":\"object\",\"properties\":{",
+        "\"name\":{\"type\":\"string\"},",
+        "\"tags\":{\"type\":\"array\",\"items\":{\"type\":\"string\"}},",
+        "\"address\":{\"type\":\"object\",\"properties\":{\"town\":{\"type\":\"string\"}},\"required\":[\"town\"]},",
+        "\"age\":{\"type\":\"integer\",\"minimum\":3,\"maximum\":3}},",
+        "\"required\":[\"name\",\"tags\"]}"
+    ));
+}
+
+#[test]
+fn test_inferred_schema_accepts_samples() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let sample = parse(&contents);
+    let schema = Schema::new(infer_schema(std::slice::from_ref(&sample))).unwrap();
+
+    assert!(schema.validate(&sample).is_empty());
+    assert!(!schema.validate(&parse("{ \"ctRoot\": [{}] }")).is_empty());
+}
@@ -0,0 +1,888 @@
+use std::cmp::Ordering;
+
+use crate::serializer::Serializer;
+use crate::value::{Number, Value};
+
+/// A compiled filter written in a subset of the jq language.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Identity,
+    Literal(Value),
+    Str(Vec<StrPart>),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Iterate(Box<Expr>),
+    Optional(Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    Neg(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+    Call(String, Vec<Expr>)
+}
+
+#[derive(Debug, Clone)]
+enum StrPart {
+    Text(String),
+    Interp(Expr)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Dot,
+    Field(String),
+    Ident(String),
+    Num(String),
+    Str(Vec<RawPart>),
+    Punct(&'static str)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum RawPart {
+    Text(String),
+    Interp(String)
+}
+
+// Longest operators first so that e.g. "==" is not read as "=" "="
+const PUNCTUATION: [&str; 23] = [
+    "//", "==", "!=", "<=", ">=",
+    "|", ",", "(", ")", "[", "]", "{", "}", ":", ";", "?",
+    "+", "-", "*", "/", "%", "<", ">"
+];
+
+impl Filter {
+    pub fn parse(filter: &str) -> Result<Filter, String> {
+        let tokens = tokenize(filter)?;
+        let mut parser = FilterParser { tokens, pos: 0 };
+        let expr = parser.pipe()?;
+
+        if let Some(tok) = parser.peek() {
+            return Err(format!("Unexpected token {:?} in filter", tok));
+        }
+
+        Ok(Filter { expr })
+    }
+
+    /// Runs the filter over `input`, producing every output in order.
+    pub fn apply(&self, input: &Value) -> Result<Vec<Value>, String> {
+        eval(&self.expr, input)
+    }
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Tok>, String> {
+    let chars: Vec<char> = filter.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+
+        if c.is_whitespace() {
+            pos += 1;
+        } else if c == '#' {
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+        } else if c == '.' {
+            pos += 1;
+            if pos < chars.len() && is_ident_start(chars[pos]) {
+                let start = pos;
+                while pos < chars.len() && is_ident_char(chars[pos]) {
+                    pos += 1;
+                }
+                tokens.push(Tok::Field(chars[start..pos].iter().collect()));
+            } else {
+                tokens.push(Tok::Dot);
+            }
+        } else if is_ident_start(c) {
+            let start = pos;
+            while pos < chars.len() && is_ident_char(chars[pos]) {
+                pos += 1;
+            }
+            tokens.push(Tok::Ident(chars[start..pos].iter().collect()));
+        } else if c.is_ascii_digit() {
+            let start = pos;
+            while pos < chars.len() && (chars[pos].is_ascii_digit() || chars[pos] == '.') {
+                pos += 1;
+            }
+            if pos < chars.len() && (chars[pos] == 'e' || chars[pos] == 'E') {
+                pos += 1;
+                if pos < chars.len() && (chars[pos] == '+' || chars[pos] == '-') {
+                    pos += 1;
+                }
+                while pos < chars.len() && chars[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+            }
+            tokens.push(Tok::Num(chars[start..pos].iter().collect()));
+        } else if c == '"' {
+            let (parts, end) = string_token(&chars, pos + 1)?;
+            tokens.push(Tok::Str(parts));
+            pos = end;
+        } else {
+            let rest: String = chars[pos..chars.len().min(pos + 2)].iter().collect();
+            match PUNCTUATION.iter().find(|p| rest.starts_with(*p)) {
+                Some(p) => {
+                    tokens.push(Tok::Punct(p));
+                    pos += p.len();
+                },
+                None => return Err(format!("Unexpected character '{}' in filter", c))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Reads a string literal starting after its opening quote, returning the
+// literal parts and the position after the closing quote.
+fn string_token(chars: &[char], mut pos: usize) -> Result<(Vec<RawPart>, usize), String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        match chars.get(pos) {
+            None => return Err("Nonterminating string in filter".to_string()),
+            Some('"') => {
+                if !text.is_empty() || parts.is_empty() {
+                    parts.push(RawPart::Text(text));
+                }
+                return Ok((parts, pos + 1));
+            },
+            Some('\\') => {
+                pos += 1;
+                match chars.get(pos) {
+                    Some('(') => {
+                        let start = pos + 1;
+                        let mut depth = 1;
+                        pos += 1;
+                        while depth > 0 {
+                            match chars.get(pos) {
+                                None => return Err("Nonterminating interpolation in filter".to_string()),
+                                Some('(') => depth += 1,
+                                Some(')') => depth -= 1,
+                                Some('"') => {
+                                    pos = string_token(chars, pos + 1)?.1;
+                                    continue;
+                                },
+                                _ => {}
+                            }
+                            pos += 1;
+                        }
+                        if !text.is_empty() {
+                            parts.push(RawPart::Text(std::mem::take(&mut text)));
+                        }
+                        parts.push(RawPart::Interp(chars[start..pos - 1].iter().collect()));
+                        continue;
+                    },
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('"') => text.push('"'),
+                    Some('\\') => text.push('\\'),
+                    Some('/') => text.push('/'),
+                    Some(c) => return Err(format!("Invalid escape \\{} in filter", c)),
+                    None => return Err("Nonterminating string in filter".to_string())
+                }
+                pos += 1;
+            },
+            Some(c) => {
+                text.push(*c);
+                pos += 1;
+            }
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+struct FilterParser {
+    tokens: Vec<Tok>,
+    pos: usize
+}
+
+impl FilterParser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Punct(p)) if *p == punct)
+    }
+
+    fn is_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Ident(name)) if name == ident)
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if !self.is_punct(punct) {
+            return Err(format!("Expected '{}' but found {:?} in filter", punct, self.peek()));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn pipe(&mut self) -> Result<Expr, String> {
+        let left = self.comma()?;
+        if self.is_punct("|") {
+            self.pos += 1;
+            let right = self.pipe()?;
+            return Ok(Expr::Pipe(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Expr, String> {
+        let mut left = self.alternative()?;
+        while self.is_punct(",") {
+            self.pos += 1;
+            let right = self.alternative()?;
+            left = Expr::Comma(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Expr, String> {
+        let left = self.or()?;
+        if self.is_punct("//") {
+            self.pos += 1;
+            let right = self.alternative()?;
+            return Ok(Expr::Alternative(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.is_ident("or") {
+            self.pos += 1;
+            let right = self.and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.is_ident("and") {
+            self.pos += 1;
+            let right = self.comparison()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        let op = match self.peek() {
+            Some(Tok::Punct("==")) => BinOp::Eq,
+            Some(Tok::Punct("!=")) => BinOp::Ne,
+            Some(Tok::Punct("<")) => BinOp::Lt,
+            Some(Tok::Punct("<=")) => BinOp::Le,
+            Some(Tok::Punct(">")) => BinOp::Gt,
+            Some(Tok::Punct(">=")) => BinOp::Ge,
+            _ => return Ok(left)
+        };
+        self.pos += 1;
+        let right = self.additive()?;
+        Ok(Expr::Binary(Box::new(left), op, Box::new(right)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Punct("+")) => BinOp::Add,
+                Some(Tok::Punct("-")) => BinOp::Sub,
+                _ => return Ok(left)
+            };
+            self.pos += 1;
+            let right = self.multiplicative()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Punct("*")) => BinOp::Mul,
+                Some(Tok::Punct("/")) => BinOp::Div,
+                Some(Tok::Punct("%")) => BinOp::Mod,
+                _ => return Ok(left)
+            };
+            self.pos += 1;
+            let right = self.unary()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.is_punct("-") {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.postfix()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+
+        loop {
+            match self.peek() {
+                Some(Tok::Field(name)) => {
+                    let name = name.clone();
+                    self.pos += 1;
+                    expr = Expr::Field(Box::new(expr), name);
+                },
+                Some(Tok::Dot) if matches!(self.tokens.get(self.pos + 1), Some(Tok::Punct("["))) => {
+                    self.pos += 1;
+                },
+                Some(Tok::Dot) if matches!(self.tokens.get(self.pos + 1), Some(Tok::Str(_))) => {
+                    self.pos += 1;
+                    let key = self.string()?;
+                    expr = Expr::Index(Box::new(expr), Box::new(key));
+                },
+                Some(Tok::Punct("[")) => {
+                    self.pos += 1;
+                    if self.is_punct("]") {
+                        self.pos += 1;
+                        expr = Expr::Iterate(Box::new(expr));
+                    } else {
+                        let idx = self.pipe()?;
+                        self.expect("]")?;
+                        expr = Expr::Index(Box::new(expr), Box::new(idx));
+                    }
+                },
+                Some(Tok::Punct("?")) => {
+                    self.pos += 1;
+                    expr = Expr::Optional(Box::new(expr));
+                },
+                _ => return Ok(expr)
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let tok = match self.peek() {
+            Some(tok) => tok.clone(),
+            None => return Err("Unexpected end of filter".to_string())
+        };
+
+        match tok {
+            Tok::Dot => {
+                self.pos += 1;
+                if let Some(Tok::Str(_)) = self.peek() {
+                    let key = self.string()?;
+                    return Ok(Expr::Index(Box::new(Expr::Identity), Box::new(key)));
+                }
+                Ok(Expr::Identity)
+            },
+            Tok::Field(name) => {
+                self.pos += 1;
+                Ok(Expr::Field(Box::new(Expr::Identity), name))
+            },
+            Tok::Num(text) => {
+                self.pos += 1;
+                match number(&text) {
+                    Some(num) => Ok(Expr::Literal(Value::Number(num))),
+                    None => Err(format!("Invalid number {} in filter", text))
+                }
+            },
+            Tok::Str(_) => self.string(),
+            Tok::Punct("(") => {
+                self.pos += 1;
+                let expr = self.pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            },
+            Tok::Punct("[") => {
+                self.pos += 1;
+                if self.is_punct("]") {
+                    self.pos += 1;
+                    return Ok(Expr::Array(None));
+                }
+                let expr = self.pipe()?;
+                self.expect("]")?;
+                Ok(Expr::Array(Some(Box::new(expr))))
+            },
+            Tok::Punct("{") => self.object(),
+            Tok::Ident(name) => {
+                self.pos += 1;
+                match name.as_str() {
+                    "true" => return Ok(Expr::Literal(Value::Bool(true))),
+                    "false" => return Ok(Expr::Literal(Value::Bool(false))),
+                    "null" => return Ok(Expr::Literal(Value::Null)),
+                    _ => {}
+                }
+
+                let mut args = Vec::new();
+                if self.is_punct("(") {
+                    self.pos += 1;
+                    loop {
+                        args.push(self.pipe()?);
+                        if self.is_punct(";") {
+                            self.pos += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    self.expect(")")?;
+                }
+                Ok(Expr::Call(name, args))
+            },
+            tok => Err(format!("Unexpected token {:?} in filter", tok))
+        }
+    }
+
+    fn string(&mut self) -> Result<Expr, String> {
+        let parts = match self.peek() {
+            Some(Tok::Str(parts)) => parts.clone(),
+            _ => return Err("Expected string in filter".to_string())
+        };
+        self.pos += 1;
+
+        if let [RawPart::Text(text)] = parts.as_slice() {
+            return Ok(Expr::Literal(Value::String(text.clone())));
+        }
+
+        let mut out = Vec::new();
+        for part in parts {
+            match part {
+                RawPart::Text(text) => out.push(StrPart::Text(text)),
+                RawPart::Interp(inner) => out.push(StrPart::Interp(Filter::parse(&inner)?.expr))
+            }
+        }
+        Ok(Expr::Str(out))
+    }
+
+    fn object(&mut self) -> Result<Expr, String> {
+        self.expect("{")?;
+        let mut entries = Vec::new();
+
+        while !self.is_punct("}") {
+            let key = match self.peek().cloned() {
+                Some(Tok::Ident(name)) => {
+                    self.pos += 1;
+                    Expr::Literal(Value::String(name))
+                },
+                Some(Tok::Str(_)) => self.string()?,
+                Some(Tok::Punct("(")) => {
+                    self.pos += 1;
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    key
+                },
+                tok => return Err(format!("Unexpected object key {:?} in filter", tok))
+            };
+
+            let value = if self.is_punct(":") {
+                self.pos += 1;
+                self.alternative()?
+            } else {
+                // `{name}` is shorthand for `{name: .name}`
+                Expr::Index(Box::new(Expr::Identity), Box::new(key.clone()))
+            };
+            entries.push((key, value));
+
+            if self.is_punct(",") {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        self.expect("}")?;
+        Ok(Expr::Object(entries))
+    }
+}
+
+fn eval(expr: &Expr, input: &Value) -> Result<Vec<Value>, String> {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Literal(val) => Ok(vec![val.clone()]),
+        Expr::Str(parts) => {
+            let mut outputs = vec![String::new()];
+            for part in parts {
+                match part {
+                    StrPart::Text(text) => outputs.iter_mut().for_each(|out| out.push_str(text)),
+                    StrPart::Interp(inner) => {
+                        let values = eval(inner, input)?;
+                        outputs = outputs.iter()
+                            .flat_map(|out| values.iter().map(move |val| format!("{}{}", out, stringify(val))))
+                            .collect();
+                    }
+                }
+            }
+            Ok(outputs.into_iter().map(Value::String).collect())
+        },
+        Expr::Field(target, name) => {
+            let mut out = Vec::new();
+            for val in eval(target, input)? {
+                out.push(index(&val, &Value::String(name.clone()))?);
+            }
+            Ok(out)
+        },
+        Expr::Index(target, idx) => {
+            let mut out = Vec::new();
+            for val in eval(target, input)? {
+                for key in eval(idx, input)? {
+                    out.push(index(&val, &key)?);
+                }
+            }
+            Ok(out)
+        },
+        Expr::Iterate(target) => {
+            let mut out = Vec::new();
+            for val in eval(target, input)? {
+                match val {
+                    Value::Array(items) => out.extend(items),
+                    Value::Object(members) => out.extend(members.into_iter().map(|(_, val)| val)),
+                    other => return Err(format!("Cannot iterate over {}", other.type_name()))
+                }
+            }
+            Ok(out)
+        },
+        Expr::Optional(inner) => Ok(eval(inner, input).unwrap_or_default()),
+        Expr::Array(None) => Ok(vec![Value::Array(Vec::new())]),
+        Expr::Array(Some(inner)) => Ok(vec![Value::Array(eval(inner, input)?)]),
+        Expr::Object(entries) => {
+            let mut objects = vec![Vec::new()];
+            for (key, value) in entries {
+                let keys = eval(key, input)?;
+                let values = eval(value, input)?;
+                let mut next = Vec::new();
+                for object in &objects {
+                    for key in &keys {
+                        let key = match key {
+                            Value::String(key) => key.clone(),
+                            other => return Err(format!("Object keys must be strings, not {}", other.type_name()))
+                        };
+                        for value in &values {
+                            let mut object: Vec<(String, Value)> = object.clone();
+                            object.retain(|(name, _)| *name != key);
+                            object.push((key.clone(), value.clone()));
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        },
+        Expr::Neg(inner) => {
+            let mut out = Vec::new();
+            for val in eval(inner, input)? {
+                match val {
+                    Value::Number(num) => out.push(Value::Number(Number::from(-num.as_f64()))),
+                    other => return Err(format!("{} cannot be negated", other.type_name()))
+                }
+            }
+            Ok(out)
+        },
+        Expr::Pipe(left, right) => {
+            let mut out = Vec::new();
+            for val in eval(left, input)? {
+                out.extend(eval(right, &val)?);
+            }
+            Ok(out)
+        },
+        Expr::Comma(left, right) => {
+            let mut out = eval(left, input)?;
+            out.extend(eval(right, input)?);
+            Ok(out)
+        },
+        Expr::Alternative(left, right) => {
+            let out: Vec<Value> = eval(left, input).unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect();
+            if out.is_empty() {
+                eval(right, input)
+            } else {
+                Ok(out)
+            }
+        },
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            let is_and = matches!(expr, Expr::And(_, _));
+            let mut out = Vec::new();
+            for val in eval(left, input)? {
+                if is_truthy(&val) != is_and {
+                    out.push(Value::Bool(!is_and));
+                    continue;
+                }
+                for val in eval(right, input)? {
+                    out.push(Value::Bool(is_truthy(&val)));
+                }
+            }
+            Ok(out)
+        },
+        Expr::Binary(left, op, right) => {
+            // jq evaluates the right hand side in the outer loop
+            let mut out = Vec::new();
+            let lefts = eval(left, input)?;
+            for rval in eval(right, input)? {
+                for lval in &lefts {
+                    out.push(binary(lval, *op, &rval)?);
+                }
+            }
+            Ok(out)
+        },
+        Expr::Call(name, args) => call(name, args, input)
+    }
+}
+
+fn index(val: &Value, key: &Value) -> Result<Value, String> {
+    match (val, key) {
+        (Value::Null, Value::String(_)) | (Value::Null, Value::Number(_)) => Ok(Value::Null),
+        (Value::Object(_), Value::String(key)) => Ok(val.get(key).cloned().unwrap_or(Value::Null)),
+        (Value::Array(items), Value::Number(idx)) => {
+            let idx = idx.as_f64().floor() as i64;
+            let idx = if idx < 0 { items.len() as i64 + idx } else { idx };
+            if idx < 0 {
+                return Ok(Value::Null);
+            }
+            Ok(items.get(idx as usize).cloned().unwrap_or(Value::Null))
+        },
+        (val, Value::String(key)) => Err(format!("Cannot index {} with \"{}\"", val.type_name(), key)),
+        (val, key) => Err(format!("Cannot index {} with {}", val.type_name(), key.type_name()))
+    }
+}
+
+fn call(name: &str, args: &[Expr], input: &Value) -> Result<Vec<Value>, String> {
+    match (name, args.len()) {
+        ("empty", 0) => Ok(Vec::new()),
+        ("not", 0) => Ok(vec![Value::Bool(!is_truthy(input))]),
+        ("length", 0) => Ok(vec![length(input)?]),
+        ("keys", 0) => match input {
+            Value::Object(members) => {
+                let mut keys: Vec<&String> = members.iter().map(|(key, _)| key).collect();
+                keys.sort();
+                keys.dedup();
+                Ok(vec![Value::Array(keys.into_iter().map(|key| Value::String(key.clone())).collect())])
+            },
+            Value::Array(items) => Ok(vec![Value::Array((0..items.len()).map(|i| Value::Number(Number::from(i))).collect())]),
+            other => Err(format!("{} has no keys", other.type_name()))
+        },
+        ("type", 0) => Ok(vec![Value::from(input.type_name())]),
+        ("add", 0) => match input {
+            Value::Array(items) => {
+                let mut acc = Value::Null;
+                for item in items {
+                    acc = binary(&acc, BinOp::Add, item)?;
+                }
+                Ok(vec![acc])
+            },
+            other => Err(format!("Cannot add the elements of {}", other.type_name()))
+        },
+        ("tostring", 0) => Ok(vec![Value::String(stringify(input))]),
+        ("tonumber", 0) => match input {
+            Value::Number(_) => Ok(vec![input.clone()]),
+            Value::String(text) => match number(text.trim()) {
+                Some(num) => Ok(vec![Value::Number(num)]),
+                None => Err(format!("Cannot parse \"{}\" as a number", text))
+            },
+            other => Err(format!("{} cannot be parsed as a number", other.type_name()))
+        },
+        ("select", 1) => {
+            let mut out = Vec::new();
+            for val in eval(&args[0], input)? {
+                if is_truthy(&val) {
+                    out.push(input.clone());
+                }
+            }
+            Ok(out)
+        },
+        ("map", 1) => {
+            let items = match input {
+                Value::Array(items) => items.clone(),
+                Value::Object(members) => members.iter().map(|(_, val)| val.clone()).collect(),
+                other => return Err(format!("Cannot iterate over {}", other.type_name()))
+            };
+            let mut out = Vec::new();
+            for item in &items {
+                out.extend(eval(&args[0], item)?);
+            }
+            Ok(vec![Value::Array(out)])
+        },
+        ("has", 1) => {
+            let mut out = Vec::new();
+            for key in eval(&args[0], input)? {
+                let found = match (input, &key) {
+                    (Value::Object(_), Value::String(key)) => input.get(key).is_some(),
+                    (Value::Array(items), Value::Number(idx)) => idx.as_f64() >= 0.0 && (idx.as_f64() as usize) < items.len(),
+                    (val, key) => return Err(format!("Cannot check whether {} has a {} key", val.type_name(), key.type_name()))
+                };
+                out.push(Value::Bool(found));
+            }
+            Ok(out)
+        },
+        _ => Err(format!("{}/{} is not defined", name, args.len()))
+    }
+}
+
+fn length(input: &Value) -> Result<Value, String> {
+    let len = match input {
+        Value::Null => 0,
+        Value::Number(num) => return Ok(Value::Number(Number::from(num.as_f64().abs()))),
+        Value::String(val) => val.chars().count(),
+        Value::Array(items) => items.len(),
+        Value::Object(members) => members.len(),
+        Value::Bool(_) => return Err("boolean has no length".to_string())
+    };
+    Ok(Value::Number(Number::from(len)))
+}
+
+fn binary(left: &Value, op: BinOp, right: &Value) -> Result<Value, String> {
+    let ordering = compare(left, right);
+
+    match op {
+        BinOp::Eq => return Ok(Value::Bool(left == right)),
+        BinOp::Ne => return Ok(Value::Bool(left != right)),
+        BinOp::Lt => return Ok(Value::Bool(ordering == Ordering::Less)),
+        BinOp::Le => return Ok(Value::Bool(ordering != Ordering::Greater)),
+        BinOp::Gt => return Ok(Value::Bool(ordering == Ordering::Greater)),
+        BinOp::Ge => return Ok(Value::Bool(ordering != Ordering::Less)),
+        _ => {}
+    }
+
+    match (left, op, right) {
+        (Value::Null, BinOp::Add, val) | (val, BinOp::Add, Value::Null) => Ok(val.clone()),
+        (Value::Number(a), op, Value::Number(b)) => {
+            let (a, b) = (a.as_f64(), b.as_f64());
+            let result = match op {
+                BinOp::Add => a + b,
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                BinOp::Div if b == 0.0 => return Err("Cannot divide by zero".to_string()),
+                BinOp::Div => a / b,
+                _ => match (a as i64).unsigned_abs().checked_rem((b as i64).unsigned_abs()) {
+                    // The remainder takes the sign of the dividend
+                    Some(rem) if a < 0.0 => -(rem as f64),
+                    Some(rem) => rem as f64,
+                    None => return Err("Cannot divide by zero".to_string())
+                }
+            };
+            Ok(Value::Number(Number::from(result)))
+        },
+        (Value::String(a), BinOp::Add, Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+        (Value::Array(a), BinOp::Add, Value::Array(b)) => {
+            let mut items = a.clone();
+            items.extend(b.iter().cloned());
+            Ok(Value::Array(items))
+        },
+        (Value::Array(a), BinOp::Sub, Value::Array(b)) => {
+            Ok(Value::Array(a.iter().filter(|item| !b.contains(item)).cloned().collect()))
+        },
+        (Value::Object(a), BinOp::Add, Value::Object(b)) => {
+            let mut members: Vec<(String, Value)> = a.iter()
+                .filter(|(key, _)| right.get(key).is_none())
+                .cloned()
+                .collect();
+            members.extend(b.iter().cloned());
+            Ok(Value::Object(members))
+        },
+        _ => Err(format!("{} and {} cannot be combined with {:?}", left.type_name(), right.type_name(), op))
+    }
+}
+
+// Numbers in filters and `tonumber` follow JSON's grammar and must be finite
+fn number(text: &str) -> Option<Number> {
+    let finite = text.parse::<f64>().is_ok_and(f64::is_finite);
+    (finite && Number::is_valid(text)).then(|| Number::new(text.to_string()))
+}
+
+fn is_truthy(val: &Value) -> bool {
+    !matches!(val, Value::Null | Value::Bool(false))
+}
+
+fn stringify(val: &Value) -> String {
+    match val {
+        Value::String(val) => val.clone(),
+        other => Serializer::compact().serialize(other)
+    }
+}
+
+// jq orders null < false < true < numbers < strings < arrays < objects
+fn compare(left: &Value, right: &Value) -> Ordering {
+    fn rank(val: &Value) -> u8 {
+        match val {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6
+        }
+    }
+
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => {
+            for (x, y) in a.iter().zip(b.iter()) {
+                let ordering = compare(x, y);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            a.len().cmp(&b.len())
+        },
+        (Value::Object(_), Value::Object(_)) => {
+            let keys = |val: &Value| match call("keys", &[], val) {
+                Ok(mut keys) => keys.pop().unwrap_or(Value::Null),
+                Err(_) => Value::Null
+            };
+            let ordering = compare(&keys(left), &keys(right));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            if let Value::Object(members) = left {
+                let mut names: Vec<&String> = members.iter().map(|(key, _)| key).collect();
+                names.sort();
+                for name in names {
+                    let ordering = compare(left.get(name).unwrap(), right.get(name).unwrap());
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                }
+            }
+            Ordering::Equal
+        },
+        _ => rank(left).cmp(&rank(right))
+    }
+}
+
+#[cfg(test)]
+mod tests;
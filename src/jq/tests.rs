@@ -0,0 +1,98 @@
+use crate::jq::Filter;
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+fn run(filter: &str, data: &str) -> Vec<String> {
+    Filter::parse(filter).unwrap()
+        .apply(&parse(data))
+        .unwrap()
+        .iter()
+        .map(|val| Serializer::compact().serialize(val))
+        .collect()
+}
+
+const PEOPLE: &str = "{\"ctRoot\": [
+    {\"name\": \"Coletta Ortiz\", \"verified\": false, \"score\": 4.1, \"pets\": [\"Ziggy\", \"Sasha\"]},
+    {\"name\": \"Hank Hill\", \"verified\": true, \"score\": 2, \"pets\": []}
+]}";
+
+#[test]
+fn test_paths_and_iteration() {
+    assert_eq!(run(".", "[1]"), vec!["[1]"]);
+    assert_eq!(run(".ctRoot[0].name", PEOPLE), vec!["\"Coletta Ortiz\""]);
+    assert_eq!(run(".ctRoot[-1].score", PEOPLE), vec!["2"]);
+    assert_eq!(run(".ctRoot[].pets[]", PEOPLE), vec!["\"Ziggy\"", "\"Sasha\""]);
+    assert_eq!(run(".[\"a b\"], .missing", "{\"a b\": 1}"), vec!["1", "null"]);
+    assert_eq!(run(".[5]", "[1]"), vec!["null"]);
+}
+
+#[test]
+fn test_pipes_select_and_map() {
+    assert_eq!(run(".ctRoot[] | select(.verified == false) | .name", PEOPLE), vec!["\"Coletta Ortiz\""]);
+    assert_eq!(run(".ctRoot | map(.score * 2)", PEOPLE), vec!["[8.2,4]"]);
+    assert_eq!(run("map(select(. > 1))", "[1, 2, 3]"), vec!["[2,3]"]);
+}
+
+#[test]
+fn test_construction() {
+    assert_eq!(run("{name: .ctRoot[0].name, count: (.ctRoot | length)}", PEOPLE),
+               vec!["{\"name\":\"Coletta Ortiz\",\"count\":2}"]);
+    assert_eq!(run(".ctRoot[0] | {name, (.pets[0]): .score}", PEOPLE),
+               vec!["{\"name\":\"Coletta Ortiz\",\"Ziggy\":4.1}"]);
+    assert_eq!(run("[.[] | . + 1]", "[1, 2]"), vec!["[2,3]"]);
+    assert_eq!(run("{a: (1, 2)}", "null"), vec!["{\"a\":1}", "{\"a\":2}"]);
+}
+
+#[test]
+fn test_builtins() {
+    assert_eq!(run("keys", "{\"b\": 1, \"a\": 2}"), vec!["[\"a\",\"b\"]"]);
+    assert_eq!(run("length", "\"héllo\""), vec!["5"]);
+    assert_eq!(run("map(length)", "[[1, 2], {}, null, -3]"), vec!["[2,0,0,3]"]);
+    assert_eq!(run("add", "[\"a\", \"b\"]"), vec!["\"ab\""]);
+    assert_eq!(run(".a // \"default\"", "{}"), vec!["\"default\""]);
+    assert_eq!(run("has(\"a\"), type", "{\"a\": 1}"), vec!["true", "\"object\""]);
+}
+
+#[test]
+fn test_arithmetic_and_interpolation() {
+    assert_eq!(run("(1 + 2) * 3 - 4 / 2", "null"), vec!["7"]);
+    assert_eq!(run("10 % 3, -.", "4"), vec!["1", "-4"]);
+    assert_eq!(run(". + {b: 2}", "{\"a\": 1, \"b\": 1}"), vec!["{\"a\":1,\"b\":2}"]);
+    assert_eq!(run("\"\\(.name) has \\(.pets | length) pets\"", "{\"name\": \"Ziggy\", \"pets\": [1, 2]}"),
+               vec!["\"Ziggy has 2 pets\""]);
+    assert_eq!(run(".a < .b and .b < \"x\"", "{\"a\": null, \"b\": 1}"), vec!["true"]);
+}
+
+#[test]
+fn test_errors() {
+    let value = parse("{\"a\": 1}");
+
+    assert!(Filter::parse(".a |").is_err());
+    assert!(Filter::parse("[.a").is_err());
+    assert!(Filter::parse(".a | nope").unwrap().apply(&value).is_err());
+    assert!(Filter::parse(".a[]").unwrap().apply(&value).is_err());
+    assert!(Filter::parse(".a.b").unwrap().apply(&value).is_err());
+    assert_eq!(Filter::parse(".a.b?").unwrap().apply(&value), Ok(Vec::new()));
+    assert!(Filter::parse("1 / 0").unwrap().apply(&value).is_err());
+    assert!(Filter::parse("1 % 0").unwrap().apply(&value).is_err());
+    assert!(Filter::parse("1.").is_err());
+    assert!(Filter::parse("1e400").is_err());
+    for text in ["nan", "inf", "+5", "1.", "1e400"] {
+        let input = Value::from(text);
+        assert_eq!(Filter::parse("tonumber").unwrap().apply(&input), Err(format!("Cannot parse \"{}\" as a number", text)));
+    }
+}
+
+#[test]
+fn test_numbers() {
+    assert_eq!(run("map(tonumber)", "[\" 12 \", \"-1.5e3\", 7]"), vec!["[12,-1.5e3,7]"]);
+    assert_eq!(run(". % -9223372036854775808, -7 % 2, 7 % -2", "5"), vec!["5", "-1", "1"]);
+    assert_eq!(run(". % 3", "-9223372036854775808"), vec!["-2"]);
+}
@@ -1,6 +1,10 @@
+use std::fmt;
 use std::str::Chars;
 use std::iter::Peekable;
 
+use crate::code::ErrorCode;
+use crate::hint::{self, Hint};
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum JsonKind {
     StringVal,
@@ -19,6 +23,8 @@ pub enum JsonKind {
     HorizontalTab,
     LineFeed,
     CarriageReturn,
+    // Comments are not JSON, but are kept for lossless tooling
+    Comment,
     // Numbers
     Plus,
     Minus,
@@ -28,20 +34,74 @@ pub enum JsonKind {
     E
 }
 
+impl JsonKind {
+    pub fn is_whitespace(&self) -> bool {
+        matches!(self, JsonKind::Space | JsonKind::HorizontalTab | JsonKind::LineFeed | JsonKind::CarriageReturn)
+    }
+
+    /// Whitespace and comments, which carry no meaning for the value.
+    pub fn is_trivia(&self) -> bool {
+        self.is_whitespace() || *self == JsonKind::Comment
+    }
+}
+
 impl<'a> TokenIter<'a> {
     pub fn new(contents: &'a str) -> Self {
         TokenIter {
             chars: contents.chars().peekable(),
-            curr_line: 1
+            curr_line: 1,
+            curr_column: 1,
+            curr_offset: 0
         }
     }
+
+    /// Yields lexing failures as errors instead of panicking, stopping after
+    /// the first one.
+    pub fn checked(mut self) -> impl Iterator<Item=Result<Token, LexError>> + 'a {
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let result = self.lex();
+            failed = matches!(result, Some(Err(_)));
+            result
+        })
+    }
+}
+
+/// A range of the source text in bytes, `end` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+/// Text the lexer could not turn into a token, positioned at the start of
+/// that token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub hint: Option<Hint>
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} | line {}, column {}", self.message, self.line, self.column)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: JsonKind,
     pub text: String,
-    pub line: usize 
+    pub line: usize,
+    // Counted in characters from 1, or 0 when the token was not lexed from source
+    pub column: usize
 }
 
 impl Token {
@@ -49,23 +109,37 @@ impl Token {
         Token {
             kind,
             line,
-            text
+            text,
+            column: 0
         }
     }
 }
 
 pub struct TokenIter<'a> {
     chars: Peekable<Chars<'a>>,
-    curr_line: usize
+    curr_line: usize,
+    curr_column: usize,
+    curr_offset: usize
 }
 
 impl<'a> Iterator for TokenIter<'a> {
     type Item = Token;
     fn next(&mut self) -> Option<Self::Item> {
+        self.lex().map(|result| result.unwrap_or_else(|err| panic!("{}", err)))
+    }
+}
+
+impl<'a> TokenIter<'a> {
+    fn lex(&mut self) -> Option<Result<Token, LexError>> {
         match self.chars.next() {
             None => None,
             Some(val) => {
-                let token = match val {
+                let column = self.curr_column;
+                let line = self.curr_line;
+                let start = self.curr_offset;
+                let span = Span { start, end: start + val.len_utf8() };
+                let error = |code: ErrorCode, message: String, span: Span, hint: Option<Hint>| Some(Err(LexError { code, message, line, column, span, hint }));
+                let mut token = match val {
                     ' ' => {
                         Token::new(JsonKind::Space, self.curr_line, val.to_string())
                     },
@@ -120,19 +194,53 @@ impl<'a> Iterator for TokenIter<'a> {
                             tmp.push(curr);
                             next = self.chars.peek();
 
-                            if curr == '\\' && next.is_some() && *next.unwrap() == '"' {
+                            if curr == '\\' && next.is_some() && *next.unwrap() != '\n' {
                                 tmp.push(self.chars.next().unwrap());
+                                if self.chars.peek().is_none() {
+                                    return error(ErrorCode::UnterminatedString, "Nonterminating string found".to_string(), span, None);
+                                }
                             } else if curr == '"' {
                                 break;
                             } else if curr == '\n' {
-                                panic!("Multiline string detected");
+                                return error(ErrorCode::MultilineString, "Multiline string detected".to_string(), span, None);
                             } else if next.is_none(){
-                                panic!("Nonterminating string found");
+                                return error(ErrorCode::UnterminatedString, "Nonterminating string found".to_string(), span, None);
                             }
                         }
 
                         Token::new(JsonKind::StringVal, self.curr_line, tmp)
                     },
+                    '/' => {
+                        let mut tmp = val.to_string();
+
+                        match self.chars.peek() {
+                            Some('/') => {
+                                while let Some(next) = self.chars.peek() {
+                                    if *next == '\n' || *next == '\r' {
+                                        break;
+                                    }
+                                    tmp.push(self.chars.next().unwrap());
+                                }
+                            },
+                            Some('*') => {
+                                tmp.push(self.chars.next().unwrap());
+                                while !(tmp.len() >= 4 && tmp.ends_with("*/")) {
+                                    match self.chars.next() {
+                                        Some(curr) => {
+                                            if curr == '\n' {
+                                                self.curr_line += 1;
+                                            }
+                                            tmp.push(curr);
+                                        },
+                                        None => return error(ErrorCode::UnterminatedComment, "Nonterminating comment found".to_string(), span, None)
+                                    }
+                                }
+                            },
+                            _ => return error(ErrorCode::UnexpectedCharacter, format!("Unexpected token: {}", val), span, None)
+                        }
+
+                        Token::new(JsonKind::Comment, line, tmp)
+                    },
                     val if val.is_numeric() => {
                         Token::new(JsonKind::Digit, self.curr_line, val.to_string())
                     },
@@ -149,19 +257,70 @@ impl<'a> Iterator for TokenIter<'a> {
 
                         let kind = match check_keyword(&tmp) {
                             Ok(v) => v,
-                            Err(e) => panic!("{}", e)
+                            Err(e) => {
+                                let span = Span { start, end: start + tmp.len() };
+                                let hint = hint::keyword(&tmp, span).unwrap_or_else(|| self.unquoted(&tmp, start));
+                                return error(ErrorCode::UnknownKeyword, e, span, Some(hint));
+                            }
                         };
 
                         Token::new(kind, self.curr_line, tmp)
                     },
+                    '\'' => {
+                        return error(ErrorCode::UnexpectedCharacter, format!("Unexpected token: {}", val), span, Some(self.single_quoted(start)));
+                    },
+                    '\u{feff}' => {
+                        return error(ErrorCode::ByteOrderMark, "Unexpected byte order mark".to_string(), span, Some(hint::byte_order_mark(span)));
+                    },
                     _ => {
-                        panic!("Unexpected token: {}", val);
+                        return error(ErrorCode::UnexpectedCharacter, format!("Unexpected token: {}", val), span, None);
                     }
                 };
 
-                Some(token)
+                token.column = column;
+                self.curr_offset += token.text.len();
+                // Line feeds and block comments spanning lines restart the column
+                match token.text.rfind('\n') {
+                    Some(idx) => self.curr_column = token.text[idx + 1..].chars().count() + 1,
+                    None => self.curr_column += token.text.chars().count()
+                }
+
+                Some(Ok(token))
+            }
+        }
+    }
+}
+
+impl<'a> TokenIter<'a> {
+    // Looks past a bare word for the rest of an identifier like `user_id`,
+    // so quoting it covers the whole thing
+    fn unquoted(&self, word: &str, start: usize) -> Hint {
+        let rest: String = self.chars.clone()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+            .collect();
+        let word = format!("{}{}", word, rest);
+        hint::unquoted(&word, Span { start, end: start + word.len() })
+    }
+
+    // Looks ahead on the same line for the quote closing a single quoted string
+    fn single_quoted(&self, start: usize) -> Hint {
+        let mut text = String::new();
+        let mut chars = self.chars.clone();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => return hint::single_quotes(Some(&text), Span { start, end: start + text.len() + 2 }),
+                '\n' => break,
+                '\\' => {
+                    text.push(c);
+                    match chars.next() {
+                        Some('\n') | None => break,
+                        Some(next) => text.push(next)
+                    }
+                },
+                c => text.push(c)
             }
         }
+        hint::single_quotes(None, Span { start, end: start + 1 })
     }
 }
 
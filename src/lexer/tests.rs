@@ -1,4 +1,6 @@
-use crate::lexer::{TokenIter, Token, JsonKind};
+use crate::code::ErrorCode;
+use crate::hint;
+use crate::lexer::{TokenIter, Token, JsonKind, LexError, Span};
 
 #[test]
 fn test_general_json() {
@@ -180,7 +182,7 @@ let test_data = vec![
     Token::new(JsonKind::EndObject, 22, "}".to_string()),
     ];
 
-let iter = TokenIter { chars: data.chars().peekable(), curr_line: 1 };
+let iter = TokenIter::new(data);
 let mut collect: Vec<Token> = Vec::new();
 
 for val in iter {
@@ -198,7 +200,7 @@ for (d, td) in collect.iter().zip(test_data.iter()) {
 
 #[test]
 fn test_strings_pass() {
-    let data_table = vec![
+    let data_table = [
 "\"Yo, does this string work?\"",
 "\"Yo, does this \\\" string work?\"",
 "{
@@ -209,40 +211,40 @@ fn test_strings_pass() {
 ]",
     ];
 
-let expected_table = vec![
+let expected_table = [
     vec![
-        Token { kind: JsonKind::StringVal, line: 1, text: "\"Yo, does this string work?\"".to_string() }, 
+        Token { kind: JsonKind::StringVal, line: 1, text: "\"Yo, does this string work?\"".to_string(), column: 1 }, 
     ],
     vec![
-        Token { kind: JsonKind::StringVal, line: 1, text: "\"Yo, does this \\\" string work?\"".to_string() }, 
+        Token { kind: JsonKind::StringVal, line: 1, text: "\"Yo, does this \\\" string work?\"".to_string(), column: 1 }, 
     ],
     vec![
-        Token { kind: JsonKind::BeginObject, line: 1, text: "{".to_string() }, 
-        Token { kind: JsonKind::LineFeed, line: 1, text: "\n".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::StringVal, line: 2, text: "\"Yo, does this string work?\"".to_string() }, 
-        Token { kind: JsonKind::LineFeed, line: 2, text: "\n".to_string() }, 
-        Token { kind: JsonKind::EndObject, line: 3, text: "}".to_string() }, 
+        Token { kind: JsonKind::BeginObject, line: 1, text: "{".to_string(), column: 1 }, 
+        Token { kind: JsonKind::LineFeed, line: 1, text: "\n".to_string(), column: 2 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 1 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 2 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 3 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 4 }, 
+        Token { kind: JsonKind::StringVal, line: 2, text: "\"Yo, does this string work?\"".to_string(), column: 5 }, 
+        Token { kind: JsonKind::LineFeed, line: 2, text: "\n".to_string(), column: 33 }, 
+        Token { kind: JsonKind::EndObject, line: 3, text: "}".to_string(), column: 1 }, 
     ],
     vec![
-        Token { kind: JsonKind::BeginArray, line: 1, text: "[".to_string() }, 
-        Token { kind: JsonKind::LineFeed, line: 1, text: "\n".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::Space, line: 2, text: " ".to_string() }, 
-        Token { kind: JsonKind::StringVal, line: 2, text: "\"Yo, does this string work?\"".to_string() }, 
-        Token { kind: JsonKind::LineFeed, line: 2, text: "\n".to_string() }, 
-        Token { kind: JsonKind::EndArray, line: 3, text: "]".to_string() }, 
+        Token { kind: JsonKind::BeginArray, line: 1, text: "[".to_string(), column: 1 }, 
+        Token { kind: JsonKind::LineFeed, line: 1, text: "\n".to_string(), column: 2 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 1 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 2 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 3 }, 
+        Token { kind: JsonKind::Space, line: 2, text: " ".to_string(), column: 4 }, 
+        Token { kind: JsonKind::StringVal, line: 2, text: "\"Yo, does this string work?\"".to_string(), column: 5 }, 
+        Token { kind: JsonKind::LineFeed, line: 2, text: "\n".to_string(), column: 33 }, 
+        Token { kind: JsonKind::EndArray, line: 3, text: "]".to_string(), column: 1 }, 
     ],
     ];
 
 for (i, data) in data_table.iter().enumerate() {
 
-    let iter = TokenIter { chars: data.chars().peekable(), curr_line: 1 };
+    let iter = TokenIter::new(data);
     let mut collect: Vec<Token> = Vec::new();
     let expected = &expected_table[i];
 
@@ -255,6 +257,7 @@ for (i, data) in data_table.iter().enumerate() {
     for (d, td) in collect.iter().zip(expected.iter()) {
         assert_eq!(d.kind, td.kind, "token kind is incorrect");
         assert_eq!(d.text, td.text, "toke text is incorrect");
+        assert_eq!(d.column, td.column, "token column is incorrect");
     }
 }
 }
@@ -262,11 +265,11 @@ for (i, data) in data_table.iter().enumerate() {
 #[test]
 #[should_panic(expected = "Nonterminating string found")]
 fn test_string_nonterm_fail() {
-    let data_table = vec![
+    let data_table = [
         "\"Yo, does this string not work?"
     ];
 
-    let iter = TokenIter { chars: data_table[0].chars().peekable(), curr_line: 1 };
+    let iter = TokenIter::new(data_table[0]);
     let mut collect: Vec<Token> = Vec::new();
 
     for val in iter {
@@ -277,12 +280,12 @@ fn test_string_nonterm_fail() {
 #[test]
 #[should_panic(expected = "Multiline string detected")]
 fn test_string_newline_fail() {
-    let data_table = vec![
+    let data_table = [
         "\"Yo, does this string not work?
         "
     ];
 
-    let iter = TokenIter { chars: data_table[0].chars().peekable(), curr_line: 1 };
+    let iter = TokenIter::new(data_table[0]);
     let mut collect: Vec<Token> = Vec::new();
 
     for val in iter {
@@ -292,13 +295,13 @@ fn test_string_newline_fail() {
 
 #[test]
 fn test_keywords_pass() {
-    let data_table = vec![
+    let data_table = [
         "true",
         "false",
         "null"
     ];
 
-    let expected_table = vec![
+    let expected_table = [
         vec![
             Token::new(JsonKind::True, 1, "true".to_string()), 
         ],
@@ -311,7 +314,7 @@ fn test_keywords_pass() {
     ];
 
         for (i, data) in data_table.iter().enumerate() {
-            let iter = TokenIter { chars: data.chars().peekable(), curr_line: 1 };
+            let iter = TokenIter::new(data);
             let mut collect: Vec<Token> = Vec::new();
             let expected = &expected_table[i];
 
@@ -330,7 +333,7 @@ fn test_keywords_pass() {
 #[test]
 #[should_panic(expected = "Unknown keyword detected")]
 fn test_keyword_fails() {
-    let data_table = vec![
+    let data_table = [
         "woooop"
     ];
 
@@ -338,7 +341,7 @@ fn test_keyword_fails() {
         Token::new(JsonKind::Zero, 1, "woooop".to_string())
     ];
 
-    let iter = TokenIter { chars: data_table[0].chars().peekable(), curr_line: 1 };
+    let iter = TokenIter::new(data_table[0]);
     let mut collect: Vec<Token> = Vec::new();
     let expected = expected_table;
 
@@ -352,3 +355,84 @@ fn test_keyword_fails() {
         assert_eq!(d.kind, td.kind, "token kind is incorrect");
     }
 }
+
+#[test]
+fn test_columns() {
+    let data = "{\n\t\"a\": [1, true]\n}";
+    let expected = [
+        (JsonKind::BeginObject, 1, 1),
+        (JsonKind::LineFeed, 1, 2),
+        (JsonKind::HorizontalTab, 2, 1),
+        (JsonKind::StringVal, 2, 2),
+        (JsonKind::NameSeperator, 2, 5),
+        (JsonKind::Space, 2, 6),
+        (JsonKind::BeginArray, 2, 7),
+        (JsonKind::Digit, 2, 8),
+        (JsonKind::ValueSeperator, 2, 9),
+        (JsonKind::Space, 2, 10),
+        (JsonKind::True, 2, 11),
+        (JsonKind::EndArray, 2, 15),
+        (JsonKind::LineFeed, 2, 16),
+        (JsonKind::EndObject, 3, 1),
+    ];
+
+    let collect: Vec<Token> = TokenIter::new(data).collect();
+
+    assert_eq!(collect.len(), expected.len(), "generated token data length does not match static test token data length");
+
+    for (d, (kind, line, column)) in collect.iter().zip(expected.iter()) {
+        assert_eq!(d.kind, *kind, "token kind is incorrect");
+        assert_eq!(d.line, *line, "token line number is incorrect");
+        assert_eq!(d.column, *column, "token column is incorrect");
+    }
+}
+
+#[test]
+fn test_comments() {
+    let data = "// head\r\n[1, /* a\nb */ 2]";
+    let expected = [
+        (JsonKind::Comment, "// head", 1, 1),
+        (JsonKind::CarriageReturn, "\r", 1, 8),
+        (JsonKind::LineFeed, "\n", 1, 9),
+        (JsonKind::BeginArray, "[", 2, 1),
+        (JsonKind::Digit, "1", 2, 2),
+        (JsonKind::ValueSeperator, ",", 2, 3),
+        (JsonKind::Space, " ", 2, 4),
+        (JsonKind::Comment, "/* a\nb */", 2, 5),
+        (JsonKind::Space, " ", 3, 5),
+        (JsonKind::Digit, "2", 3, 6),
+        (JsonKind::EndArray, "]", 3, 7),
+    ];
+
+    let collect: Vec<Token> = TokenIter::new(data).collect();
+
+    assert_eq!(collect.len(), expected.len(), "generated token data length does not match static test token data length");
+
+    for (d, (kind, text, line, column)) in collect.iter().zip(expected.iter()) {
+        assert_eq!(d.kind, *kind, "token kind is incorrect");
+        assert_eq!(d.text, *text, "token text is incorrect");
+        assert_eq!(d.line, *line, "token line number is incorrect");
+        assert_eq!(d.column, *column, "token column is incorrect");
+    }
+}
+
+#[test]
+#[should_panic(expected = "Nonterminating comment found")]
+fn test_comment_nonterm_fail() {
+    TokenIter::new("[1] /* open */ /*/").for_each(drop);
+}
+
+#[test]
+fn test_checked_errors() {
+    let collect: Vec<Result<Token, LexError>> = TokenIter::new("[\n  tru, 1]").checked().collect();
+
+    assert_eq!(collect.len(), 5);
+    assert_eq!(collect[4].as_ref().unwrap_err(), &LexError {
+        code: ErrorCode::UnknownKeyword,
+        message: "Unknown keyword detected: tru".to_string(),
+        line: 2,
+        column: 3,
+        span: Span { start: 4, end: 7 },
+        hint: Some(hint::unquoted("tru", Span { start: 4, end: 7 }))
+    });
+}
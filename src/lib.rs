@@ -0,0 +1,39 @@
+pub mod batch;
+pub mod canonical;
+pub mod cbor;
+pub mod code;
+pub mod convert;
+pub mod cst;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod diagnostic;
+pub mod diff;
+pub mod edit;
+pub mod explore;
+pub mod fix;
+pub mod highlight;
+pub mod hint;
+pub mod infer;
+pub mod jq;
+pub mod lexer;
+pub mod lsp;
+pub mod merge;
+pub mod minify;
+pub mod msgpack;
+pub mod parser;
+pub mod patch;
+pub mod path;
+pub mod pointer;
+pub mod query;
+pub mod repair;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod serializer;
+pub mod stats;
+pub mod value;
+
+#[cfg(feature = "serde")]
+pub use de::{from_str, Deserializer, Error};
+#[cfg(feature = "serde")]
+pub use ser::{to_string, to_string_pretty, to_value, to_writer, Serializer};
@@ -0,0 +1,642 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use crate::code::ErrorCode;
+use crate::cst::{Document, Node, NodeKind};
+use crate::lexer::{JsonKind, Token, TokenIter};
+use crate::parser::{decode_escapes, Parser};
+use crate::path::{Path, PathElement};
+use crate::pointer::Pointer;
+use crate::schema::{percent_decode, Schema};
+use crate::serializer::{write_string, Serializer};
+use crate::value::Value;
+
+/// Serves the Language Server Protocol over a pair of byte streams, usually
+/// stdin and stdout, until the client sends `exit`.
+pub fn run<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = Server::new();
+
+    while let Some(body) = read_message(&mut input)? {
+        let message = match Parser::checked(Box::new(TokenIter::new(&body).checked())).try_parse() {
+            Ok(message) => message,
+            Err(err) => {
+                write_message(&mut output, &error_response(&Value::Null, -32700, &err.to_string()))?;
+                continue;
+            }
+        };
+        if message.get("method").and_then(Value::as_str) == Some("exit") {
+            break;
+        }
+
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+// Messages are framed by HTTP style headers, only Content-Length matters
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body).map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = Serializer::compact().serialize(message);
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// The open documents and the requests that work on them, kept apart from
+/// the transport so it can be driven message by message.
+pub struct Server {
+    documents: HashMap<String, String>
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server { documents: HashMap::new() }
+    }
+
+    /// Handles one request or notification, returning the responses and
+    /// notifications to send back.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let uri = param(params, "/textDocument/uri").and_then(Value::as_str).unwrap_or("").to_string();
+
+        let id = match message.get("id") {
+            Some(id) => id,
+            // Notifications get no response, though changes publish diagnostics
+            None => return match method {
+                "textDocument/didOpen" => {
+                    let text = param(params, "/textDocument/text").and_then(Value::as_str).unwrap_or("");
+                    self.documents.insert(uri.clone(), text.to_string());
+                    vec![self.diagnostics(&uri)]
+                },
+                "textDocument/didChange" => {
+                    // Only full text sync is offered, so the last change holds everything
+                    let changes = match params.get("contentChanges") {
+                        Some(Value::Array(changes)) => changes,
+                        _ => return Vec::new()
+                    };
+                    match changes.last().and_then(|change| change.get("text")).and_then(Value::as_str) {
+                        Some(text) => {
+                            self.documents.insert(uri.clone(), text.to_string());
+                            vec![self.diagnostics(&uri)]
+                        },
+                        None => Vec::new()
+                    }
+                },
+                "textDocument/didClose" => {
+                    self.documents.remove(&uri);
+                    vec![notification("textDocument/publishDiagnostics", object(vec![
+                        ("uri", Value::from(uri.as_str())),
+                        ("diagnostics", Value::Array(Vec::new()))
+                    ]))]
+                },
+                _ => Vec::new()
+            }
+        };
+
+        let text = self.documents.get(&uri).map(String::as_str).unwrap_or("");
+        let result = match method {
+            "initialize" => capabilities(),
+            "shutdown" => Value::Null,
+            "textDocument/formatting" => formatting(text, params),
+            "textDocument/documentSymbol" => document_symbols(text),
+            "textDocument/foldingRange" => folding_ranges(text),
+            "textDocument/hover" => hover(text, params),
+            "textDocument/completion" => completion(&uri, text, params),
+            _ => return vec![error_response(id, -32601, &format!("Method not found: {}", method))]
+        };
+        vec![response(id, result)]
+    }
+
+    // The parser stops at the first error, so there is at most one
+    fn diagnostics(&self, uri: &str) -> Value {
+        let text = self.documents.get(uri).map(String::as_str).unwrap_or("");
+        let lines = LineIndex::new(text);
+
+        let diagnostics = match Parser::checked(Box::new(TokenIter::new(text).checked())).try_validate() {
+            Ok(()) => Vec::new(),
+            Err(err) => {
+                let message = match &err.hint {
+                    Some(hint) => format!("{} at {}\nhint: {}", err.message, err.path, hint),
+                    None => format!("{} at {}", err.message, err.path)
+                };
+                vec![object(vec![
+                    ("range", lines.range(err.span.start, err.span.end)),
+                    ("severity", Value::from(1i64)),
+                    ("code", Value::from(err.code.as_str())),
+                    ("source", Value::from("jsorry")),
+                    ("message", Value::from(message.as_str()))
+                ])]
+            }
+        };
+
+        notification("textDocument/publishDiagnostics", object(vec![
+            ("uri", Value::from(uri)),
+            ("diagnostics", Value::Array(diagnostics))
+        ]))
+    }
+}
+
+impl Default for Server {
+    fn default() -> Server {
+        Server::new()
+    }
+}
+
+fn capabilities() -> Value {
+    object(vec![
+        ("capabilities", object(vec![
+            ("textDocumentSync", Value::from(1i64)),
+            ("documentFormattingProvider", Value::from(true)),
+            ("documentSymbolProvider", Value::from(true)),
+            ("foldingRangeProvider", Value::from(true)),
+            ("hoverProvider", Value::from(true)),
+            ("completionProvider", object(vec![
+                ("triggerCharacters", Value::Array(vec![Value::from("\""), Value::from(":")]))
+            ]))
+        ])),
+        ("serverInfo", object(vec![("name", Value::from("jsorry"))]))
+    ])
+}
+
+// Features that need the whole tree only work on documents that parse,
+// comments are skipped like whitespace
+fn tree(text: &str) -> Option<Document> {
+    Document::try_parse(text).ok()
+}
+
+// One edit replacing the whole document with its pretty printed value,
+// indented as the client's formatting options ask
+fn formatting(text: &str, params: &Value) -> Value {
+    let value = match Parser::checked(Box::new(TokenIter::new(text).checked())).try_parse() {
+        Ok(value) => value,
+        Err(_) => return Value::Array(Vec::new())
+    };
+    let tab_size = param(params, "/options/tabSize").and_then(Value::as_f64).map_or(2, |size| size as usize);
+    let indent = match param(params, "/options/insertSpaces") {
+        Some(Value::Bool(false)) => "\t".to_string(),
+        _ => " ".repeat(tab_size)
+    };
+    let formatted = Serializer::new().indent(&indent).serialize(&value) + "\n";
+    if formatted == text {
+        return Value::Array(Vec::new());
+    }
+
+    let lines = LineIndex::new(text);
+    Value::Array(vec![object(vec![
+        ("range", lines.range(0, text.len())),
+        ("newText", Value::from(formatted.as_str()))
+    ])])
+}
+
+fn document_symbols(text: &str) -> Value {
+    match tree(text) {
+        Some(doc) => Value::Array(symbols(&doc.root, &LineIndex::new(text))),
+        None => Value::Null
+    }
+}
+
+fn symbols(node: &Node, lines: &LineIndex) -> Vec<Value> {
+    match &node.kind {
+        NodeKind::Object { members, .. } => members.iter().map(|member| {
+            let key = lines.token_span(&member.key);
+            let name = match member.name() {
+                name if name.is_empty() => member.key.text.clone(),
+                name => name
+            };
+            symbol(&name, (key.0, lines.node_span(&member.value).1), key, &member.value, lines)
+        }).collect(),
+        NodeKind::Array { items, .. } => items.iter().enumerate().map(|(idx, item)| {
+            let span = lines.node_span(&item.value);
+            symbol(&idx.to_string(), span, span, &item.value, lines)
+        }).collect(),
+        NodeKind::Scalar(_) => Vec::new()
+    }
+}
+
+fn symbol(name: &str, span: (usize, usize), selection: (usize, usize), value: &Node, lines: &LineIndex) -> Value {
+    // Object, Array, String, Number, Boolean and Null in LSP's SymbolKind
+    let kind: i64 = match &value.kind {
+        NodeKind::Object { .. } => 19,
+        NodeKind::Array { .. } => 18,
+        NodeKind::Scalar(tokens) => match tokens[0].kind {
+            JsonKind::StringVal => 15,
+            JsonKind::True | JsonKind::False => 17,
+            JsonKind::Null => 21,
+            _ => 16
+        }
+    };
+
+    object(vec![
+        ("name", Value::from(name)),
+        ("kind", Value::from(kind)),
+        ("range", lines.range(span.0, span.1)),
+        ("selectionRange", lines.range(selection.0, selection.1)),
+        ("children", Value::Array(symbols(value, lines)))
+    ])
+}
+
+// Folds hide the lines between the brackets, leaving the closing one visible
+fn folding_ranges(text: &str) -> Value {
+    let doc = match tree(text) {
+        Some(doc) => doc,
+        None => return Value::Null
+    };
+    let lines = LineIndex::new(text);
+    let mut ranges = Vec::new();
+    folds(&doc.root, &lines, &mut ranges);
+    Value::Array(ranges)
+}
+
+fn folds(node: &Node, lines: &LineIndex, ranges: &mut Vec<Value>) {
+    let children: Vec<&Node> = match &node.kind {
+        NodeKind::Object { members, .. } => members.iter().map(|member| &member.value).collect(),
+        NodeKind::Array { items, .. } => items.iter().map(|item| &item.value).collect(),
+        NodeKind::Scalar(_) => return
+    };
+
+    let (start, end) = lines.node_span(node);
+    let (start_line, end_line) = (lines.position(start).0, lines.position(end).0);
+    if end_line > start_line + 1 {
+        ranges.push(object(vec![
+            ("startLine", Value::from(start_line as i64)),
+            ("endLine", Value::from(end_line as i64 - 1))
+        ]));
+    }
+    for child in children {
+        folds(child, lines, ranges);
+    }
+}
+
+fn hover(text: &str, params: &Value) -> Value {
+    let (doc, lines) = match tree(text) {
+        Some(doc) => (doc, LineIndex::new(text)),
+        None => return Value::Null
+    };
+    let offset = match position_param(params) {
+        Some((line, character)) => lines.offset(line, character),
+        None => return Value::Null
+    };
+
+    match locate(&doc.root, offset, Pointer::root(), &lines) {
+        Some((pointer, span)) => {
+            let contents = if pointer.is_root() {
+                "JSON pointer: `\"\"` (the whole document)".to_string()
+            } else {
+                format!("JSON pointer: `{}`", pointer)
+            };
+            object(vec![
+                ("contents", object(vec![("kind", Value::from("markdown")), ("value", Value::from(contents.as_str()))])),
+                ("range", lines.range(span.0, span.1))
+            ])
+        },
+        None => Value::Null
+    }
+}
+
+// The innermost value, or key, found at a byte offset
+fn locate(node: &Node, offset: usize, pointer: Pointer, lines: &LineIndex) -> Option<(Pointer, (usize, usize))> {
+    let span = lines.node_span(node);
+    if offset < span.0 || offset >= span.1 {
+        return None;
+    }
+
+    match &node.kind {
+        NodeKind::Object { members, .. } => {
+            for member in members {
+                let child = pointer.child(&member.name());
+                let key = lines.token_span(&member.key);
+                if offset >= key.0 && offset < key.1 {
+                    return Some((child, key));
+                }
+                if let Some(found) = locate(&member.value, offset, child, lines) {
+                    return Some(found);
+                }
+            }
+        },
+        NodeKind::Array { items, .. } => {
+            for (idx, item) in items.iter().enumerate() {
+                if let Some(found) = locate(&item.value, offset, pointer.child(&idx.to_string()), lines) {
+                    return Some(found);
+                }
+            }
+        },
+        NodeKind::Scalar(_) => {}
+    }
+    Some((pointer, span))
+}
+
+// Suggests property names, and values once a key has been written, from the
+// schema named by a top level `$schema` member
+fn completion(uri: &str, text: &str, params: &Value) -> Value {
+    let empty = Value::Array(Vec::new());
+    let lines = LineIndex::new(text);
+    let offset = match position_param(params) {
+        Some((line, character)) => lines.offset(line, character),
+        None => return empty
+    };
+    let schema = match schema_location(text).and_then(|location| load_schema(uri, &location)) {
+        Some(schema) => schema,
+        None => return empty
+    };
+    let context = match Context::at(&text[..offset]) {
+        Some(context) => context,
+        None => return empty
+    };
+
+    let mut items = Vec::new();
+    if context.in_key {
+        for sub in schema.subschemas(&context.path) {
+            let properties = match sub.get("properties") {
+                Some(Value::Object(properties)) => properties,
+                _ => continue
+            };
+            for (name, property) in properties {
+                if context.keys.contains(name) || items.iter().any(|item: &Value| item.get("label").and_then(Value::as_str) == Some(name)) {
+                    continue;
+                }
+                let mut quoted = String::new();
+                write_string(&mut quoted, name);
+                let insert = if context.in_string { name.clone() } else { format!("{}: ", quoted) };
+                items.push(completion_item(name, 10, &insert, property));
+            }
+        }
+    } else if !context.in_string {
+        for sub in schema.subschemas(&context.path) {
+            let mut options: Vec<Value> = match (sub.get("enum"), sub.get("const")) {
+                (Some(Value::Array(options)), _) => options.clone(),
+                (_, Some(option)) => vec![option.clone()],
+                _ => Vec::new()
+            };
+            if sub.get("type").and_then(Value::as_str) == Some("boolean") {
+                options.extend([Value::Bool(true), Value::Bool(false)]);
+            }
+            for option in options {
+                let label = Serializer::compact().serialize(&option);
+                if !items.iter().any(|item: &Value| item.get("label").and_then(Value::as_str) == Some(&label)) {
+                    items.push(completion_item(&label, 12, &label, sub));
+                }
+            }
+        }
+    }
+    Value::Array(items)
+}
+
+fn completion_item(label: &str, kind: i64, insert: &str, schema: &Value) -> Value {
+    let mut members = vec![
+        ("label", Value::from(label)),
+        ("kind", Value::from(kind)),
+        ("insertText", Value::from(insert))
+    ];
+    let detail = match schema.get("type") {
+        Some(Value::String(name)) => Some(name.clone()),
+        Some(Value::Array(names)) => Some(names.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | ")),
+        _ => None
+    };
+    if let Some(detail) = detail {
+        members.push(("detail", Value::from(detail.as_str())));
+    }
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        members.push(("documentation", Value::from(description)));
+    }
+    object(members)
+}
+
+/// Where the cursor sits in a document that is usually only half written,
+/// worked out from the tokens before it.
+struct Context {
+    // Of the innermost object or array, or of the value being written
+    path: Path,
+    in_key: bool,
+    in_string: bool,
+    // Keys already used in the innermost object
+    keys: Vec<String>
+}
+
+enum Frame {
+    Object { key: Option<String>, keys: Vec<String> },
+    Array { index: usize }
+}
+
+impl Context {
+    fn at(before: &str) -> Option<Context> {
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut in_string = false;
+
+        for result in TokenIter::new(before).checked() {
+            let token = match result {
+                Ok(token) => token,
+                Err(err) => {
+                    in_string = err.code == ErrorCode::UnterminatedString;
+                    break;
+                }
+            };
+            match (token.kind, frames.last_mut()) {
+                (JsonKind::BeginObject, _) => frames.push(Frame::Object { key: None, keys: Vec::new() }),
+                (JsonKind::BeginArray, _) => frames.push(Frame::Array { index: 0 }),
+                (JsonKind::EndObject | JsonKind::EndArray, _) => {
+                    frames.pop();
+                },
+                // A lone quote at the very end lexes as a string of its own
+                (JsonKind::StringVal, _) if token.text.len() < 2 || !token.text.ends_with('"') => in_string = true,
+                // Keys with half typed escapes still take up the member, but
+                // are not offered as known keys
+                (JsonKind::StringVal, Some(Frame::Object { key: key @ None, keys })) => match decode_escapes(&token.text) {
+                    Ok(name) => {
+                        keys.push(name.clone());
+                        *key = Some(name);
+                    },
+                    Err(_) => *key = Some(token.text[1..token.text.len() - 1].to_string())
+                },
+                (JsonKind::ValueSeperator, Some(Frame::Object { key, .. })) => *key = None,
+                (JsonKind::ValueSeperator, Some(Frame::Array { index })) => *index += 1,
+                _ => {}
+            }
+        }
+
+        let mut path = Path::root();
+        let (last, outer) = frames.split_last()?;
+        for frame in outer {
+            match frame {
+                Frame::Object { key, .. } => path.push(PathElement::Key(key.clone()?)),
+                Frame::Array { index } => path.push(PathElement::Index(*index))
+            }
+        }
+
+        Some(match last {
+            Frame::Object { key: None, keys } => Context { path, in_key: true, in_string, keys: keys.clone() },
+            Frame::Object { key: Some(key), keys } => Context {
+                path: path.key(key),
+                in_key: false,
+                in_string,
+                keys: keys.clone()
+            },
+            Frame::Array { index } => Context { path: path.index(*index), in_key: false, in_string, keys: Vec::new() }
+        })
+    }
+}
+
+// The value of a `$schema` member on the top level object
+fn schema_location(text: &str) -> Option<String> {
+    let tokens: Vec<Token> = TokenIter::new(text).checked()
+        .map_while(Result::ok)
+        .filter(|token| !token.kind.is_trivia())
+        .collect();
+
+    let mut depth = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.kind {
+            JsonKind::BeginObject | JsonKind::BeginArray => depth += 1,
+            JsonKind::EndObject | JsonKind::EndArray => depth -= 1,
+            JsonKind::StringVal if depth == 1 && token.text == "\"$schema\"" => {
+                if let [colon, value, ..] = &tokens[idx + 1..] {
+                    if colon.kind == JsonKind::NameSeperator && value.kind == JsonKind::StringVal {
+                        return decode_escapes(&value.text).ok();
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    None
+}
+
+// Schemas are read from local files, relative to the document when the
+// location is a relative path. Remote schemas are not fetched.
+fn load_schema(uri: &str, location: &str) -> Option<Schema> {
+    let path = match location.strip_prefix("file://") {
+        Some(path) => percent_decode(path),
+        None if location.contains("://") => return None,
+        None => {
+            let document = percent_decode(uri.strip_prefix("file://")?);
+            let dir = std::path::Path::new(&document).parent()?;
+            dir.join(location).to_string_lossy().into_owned()
+        }
+    };
+
+    let contents = fs::read_to_string(path).ok()?;
+    let value = Parser::checked(Box::new(TokenIter::new(&contents).checked())).try_parse().ok()?;
+    Schema::new(value).ok()
+}
+
+/// Converts byte offsets to LSP positions, which count lines from 0 and
+/// characters in UTF-16 code units.
+struct LineIndex<'a> {
+    text: &'a str,
+    starts: Vec<usize>
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> LineIndex<'a> {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(idx, _)| idx + 1));
+        LineIndex { text, starts }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        (line, self.text[self.starts[line]..offset].encode_utf16().count())
+    }
+
+    fn offset(&self, line: usize, character: usize) -> usize {
+        let start = match self.starts.get(line) {
+            Some(start) => *start,
+            None => return self.text.len()
+        };
+        let end = self.starts.get(line + 1).map_or(self.text.len(), |next| next - 1);
+
+        let mut units = 0;
+        for (idx, c) in self.text[start..end].char_indices() {
+            if units >= character {
+                return start + idx;
+            }
+            units += c.len_utf16();
+        }
+        end
+    }
+
+    fn range(&self, start: usize, end: usize) -> Value {
+        let position = |(line, character): (usize, usize)| object(vec![
+            ("line", Value::from(line as i64)),
+            ("character", Value::from(character as i64))
+        ]);
+        object(vec![("start", position(self.position(start))), ("end", position(self.position(end)))])
+    }
+
+    // Tokens know their line and column in characters, both from 1
+    fn token_span(&self, token: &Token) -> (usize, usize) {
+        let line_start = self.starts[token.line - 1];
+        let start = line_start + self.text[line_start..].chars()
+            .take(token.column - 1)
+            .map(char::len_utf8)
+            .sum::<usize>();
+        (start, start + token.text.len())
+    }
+
+    fn node_span(&self, node: &Node) -> (usize, usize) {
+        match &node.kind {
+            NodeKind::Scalar(tokens) => (self.token_span(&tokens[0]).0, self.token_span(&tokens[tokens.len() - 1]).1),
+            NodeKind::Array { open, close, .. } | NodeKind::Object { open, close, .. } =>
+                (self.token_span(open).0, self.token_span(close).1)
+        }
+    }
+}
+
+fn position_param(params: &Value) -> Option<(usize, usize)> {
+    let line = param(params, "/position/line")?.as_f64()?;
+    let character = param(params, "/position/character")?.as_f64()?;
+    Some((line as usize, character as usize))
+}
+
+fn param<'v>(params: &'v Value, pointer: &str) -> Option<&'v Value> {
+    Pointer::parse(pointer).ok()?.resolve(params)
+}
+
+fn object(members: Vec<(&str, Value)>) -> Value {
+    Value::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn response(id: &Value, result: Value) -> Value {
+    object(vec![("jsonrpc", Value::from("2.0")), ("id", id.clone()), ("result", result)])
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    object(vec![
+        ("jsonrpc", Value::from("2.0")),
+        ("id", id.clone()),
+        ("error", object(vec![("code", Value::from(code)), ("message", Value::from(message))]))
+    ])
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    object(vec![("jsonrpc", Value::from("2.0")), ("method", Value::from(method)), ("params", params)])
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,203 @@
+use std::io::Cursor;
+
+use crate::lexer::TokenIter;
+use crate::lsp::{run, Server};
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+const URI: &str = "file:///tmp/doc.json";
+
+fn parse(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+fn compact(value: &Value) -> String {
+    Serializer::compact().serialize(value)
+}
+
+fn quoted(text: &str) -> String {
+    compact(&Value::from(text))
+}
+
+// Opens `text` and returns the published diagnostics
+fn open(server: &mut Server, uri: &str, text: &str) -> Value {
+    let message = format!("{{\"jsonrpc\": \"2.0\", \"method\": \"textDocument/didOpen\", \
+                           \"params\": {{\"textDocument\": {{\"uri\": {}, \"text\": {}}}}}}}", quoted(uri), quoted(text));
+    let mut replies = server.handle(&parse(&message));
+    assert_eq!(replies.len(), 1);
+    replies.remove(0).get("params").unwrap().get("diagnostics").unwrap().clone()
+}
+
+fn request(server: &mut Server, method: &str, params: &str) -> Value {
+    let message = format!("{{\"jsonrpc\": \"2.0\", \"id\": 7, \"method\": {}, \"params\": {}}}", quoted(method), params);
+    let mut replies = server.handle(&parse(&message));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].get("id"), Some(&Value::from(7)));
+    replies.remove(0).get("result").unwrap().clone()
+}
+
+fn at(line: usize, character: usize) -> String {
+    format!("{{\"textDocument\": {{\"uri\": {}}}, \"position\": {{\"line\": {}, \"character\": {}}}}}", quoted(URI), line, character)
+}
+
+fn document() -> String {
+    format!("{{\"textDocument\": {{\"uri\": {}}}}}", quoted(URI))
+}
+
+fn frame(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+#[test]
+fn test_run() {
+    let input = [
+        frame("{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"initialize\", \"params\": {}}"),
+        frame("{\"jsonrpc\": \"2.0\", \"method\": \"initialized\", \"params\": {}}"),
+        frame("{\"jsonrpc\": \"2.0\", \"id\": 2, \"method\": \"unknown\"}"),
+        frame("{broken"),
+        frame("{\"jsonrpc\": \"2.0\", \"id\": 3, \"method\": \"shutdown\"}"),
+        frame("{\"jsonrpc\": \"2.0\", \"method\": \"exit\"}"),
+        frame("{\"jsonrpc\": \"2.0\", \"id\": 4, \"method\": \"shutdown\"}")
+    ].concat();
+    let mut output = Vec::new();
+    run(Cursor::new(input), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let bodies: Vec<Value> = output.split("Content-Length: ").skip(1)
+        .map(|message| parse(message.split_once("\r\n\r\n").unwrap().1))
+        .collect();
+    assert_eq!(bodies.len(), 4);
+    assert_eq!(bodies[0].get("result").unwrap().get("capabilities").unwrap().get("hoverProvider"), Some(&Value::Bool(true)));
+    assert_eq!(bodies[1].get("error").unwrap().get("code"), Some(&Value::from(-32601)));
+    assert_eq!(bodies[2].get("error").unwrap().get("code"), Some(&Value::from(-32700)));
+    assert_eq!(compact(&bodies[3]), "{\"jsonrpc\":\"2.0\",\"id\":3,\"result\":null}");
+}
+
+#[test]
+fn test_diagnostics() {
+    let mut server = Server::new();
+    assert_eq!(compact(&open(&mut server, URI, "{\"a\": 1}")), "[]");
+
+    // Characters are counted in UTF-16, so the emoji takes two
+    let diagnostics = open(&mut server, URI, "{\"a\": \"😀\",\n \"b\": [1,]}");
+    assert_eq!(compact(diagnostics.index(0).unwrap().get("range").unwrap()),
+               "{\"start\":{\"line\":1,\"character\":9},\"end\":{\"line\":1,\"character\":10}}");
+    assert_eq!(diagnostics.index(0).unwrap().get("code").unwrap().as_str(), Some("E0007"));
+    assert_eq!(diagnostics.index(0).unwrap().get("message").unwrap().as_str(),
+               Some("Unexpected token EndArray at $.b[1]\nhint: trailing commas are not allowed, remove this comma"));
+
+    let change = format!("{{\"jsonrpc\": \"2.0\", \"method\": \"textDocument/didChange\", \"params\": \
+                          {{\"textDocument\": {{\"uri\": {}}}, \"contentChanges\": [{{\"text\": \"[1, 2]\"}}]}}}}", quoted(URI));
+    let replies = server.handle(&parse(&change));
+    assert_eq!(compact(replies[0].get("params").unwrap().get("diagnostics").unwrap()), "[]");
+}
+
+#[test]
+fn test_formatting() {
+    let mut server = Server::new();
+    open(&mut server, URI, "{\"a\":[1,2]}");
+    assert_eq!(compact(&request(&mut server, "textDocument/formatting", &document())),
+               "[{\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":11}},\
+               \"newText\":\"{\\n  \\\"a\\\": [\\n    1,\\n    2\\n  ]\\n}\\n\"}]");
+
+    let options = |options: &str| format!("{{\"textDocument\": {{\"uri\": {}}}, \"options\": {}}}", quoted(URI), options);
+    let new_text = |result: Value| result.index(0).unwrap().get("newText").unwrap().as_str().unwrap().to_string();
+    assert_eq!(new_text(request(&mut server, "textDocument/formatting", &options("{\"tabSize\": 4, \"insertSpaces\": true}"))),
+               "{\n    \"a\": [\n        1,\n        2\n    ]\n}\n");
+    assert_eq!(new_text(request(&mut server, "textDocument/formatting", &options("{\"tabSize\": 8, \"insertSpaces\": false}"))),
+               "{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t]\n}\n");
+
+    open(&mut server, URI, "[1,]");
+    assert_eq!(compact(&request(&mut server, "textDocument/formatting", &document())), "[]");
+}
+
+#[test]
+fn test_document_symbols() {
+    let mut server = Server::new();
+    open(&mut server, URI, "{\"name\": \"x\",\n \"tags\": [true, null],\n \"\": {\"n\": 1}}");
+    let symbols = request(&mut server, "textDocument/documentSymbol", &document());
+
+    let outline = |symbol: &Value| format!("{}:{}", symbol.get("name").unwrap().as_str().unwrap(), compact(symbol.get("kind").unwrap()));
+    let Value::Array(symbols) = symbols else { panic!("expected symbols") };
+    assert_eq!(symbols.iter().map(outline).collect::<Vec<_>>(), vec!["name:15", "tags:18", "\"\":19"]);
+    let Some(Value::Array(children)) = symbols[1].get("children") else { panic!("expected children") };
+    assert_eq!(children.iter().map(outline).collect::<Vec<_>>(), vec!["0:17", "1:21"]);
+    assert_eq!(compact(symbols[1].get("range").unwrap()),
+               "{\"start\":{\"line\":1,\"character\":1},\"end\":{\"line\":1,\"character\":21}}");
+    assert_eq!(compact(symbols[1].get("selectionRange").unwrap()),
+               "{\"start\":{\"line\":1,\"character\":1},\"end\":{\"line\":1,\"character\":7}}");
+}
+
+#[test]
+fn test_folding_ranges() {
+    let mut server = Server::new();
+    open(&mut server, URI, "{\n  \"a\": [\n    1\n  ],\n  \"b\": {}\n}");
+    assert_eq!(compact(&request(&mut server, "textDocument/foldingRange", &document())),
+               "[{\"startLine\":0,\"endLine\":4},{\"startLine\":1,\"endLine\":2}]");
+}
+
+#[test]
+fn test_hover() {
+    let mut server = Server::new();
+    open(&mut server, URI, "{\"a/b\": [1, {\"c\": true}]}");
+
+    let hover = request(&mut server, "textDocument/hover", &at(0, 19));
+    assert_eq!(hover.get("contents").unwrap().get("value").unwrap().as_str(), Some("JSON pointer: `/a~1b/1/c`"));
+    assert_eq!(compact(hover.get("range").unwrap()), "{\"start\":{\"line\":0,\"character\":18},\"end\":{\"line\":0,\"character\":22}}");
+
+    let hover = request(&mut server, "textDocument/hover", &at(0, 2));
+    assert_eq!(hover.get("contents").unwrap().get("value").unwrap().as_str(), Some("JSON pointer: `/a~1b`"));
+    let hover = request(&mut server, "textDocument/hover", &at(0, 9));
+    assert_eq!(hover.get("contents").unwrap().get("value").unwrap().as_str(), Some("JSON pointer: `/a~1b/0`"));
+    assert_eq!(request(&mut server, "textDocument/hover", &at(3, 0)), Value::Null);
+}
+
+#[test]
+fn test_completion() {
+    let dir = std::env::temp_dir().join(format!("jsorry-lsp-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("schema.json"), "{
+        \"properties\": {
+            \"name\": {\"type\": \"string\", \"description\": \"Who it is\"},
+            \"role\": {\"enum\": [\"admin\", \"user\"]},
+            \"tags\": {\"type\": \"array\", \"items\": {\"$ref\": \"#/$defs/tag\"}}
+        },
+        \"$defs\": {\"tag\": {\"properties\": {\"label\": {}, \"active\": {\"type\": \"boolean\"}}}}
+    }").unwrap();
+    let uri = format!("file://{}", dir.join("doc.json").display());
+    let position = |line: usize, character: usize| format!(
+        "{{\"textDocument\": {{\"uri\": {}}}, \"position\": {{\"line\": {}, \"character\": {}}}}}", quoted(&uri), line, character);
+    let labels = |items: Value| match items {
+        Value::Array(items) => items.iter().map(|item| item.get("label").unwrap().as_str().unwrap().to_string()).collect::<Vec<_>>(),
+        _ => panic!("expected completion items")
+    };
+
+    let mut server = Server::new();
+    open(&mut server, &uri, "{\"$schema\": \"schema.json\", \"name\": \"x\", \n}");
+    let items = request(&mut server, "textDocument/completion", &position(0, 40));
+    assert_eq!(labels(items.clone()), vec!["role", "tags"]);
+    assert_eq!(items.index(0).unwrap().get("insertText").unwrap().as_str(), Some("\"role\": "));
+
+    // Half way through a key, in an array of objects behind a `$ref`
+    open(&mut server, &uri, "{\"$schema\": \"schema.json\", \"tags\": [{}, {\"ac");
+    let items = request(&mut server, "textDocument/completion", &position(0, 44));
+    assert_eq!(labels(items.clone()), vec!["label", "active"]);
+    assert_eq!(items.index(1).unwrap().get("insertText").unwrap().as_str(), Some("active"));
+
+    open(&mut server, &uri, "{\"$schema\": \"schema.json\", \"role\": , \"tags\": [{\"active\": }]}");
+    assert_eq!(labels(request(&mut server, "textDocument/completion", &position(0, 35))), vec!["\"admin\"", "\"user\""]);
+    assert_eq!(labels(request(&mut server, "textDocument/completion", &position(0, 57))), vec!["true", "false"]);
+
+    open(&mut server, &uri, "{\"name\": \"x\", }");
+    assert!(labels(request(&mut server, "textDocument/completion", &position(0, 14))).is_empty());
+
+    // Half typed escapes are skipped rather than taking the server down
+    let text = "{\"$schema\": \"schema.json\", \"\\uZZZZ\": 1, \"";
+    open(&mut server, &uri, text);
+    assert_eq!(labels(request(&mut server, "textDocument/completion", &position(0, text.chars().count()))), vec!["name", "role", "tags"]);
+    open(&mut server, &uri, "{\"$schema\": \"sch\\qema.json\", \"");
+    assert!(labels(request(&mut server, "textDocument/completion", &position(0, 28))).is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,9 +1,29 @@
-mod lexer;
-mod parser;
-
-use std::{env, fs, process};
-use crate::lexer::TokenIter;
-use crate::parser::Parser;
+use std::{env, fs, io, process};
+use std::io::{IsTerminal, Write};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use jsorry::batch::{check_files, collect_files, BatchOptions, Pattern};
+use jsorry::canonical::digest;
+use jsorry::code::ErrorCode;
+use jsorry::convert::{from_csv_strings, Format};
+use jsorry::cst::Document;
+use jsorry::diagnostic::{self, Diagnostic};
+use jsorry::diff::{diff, Change, DiffOptions};
+use jsorry::fix::{apply_safe_fixes, unified_diff};
+use jsorry::infer::infer_schema;
+use jsorry::jq::Filter;
+use jsorry::lexer::TokenIter;
+use jsorry::merge::{merge_diff, merge_patch};
+use jsorry::minify::minify;
+use jsorry::parser::{ParseError, Parser, Position};
+use jsorry::patch::Patch;
+use jsorry::path::Path;
+use jsorry::pointer::Pointer;
+use jsorry::query::JsonPath;
+use jsorry::repair::repair;
+use jsorry::schema::Schema;
+use jsorry::serializer::Serializer;
+use jsorry::value::Value;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -13,16 +33,434 @@ fn main() {
         process::exit(1);
     });
 
-    println!("Starting to process {} file", config.file_path);
-    let contents = fs::read_to_string(&config.file_path).unwrap();
-    let tokens = TokenIter::new(&contents);
-    let mut parser = Parser::new(Box::new(tokens));
-    parser.start();
-    println!("File {} looks good 👍", config.file_path);
+    let output = config.output;
+    match config.command {
+        Command::Check { file_path } => {
+            if output == OutputFormat::Text {
+                println!("Starting to process {} file", file_path);
+            }
+            let contents = fs::read_to_string(&file_path).unwrap();
+            let tokens = TokenIter::new(&contents).checked();
+            let mut parser = Parser::checked(Box::new(tokens));
+            parser.try_start().unwrap_or_else(|err| report(&file_path, &err, output));
+            looks_good(&file_path, output);
+        },
+        Command::Query { query, file_path } => {
+            let path = JsonPath::parse(&query).unwrap_or_else(|err| {
+                println!("Invalid query: {}", err);
+                process::exit(1);
+            });
+            let value = parse_file(&file_path, output);
+            let serializer = Serializer::compact();
+
+            for node in path.query(&value) {
+                println!("{}\t{}", node.path.normalized(), serializer.serialize(node.value));
+            }
+        },
+        Command::Jq { filter, file_path } => {
+            let filter = Filter::parse(&filter).unwrap_or_else(|err| {
+                println!("Invalid filter: {}", err);
+                process::exit(1);
+            });
+            let value = parse_file(&file_path, output);
+            let serializer = Serializer::new();
+
+            let outputs = filter.apply(&value).unwrap_or_else(|err| {
+                println!("Filter failed: {}", err);
+                process::exit(1);
+            });
+            for output in outputs {
+                println!("{}", serializer.serialize(&output));
+            }
+        },
+        Command::Patch { patch_path, file_path } => {
+            let patch = Patch::from_value(&parse_file(&patch_path, output)).unwrap_or_else(|err| {
+                println!("Invalid patch: {}", err);
+                process::exit(1);
+            });
+            let value = parse_file(&file_path, output);
+
+            let patched = patch.apply(&value).unwrap_or_else(|err| {
+                println!("Patch failed: {}", err);
+                process::exit(1);
+            });
+            println!("{}", Serializer::new().serialize(&patched));
+        },
+        Command::Diff { from_path, to_path, format, options } => {
+            let (from, from_positions) = parse_document(&from_path, output);
+            let (to, to_positions) = parse_document(&to_path, output);
+
+            match format {
+                DiffFormat::Text => {
+                    let serializer = Serializer::compact();
+                    let line = |positions: &HashMap<Path, Position>, path: &Path| positions.get(path)
+                        .map(|pos| pos.line)
+                        .unwrap_or(0);
+
+                    for change in diff(&from, &to, &options) {
+                        match change {
+                            Change::Added { path, value } => println!("+ {} ({}:{}): {}",
+                                path, to_path, line(&to_positions, &path), serializer.serialize(value)),
+                            Change::Removed { path, value } => println!("- {} ({}:{}): {}",
+                                path, from_path, line(&from_positions, &path), serializer.serialize(value)),
+                            Change::Changed { from_path: old_path, to_path: new_path, old, new } => println!(
+                                "~ {} ({}:{}, {}:{}): {} -> {}",
+                                new_path, from_path, line(&from_positions, &old_path),
+                                to_path, line(&to_positions, &new_path),
+                                serializer.serialize(old), serializer.serialize(new))
+                        }
+                    }
+                },
+                DiffFormat::Patch => {
+                    let patch = Patch::diff(&from, &to);
+                    println!("{}", Serializer::new().serialize(&patch.to_value()));
+                },
+                DiffFormat::MergePatch => {
+                    println!("{}", Serializer::new().serialize(&merge_diff(&from, &to)));
+                }
+            }
+        },
+        Command::Validate { schema_path, file_path, fix } => {
+            if fix != FixMode::Off {
+                let contents = fs::read_to_string(&file_path).unwrap();
+                let (fixed, applied) = apply_safe_fixes(&contents);
+                if fix == FixMode::DryRun {
+                    print!("{}", unified_diff(&contents, &fixed, &file_path));
+                    return;
+                }
+
+                for err in &applied {
+                    eprintln!("fixed {}", err);
+                }
+                if !applied.is_empty() {
+                    fs::write(&file_path, fixed).unwrap();
+                }
+            }
+
+            let (value, positions) = parse_document(&file_path, output);
+
+            if let Some(schema_path) = schema_path {
+                let schema = Schema::new(parse_file(&schema_path, output)).unwrap_or_else(|err| {
+                    println!("Invalid schema: {}", err);
+                    process::exit(1);
+                });
+
+                let violations = schema.validate(&value);
+                for violation in &violations {
+                    let pos = positions.get(&violation.instance_path)
+                        .copied()
+                        .unwrap_or(Position { line: 0, column: 0 });
+                    println!("{}:{}:{}: {}: {} (schema path: #{})", file_path, pos.line, pos.column,
+                             violation.instance_path, violation.message, violation.schema_path);
+                }
+
+                if !violations.is_empty() {
+                    println!("File {} has {} schema violation(s)", file_path, violations.len());
+                    process::exit(1);
+                }
+            }
+
+            looks_good(&file_path, output);
+        },
+        Command::InferSchema { file_paths } => {
+            let samples: Vec<Value> = file_paths.iter().map(|file_path| parse_file(file_path, output)).collect();
+            println!("{}", Serializer::new().serialize(&infer_schema(&samples)));
+        },
+        Command::Set { file_path, pointer, value } => {
+            let pointer = Pointer::parse(&pointer).unwrap_or_else(|err| {
+                println!("Invalid path: {}", err);
+                process::exit(1);
+            });
+            let value = Parser::checked(Box::new(TokenIter::new(&value).checked())).try_parse().unwrap_or_else(|err| {
+                println!("Invalid value: {}", err);
+                process::exit(1);
+            });
+            let mut doc = Document::try_parse(&fs::read_to_string(&file_path).unwrap())
+                .unwrap_or_else(|err| report(&file_path, &err, output));
+
+            doc.set(&pointer, &value).unwrap_or_else(|err| {
+                println!("Set failed: {}", err);
+                process::exit(1);
+            });
+            fs::write(&file_path, doc.to_string()).unwrap();
+        },
+        Command::Minify { file_path, in_place } => {
+            let contents = match &file_path {
+                Some(file_path) => fs::read_to_string(file_path).unwrap(),
+                None => io::read_to_string(io::stdin()).unwrap()
+            };
+            let minified = minify(&contents)
+                .unwrap_or_else(|err| report(file_path.as_deref().unwrap_or("stdin"), &err, output));
+
+            match file_path {
+                Some(file_path) if in_place => fs::write(file_path, minified).unwrap(),
+                _ => println!("{}", minified)
+            }
+        },
+        Command::Repair { file_path, in_place } => {
+            let contents = match &file_path {
+                Some(file_path) => fs::read_to_string(file_path).unwrap(),
+                None => io::read_to_string(io::stdin()).unwrap()
+            };
+            let (value, repairs) = repair(&contents);
+            let repaired = Serializer::new().serialize(&value);
+
+            // Repairs go to stderr so that stdout stays valid JSON
+            for applied in &repairs {
+                eprintln!("repaired {}", applied);
+            }
+            match file_path {
+                Some(file_path) if in_place => fs::write(file_path, repaired + "\n").unwrap(),
+                _ => println!("{}", repaired)
+            }
+        },
+        Command::Hash { algo, file_paths } => {
+            for file_path in &file_paths {
+                let hash = digest(&parse_file(file_path, output), &algo).unwrap_or_else(|err| {
+                    println!("Hash failed: {}", err);
+                    process::exit(1);
+                });
+                println!("{}  {}", hash, file_path);
+            }
+        },
+        Command::Cat { file_path, html } => {
+            let contents = match &file_path {
+                Some(file_path) => fs::read_to_string(file_path).unwrap(),
+                None => io::read_to_string(io::stdin()).unwrap()
+            };
+            let name = file_path.as_deref().unwrap_or("stdin");
+            Parser::checked(Box::new(TokenIter::new(&contents).checked())).try_validate()
+                .unwrap_or_else(|err| report(name, &err, output));
+
+            if html {
+                print!("{}", jsorry::highlight::html(&contents, name));
+            } else if io::stdout().is_terminal() {
+                println!("{}", jsorry::highlight::ansi(&contents));
+            } else {
+                let pieces = jsorry::highlight::highlight(&contents);
+                println!("{}", pieces.into_iter().map(|(_, text)| text).collect::<String>());
+            }
+        },
+        Command::Explore { file_path } => {
+            let contents = match &file_path {
+                Some(file_path) => fs::read_to_string(file_path).unwrap(),
+                None => io::read_to_string(io::stdin()).unwrap()
+            };
+            let name = file_path.as_deref().unwrap_or("stdin");
+            let value = Parser::checked(Box::new(TokenIter::new(&contents).checked())).try_parse()
+                .unwrap_or_else(|err| report(name, &err, output));
+
+            if let Err(err) = jsorry::explore::run(&value) {
+                println!("Could not use the terminal: {}", err);
+                process::exit(1);
+            }
+        },
+        Command::Stats { file_path, json } => {
+            let contents = match &file_path {
+                Some(file_path) => fs::read_to_string(file_path).unwrap(),
+                None => io::read_to_string(io::stdin()).unwrap()
+            };
+            let stats = jsorry::stats::stats(&contents)
+                .unwrap_or_else(|err| report(file_path.as_deref().unwrap_or("stdin"), &err, output));
+
+            if json {
+                println!("{}", Serializer::new().serialize(&stats.to_value()));
+            } else {
+                print!("{}", stats.report(10));
+            }
+        },
+        Command::CheckAll { paths, options } => {
+            let files = collect_files(&paths, &options).unwrap_or_else(|err| {
+                println!("Could not collect files: {}", err);
+                process::exit(1);
+            });
+            let report = check_files(&files, &options);
+
+            match output {
+                OutputFormat::Text => {
+                    for diagnostic in &report.diagnostics {
+                        println!("File {} is not valid JSON ({}): {} at {} | line {}, column {}", diagnostic.file,
+                                 diagnostic.code, diagnostic.message, diagnostic.path, diagnostic.line, diagnostic.column);
+                        if let Some(hint) = &diagnostic.hint {
+                            println!("  hint: {}", hint);
+                        }
+                    }
+                    for (file, reason) in &report.unreadable {
+                        println!("Could not read {}: {}", file, reason);
+                    }
+                    println!("Checked {} files and {} NDJSON lines, {} invalid", report.files, report.lines, report.invalid_files);
+                },
+                OutputFormat::Json => println!("{}", Serializer::new().serialize(&diagnostic::to_json(&report.diagnostics))),
+                OutputFormat::Sarif => println!("{}", Serializer::new().serialize(&diagnostic::to_sarif(&report.diagnostics))),
+                OutputFormat::Github => for diagnostic in &report.diagnostics {
+                    println!("{}", diagnostic.github());
+                }
+            }
+            if output != OutputFormat::Text {
+                for (file, reason) in &report.unreadable {
+                    eprintln!("Could not read {}: {}", file, reason);
+                }
+            }
+            if !report.is_ok() {
+                process::exit(1);
+            }
+        },
+        Command::Lsp => {
+            jsorry::lsp::run(io::stdin().lock(), io::stdout().lock()).unwrap_or_else(|err| {
+                eprintln!("Language server stopped: {}", err);
+                process::exit(1);
+            });
+        },
+        Command::Explain { code: Some(code) } => {
+            println!("{}: {}\n\n{}", code, code.title(), code.explanation());
+        },
+        Command::Explain { code: None } => {
+            for code in ErrorCode::ALL {
+                println!("{}  {}", code, code.title());
+            }
+        },
+        Command::Convert { file_path, from, to, pointer, csv_strings } => {
+            let from = from.unwrap_or_else(|| Format::from_path(&file_path));
+            let contents = fs::read(&file_path).unwrap();
+            let read = match from {
+                Format::Csv if csv_strings => std::str::from_utf8(&contents).map_err(|err| err.to_string()).and_then(from_csv_strings),
+                _ => from.read(&contents)
+            };
+            let mut value = read.unwrap_or_else(|err| {
+                println!("Could not read {}: {}", file_path, err);
+                process::exit(1);
+            });
+            if let Some(pointer) = pointer {
+                let pointer = Pointer::parse(&pointer).unwrap_or_else(|err| {
+                    println!("Invalid path: {}", err);
+                    process::exit(1);
+                });
+                value = pointer.resolve(&value).cloned().unwrap_or_else(|| {
+                    println!("Nothing found at {}", pointer);
+                    process::exit(1);
+                });
+            }
+
+            let output = to.write(&value).unwrap_or_else(|err| {
+                println!("Convert failed: {}", err);
+                process::exit(1);
+            });
+            let mut stdout = io::stdout();
+            stdout.write_all(&output).unwrap();
+            if !to.is_binary() && !output.ends_with(b"\n") {
+                stdout.write_all(b"\n").unwrap();
+            }
+        },
+        Command::Merge { file_paths } => {
+            let mut merged = parse_file(&file_paths[0], output);
+            for file_path in &file_paths[1..] {
+                merged = merge_patch(&merged, &parse_file(file_path, output));
+            }
+            println!("{}", Serializer::new().serialize(&merged));
+        }
+    }
+}
+
+fn parse_file(file_path: &str, output: OutputFormat) -> Value {
+    let contents = fs::read_to_string(file_path).unwrap();
+    let tokens = TokenIter::new(&contents).checked();
+    let value = Parser::checked(Box::new(tokens)).try_parse();
+    value.unwrap_or_else(|err| report(file_path, &err, output))
+}
+
+// Also returns where each value starts, which costs a map entry per value
+fn parse_document(file_path: &str, output: OutputFormat) -> (Value, HashMap<Path, Position>) {
+    let contents = fs::read_to_string(file_path).unwrap();
+    let tokens = TokenIter::new(&contents).checked();
+    let mut parser = Parser::checked(Box::new(tokens)).with_positions();
+    let value = parser.try_parse().unwrap_or_else(|err| report(file_path, &err, output));
+    (value, parser.positions().clone())
+}
+
+fn report(file_path: &str, err: &ParseError, output: OutputFormat) -> ! {
+    let diagnostics = [Diagnostic::new(file_path, err)];
+    match output {
+        OutputFormat::Text => {
+            println!("File {} is not valid JSON ({}): {}", file_path, err.code, err);
+            if let Some(hint) = &err.hint {
+                println!("  hint: {}", hint);
+            }
+        },
+        OutputFormat::Json => println!("{}", Serializer::new().serialize(&diagnostic::to_json(&diagnostics))),
+        OutputFormat::Sarif => println!("{}", Serializer::new().serialize(&diagnostic::to_sarif(&diagnostics))),
+        OutputFormat::Github => println!("{}", diagnostics[0].github())
+    }
+    process::exit(1);
 }
 
+// Machine readable formats report a clean file as no diagnostics at all
+fn looks_good(file_path: &str, output: OutputFormat) {
+    match output {
+        OutputFormat::Text => println!("File {} looks good 👍", file_path),
+        OutputFormat::Json => println!("{}", Serializer::new().serialize(&diagnostic::to_json(&[]))),
+        OutputFormat::Sarif => println!("{}", Serializer::new().serialize(&diagnostic::to_sarif(&[]))),
+        OutputFormat::Github => {}
+    }
+}
+
+enum DiffFormat {
+    Text,
+    Patch,
+    MergePatch
+}
+
+// How parse errors are reported, for people or for CI tooling
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+    Github
+}
+
+#[derive(PartialEq)]
+enum FixMode {
+    Off,
+    InPlace,
+    DryRun
+}
+
+enum Command {
+    Check { file_path: String },
+    // Files and directory trees, checked in parallel
+    CheckAll { paths: Vec<PathBuf>, options: BatchOptions },
+    Query { query: String, file_path: String },
+    Jq { filter: String, file_path: String },
+    Patch { patch_path: String, file_path: String },
+    Diff { from_path: String, to_path: String, format: DiffFormat, options: DiffOptions },
+    Merge { file_paths: Vec<String> },
+    // Safe fixes are applied before validating
+    Validate { schema_path: Option<String>, file_path: String, fix: FixMode },
+    InferSchema { file_paths: Vec<String> },
+    Set { file_path: String, pointer: String, value: String },
+    // Reads stdin without a file
+    Minify { file_path: Option<String>, in_place: bool },
+    Repair { file_path: Option<String>, in_place: bool },
+    Hash { algo: String, file_paths: Vec<String> },
+    // Without `from` the format is guessed from the file extension
+    Convert { file_path: String, from: Option<Format>, to: Format, pointer: Option<String>, csv_strings: bool },
+    // Lists every code without one
+    Explain { code: Option<ErrorCode> },
+    // Talks the Language Server Protocol on stdin and stdout
+    Lsp,
+    // Colors are only used on a terminal
+    Cat { file_path: Option<String>, html: bool },
+    // Keys are read from the terminal, so the document may come from stdin
+    Explore { file_path: Option<String> },
+    Stats { file_path: Option<String>, json: bool }
+}
+
+// Options that take a value, which may be passed as the following argument
+const VALUE_OPTIONS: [&str; 10] = ["format", "schema", "algo", "from", "to", "pointer", "output-format", "include", "exclude", "jobs"];
+
 struct Config {
-    file_path: String
+    command: Command,
+    output: OutputFormat
 }
 
 impl Config {
@@ -31,8 +469,239 @@ impl Config {
             return Err("not enough arguments");
         }
 
-        let file_path = args[1].clone();
+        // Options look like `--name=value` or `--name value` and may appear
+        // anywhere after the subcommand, flags are just `--name`. Checking a
+        // file takes no subcommand, so options may come first there.
+        let subcommand = Some(args[1].as_str()).filter(|arg| !arg.starts_with("--"));
+        let mut options: Vec<(&str, Option<&str>)> = Vec::new();
+        let mut positional: Vec<&String> = Vec::new();
+        let mut rest = args[if subcommand.is_some() { 2 } else { 1 }..].iter();
+        while let Some(arg) = rest.next() {
+            match arg.strip_prefix("--") {
+                Some(option) => match option.split_once('=') {
+                    Some((name, value)) => options.push((name, Some(value))),
+                    None if VALUE_OPTIONS.contains(&option) => match rest.next() {
+                        Some(value) => options.push((option, Some(value))),
+                        None => return Err("missing value for option")
+                    },
+                    None => options.push((option, None))
+                },
+                None => positional.push(arg)
+            }
+        }
+        let option = |name: &str| options.iter()
+            .find(|(option, _)| *option == name)
+            .and_then(|(_, value)| *value);
+        // Options that may be repeated
+        let values = |name: &str| options.iter()
+            .filter(|(option, _)| *option == name)
+            .filter_map(|(_, value)| *value)
+            .collect::<Vec<_>>();
+        let flag = |name: &str| options.iter().any(|(option, value)| *option == name && value.is_none());
+
+        let output = match option("output-format") {
+            None | Some("text") => OutputFormat::Text,
+            Some("json") => OutputFormat::Json,
+            Some("sarif") => OutputFormat::Sarif,
+            Some("github") => OutputFormat::Github,
+            Some(_) => return Err("output format must be one of: text, json, sarif, github")
+        };
+
+        let command = match subcommand.unwrap_or_default() {
+            "query" => match positional.as_slice() {
+                [query, file_path] => Command::Query { query: query.to_string(), file_path: file_path.to_string() },
+                _ => return Err("usage: jsorry query <jsonpath> <file>")
+            },
+            "jq" => match positional.as_slice() {
+                [filter, file_path] => Command::Jq { filter: filter.to_string(), file_path: file_path.to_string() },
+                _ => return Err("usage: jsorry jq <filter> <file>")
+            },
+            "patch" => match positional.as_slice() {
+                [patch_path, file_path] => Command::Patch {
+                    patch_path: patch_path.to_string(),
+                    file_path: file_path.to_string()
+                },
+                _ => return Err("usage: jsorry patch <patch-file> <file>")
+            },
+            "diff" => {
+                let format = match option("format") {
+                    None | Some("text") => DiffFormat::Text,
+                    Some("patch") => DiffFormat::Patch,
+                    Some("merge-patch") => DiffFormat::MergePatch,
+                    Some(_) => return Err("diff format must be one of: text, patch, merge-patch")
+                };
+                let options = DiffOptions { ignore_array_order: flag("ignore-array-order") };
+
+                match positional.as_slice() {
+                    [from_path, to_path] => Command::Diff {
+                        from_path: from_path.to_string(),
+                        to_path: to_path.to_string(),
+                        format,
+                        options
+                    },
+                    _ => return Err("usage: jsorry diff [--format=text|patch|merge-patch] [--ignore-array-order] <from-file> <to-file>")
+                }
+            },
+            "validate" => {
+                let fix = match (flag("fix"), flag("fix-dry-run")) {
+                    (false, false) => FixMode::Off,
+                    (true, false) => FixMode::InPlace,
+                    (false, true) => FixMode::DryRun,
+                    (true, true) => return Err("--fix and --fix-dry-run can not be combined")
+                };
+
+                // Schema violations have no error code or span, so they are
+                // only reported as text
+                if option("schema").is_some() && output != OutputFormat::Text {
+                    return Err("--output-format can not be combined with --schema");
+                }
+
+                match positional.as_slice() {
+                    [file_path] => Command::Validate {
+                        schema_path: option("schema").map(|path| path.to_string()),
+                        file_path: file_path.to_string(),
+                        fix
+                    },
+                    _ => return Err("usage: jsorry validate [--fix | --fix-dry-run] [--schema <schema-file>] <file>")
+                }
+            },
+            "infer-schema" => {
+                if positional.is_empty() {
+                    return Err("usage: jsorry infer-schema <file>...");
+                }
+
+                Command::InferSchema { file_paths: positional.iter().map(|arg| arg.to_string()).collect() }
+            },
+            "set" => match positional.as_slice() {
+                [file_path, pointer, value] => Command::Set {
+                    file_path: file_path.to_string(),
+                    pointer: pointer.to_string(),
+                    value: value.to_string()
+                },
+                _ => return Err("usage: jsorry set <file> <json-pointer> <json-value>")
+            },
+            "minify" => {
+                let in_place = flag("in-place");
+                let file_path = match positional.as_slice() {
+                    [] => None,
+                    [file_path] if file_path.as_str() == "-" => None,
+                    [file_path] => Some(file_path.to_string()),
+                    _ => return Err("usage: jsorry minify [--in-place] [<file> | -]")
+                };
+                if in_place && file_path.is_none() {
+                    return Err("--in-place needs a file to rewrite");
+                }
+
+                Command::Minify { file_path, in_place }
+            },
+            "repair" => {
+                let in_place = flag("in-place");
+                let file_path = match positional.as_slice() {
+                    [] => None,
+                    [file_path] if file_path.as_str() == "-" => None,
+                    [file_path] => Some(file_path.to_string()),
+                    _ => return Err("usage: jsorry repair [--in-place] [<file> | -]")
+                };
+                if in_place && file_path.is_none() {
+                    return Err("--in-place needs a file to rewrite");
+                }
+
+                Command::Repair { file_path, in_place }
+            },
+            "hash" => {
+                if positional.is_empty() {
+                    return Err("usage: jsorry hash [--algo sha256|sha384|sha512] <file>...");
+                }
+
+                Command::Hash {
+                    algo: option("algo").unwrap_or("sha256").to_string(),
+                    file_paths: positional.iter().map(|arg| arg.to_string()).collect()
+                }
+            },
+            "lsp" => Command::Lsp,
+            "check" => {
+                let usage = "usage: jsorry check [--include <glob>]... [--exclude <glob>]... [--ndjson] [--jobs <n>] <path>...";
+                if positional.is_empty() {
+                    return Err(usage);
+                }
+                let patterns = |globs: Vec<&str>| globs.into_iter().map(|glob| Pattern::parse(glob).ok_or(usage)).collect::<Result<Vec<_>, _>>();
+
+                let mut options = BatchOptions::default();
+                let include = patterns(values("include"))?;
+                if !include.is_empty() {
+                    options.include = include;
+                }
+                options.exclude = patterns(values("exclude"))?;
+                options.ndjson = flag("ndjson");
+                if let Some(jobs) = option("jobs") {
+                    options.jobs = jobs.parse().ok().filter(|jobs| *jobs > 0).ok_or(usage)?;
+                }
+
+                Command::CheckAll { paths: positional.iter().map(PathBuf::from).collect(), options }
+            },
+            "stats" => {
+                let file_path = match positional.as_slice() {
+                    [] => None,
+                    [file_path] if file_path.as_str() == "-" => None,
+                    [file_path] => Some(file_path.to_string()),
+                    _ => return Err("usage: jsorry stats [--json] [<file> | -]")
+                };
+
+                Command::Stats { file_path, json: flag("json") }
+            },
+            "explore" => match positional.as_slice() {
+                [] => Command::Explore { file_path: None },
+                [file_path] if file_path.as_str() == "-" => Command::Explore { file_path: None },
+                [file_path] => Command::Explore { file_path: Some(file_path.to_string()) },
+                _ => return Err("usage: jsorry explore [<file> | -]")
+            },
+            "cat" => {
+                let file_path = match positional.as_slice() {
+                    [] => None,
+                    [file_path] if file_path.as_str() == "-" => None,
+                    [file_path] => Some(file_path.to_string()),
+                    _ => return Err("usage: jsorry cat [--html] [<file> | -]")
+                };
+
+                Command::Cat { file_path, html: flag("html") }
+            },
+            "explain" => match positional.as_slice() {
+                [] => Command::Explain { code: None },
+                [code] => match ErrorCode::parse(code) {
+                    Some(code) => Command::Explain { code: Some(code) },
+                    None => return Err("unknown error code, run jsorry explain to list them all")
+                },
+                _ => return Err("usage: jsorry explain [<code>]")
+            },
+            "convert" => {
+                let usage = "usage: jsorry convert --to <format> [--from <format>] [--pointer <json-pointer>] [--csv-strings] <file>, formats: json, yaml, toml, csv, cbor, msgpack";
+                let format = |name: Option<&str>| name.map(|name| Format::parse(name).map_err(|_| usage)).transpose();
+
+                match (positional.as_slice(), format(option("to"))?) {
+                    ([file_path], Some(to)) => Command::Convert {
+                        file_path: file_path.to_string(),
+                        from: format(option("from"))?,
+                        to,
+                        pointer: option("pointer").map(|pointer| pointer.to_string()),
+                        csv_strings: flag("csv-strings")
+                    },
+                    _ => return Err(usage)
+                }
+            },
+            "merge" => {
+                if positional.len() < 2 {
+                    return Err("usage: jsorry merge <base-file> <override-file>...");
+                }
+
+                Command::Merge { file_paths: positional.iter().map(|arg| arg.to_string()).collect() }
+            },
+            _ => match (subcommand, positional.as_slice()) {
+                (Some(file_path), []) => Command::Check { file_path: file_path.to_string() },
+                (None, [file_path]) => Command::Check { file_path: file_path.to_string() },
+                _ => return Err("usage: jsorry [--output-format text|json|sarif|github] <file>")
+            }
+        };
 
-        Ok(Config { file_path })
+        Ok(Config { command, output })
     }
 }
@@ -0,0 +1,54 @@
+use crate::value::Value;
+
+/// Applies a JSON Merge Patch (RFC 7396) to `target`.
+pub fn merge_patch(target: &Value, patch: &Value) -> Value {
+    let members = match patch {
+        Value::Object(members) => members,
+        _ => return patch.clone()
+    };
+
+    let mut result = match target {
+        Value::Object(_) => target.clone(),
+        _ => Value::Object(Vec::new())
+    };
+
+    for (key, val) in members {
+        if let Value::Null = val {
+            result.remove(key);
+        } else {
+            let merged = merge_patch(result.get(key).unwrap_or(&Value::Null), val);
+            result.insert(key, merged);
+        }
+    }
+
+    result
+}
+
+/// Generates a merge patch turning `from` into `to`. Merge patches can not
+/// set a member to null, so such changes come out as removals.
+pub fn merge_diff(from: &Value, to: &Value) -> Value {
+    match (from, to) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut members = Vec::new();
+
+            for (key, _) in old {
+                if to.get(key).is_none() {
+                    members.push((key.clone(), Value::Null));
+                }
+            }
+            for (key, val) in new {
+                match from.get(key) {
+                    Some(old_val) if old_val == val => {},
+                    Some(old_val) => members.push((key.clone(), merge_diff(old_val, val))),
+                    None => members.push((key.clone(), val.clone()))
+                }
+            }
+
+            Value::Object(members)
+        },
+        _ => to.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests;
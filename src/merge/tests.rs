@@ -0,0 +1,65 @@
+use crate::lexer::TokenIter;
+use crate::merge::{merge_diff, merge_patch};
+use crate::parser::Parser;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+#[test]
+fn test_rfc_examples() {
+    let table = [
+        ("{\"a\":\"b\"}", "{\"a\":\"c\"}", "{\"a\":\"c\"}"),
+        ("{\"a\":\"b\"}", "{\"b\":\"c\"}", "{\"a\":\"b\",\"b\":\"c\"}"),
+        ("{\"a\":\"b\"}", "{\"a\":null}", "{}"),
+        ("{\"a\":\"b\",\"b\":\"c\"}", "{\"a\":null}", "{\"b\":\"c\"}"),
+        ("{\"a\":[\"b\"]}", "{\"a\":\"c\"}", "{\"a\":\"c\"}"),
+        ("{\"a\":\"c\"}", "{\"a\":[\"b\"]}", "{\"a\":[\"b\"]}"),
+        ("{\"a\":{\"b\":\"c\"}}", "{\"a\":{\"b\":\"d\",\"c\":null}}", "{\"a\":{\"b\":\"d\"}}"),
+        ("{\"a\":[{\"b\":\"c\"}]}", "{\"a\":[1]}", "{\"a\":[1]}"),
+        ("[\"a\",\"b\"]", "[\"c\",\"d\"]", "[\"c\",\"d\"]"),
+        ("{\"a\":\"b\"}", "[\"c\"]", "[\"c\"]"),
+        ("{\"a\":\"foo\"}", "null", "null"),
+        ("{\"a\":\"foo\"}", "\"bar\"", "\"bar\""),
+        ("{\"e\":null}", "{\"a\":1}", "{\"e\":null,\"a\":1}"),
+        ("[1,2]", "{\"a\":\"b\",\"c\":null}", "{\"a\":\"b\"}"),
+        ("{}", "{\"a\":{\"bb\":{\"ccc\":null}}}", "{\"a\":{\"bb\":{}}}"),
+    ];
+
+    for (target, patch, expected) in table.iter() {
+        assert_eq!(merge_patch(&parse(target), &parse(patch)), parse(expected), "patch {}", patch);
+    }
+}
+
+#[test]
+fn test_layered_overrides_keep_order() {
+    let base = parse("{\"name\": \"api\", \"db\": {\"host\": \"localhost\", \"port\": 5432}, \"debug\": true}");
+    let env = parse("{\"db\": {\"host\": \"db.prod\"}, \"debug\": null}");
+
+    let merged = merge_patch(&base, &env);
+
+    assert_eq!(merged, parse("{\"name\": \"api\", \"db\": {\"host\": \"db.prod\", \"port\": 5432}}"));
+    match merged {
+        Value::Object(members) => assert_eq!(members[0].0, "name"),
+        _ => panic!("expected an object")
+    }
+}
+
+#[test]
+fn test_diff_round_trip() {
+    let table = [
+        ("{\"a\": 1, \"b\": {\"c\": [1], \"d\": 2}}", "{\"a\": 1, \"b\": {\"c\": [1, 2]}, \"e\": 3}",
+         "{\"b\": {\"d\": null, \"c\": [1, 2]}, \"e\": 3}"),
+        ("{\"a\": 1}", "[1]", "[1]"),
+        ("{\"a\": 1}", "{\"a\": 1}", "{}"),
+    ];
+
+    for (from, to, expected) in table.iter() {
+        let (from, to) = (parse(from), parse(to));
+        let patch = merge_diff(&from, &to);
+        assert_eq!(patch, parse(expected));
+        assert_eq!(merge_patch(&from, &patch), to);
+    }
+}
@@ -0,0 +1,25 @@
+use std::cell::RefCell;
+
+use crate::lexer::TokenIter;
+use crate::parser::{ParseError, Parser};
+
+/// Strips all whitespace from a document, streaming its tokens through the
+/// parser for validation instead of building a value tree.
+pub fn minify(contents: &str) -> Result<String, ParseError> {
+    let out = RefCell::new(String::with_capacity(contents.len()));
+
+    // The parser still needs the whitespace, or `1 2` would read as `12`
+    let tokens = TokenIter::new(contents).checked().inspect(|token| {
+        if let Ok(token) = token {
+            if !token.kind.is_whitespace() {
+                out.borrow_mut().push_str(&token.text);
+            }
+        }
+    });
+    Parser::checked(Box::new(tokens)).try_validate()?;
+
+    Ok(out.into_inner())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,31 @@
+use crate::lexer::TokenIter;
+use crate::minify::minify;
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+
+#[test]
+fn test_minify() {
+    assert_eq!(minify("{\r\n\t\"a b\" : [ 1 , -2.5e+3 ],\n  \"c\": { }\n}\n").unwrap(), "{\"a b\":[1,-2.5e+3],\"c\":{}}");
+    assert_eq!(minify(" \"  spaces stay  \" ").unwrap(), "\"  spaces stay  \"");
+}
+
+#[test]
+fn test_minify_matches_compact_serializer() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let value = Parser::new(Box::new(TokenIter::new(&contents))).parse();
+
+    assert_eq!(minify(&contents).unwrap(), Serializer::compact().serialize(&value));
+}
+
+#[test]
+fn test_minify_invalid() {
+    let err = minify("[1 2]").unwrap_err();
+    assert!(err.message.starts_with("Expected token"), "{}", err);
+    assert_eq!(err.code.as_str(), "E0008");
+    assert_eq!(minify("[\"open").unwrap_err().code.as_str(), "E0001");
+}
+
+#[test]
+fn test_minify_rejects_comments() {
+    assert!(minify("[1, /* two */ 2]").unwrap_err().message.starts_with("Unexpected token Comment"));
+}
@@ -0,0 +1,253 @@
+use crate::path::Path;
+use crate::value::{Number, Value};
+
+/// Encodes a value as MessagePack. Integers that fit in 64 bits stay
+/// integers, other numbers become the smallest float that holds them exactly,
+/// so integers beyond 64 bits lose precision as doubles. Numbers too large
+/// for a double, such as `1e400`, are an error.
+pub fn encode(value: &Value) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    write_value(&mut out, value, &Path::root())?;
+    Ok(out)
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value, path: &Path) -> Result<(), String> {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(val) => out.push(if *val { 0xc3 } else { 0xc2 }),
+        Value::Number(num) => write_number(out, num, path)?,
+        Value::String(val) => write_str(out, val),
+        Value::Array(items) => {
+            write_len(out, items.len(), 0x90, [0xdc, 0xdd]);
+            for (idx, item) in items.iter().enumerate() {
+                write_value(out, item, &path.index(idx))?;
+            }
+        },
+        Value::Object(members) => {
+            write_len(out, members.len(), 0x80, [0xde, 0xdf]);
+            for (key, val) in members {
+                write_str(out, key);
+                write_value(out, val, &path.key(key))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Arrays and maps up to 15 entries fit in the marker itself
+fn write_len(out: &mut Vec<u8>, len: usize, fix: u8, [marker16, marker32]: [u8; 2]) {
+    if len < 16 {
+        out.push(fix | len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(marker16);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(marker32);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, val: &str) {
+    let len = val.len();
+    if len < 32 {
+        out.push(0xa0 | len as u8);
+    } else if len <= u8::MAX as usize {
+        out.push(0xd9);
+        out.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(0xda);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdb);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(val.as_bytes());
+}
+
+fn write_number(out: &mut Vec<u8>, num: &Number, path: &Path) -> Result<(), String> {
+    if num.is_integer() {
+        if let Some(val) = num.as_i64() {
+            write_int(out, val);
+            return Ok(());
+        }
+        if let Ok(val) = num.as_str().parse::<u64>() {
+            out.push(0xcf);
+            out.extend_from_slice(&val.to_be_bytes());
+            return Ok(());
+        }
+    }
+
+    let val = num.as_f64();
+    if !val.is_finite() {
+        return Err(format!("{}: {} is too large for a MessagePack float", path, num));
+    }
+    if val as f32 as f64 == val {
+        out.push(0xca);
+        out.extend_from_slice(&(val as f32).to_be_bytes());
+    } else {
+        out.push(0xcb);
+        out.extend_from_slice(&val.to_be_bytes());
+    }
+    Ok(())
+}
+
+fn write_int(out: &mut Vec<u8>, val: i64) {
+    match val {
+        -32..=0x7f => out.push(val as u8),
+        0x80..=0xff => out.extend_from_slice(&[0xcc, val as u8]),
+        0x100..=0xffff => {
+            out.push(0xcd);
+            out.extend_from_slice(&(val as u16).to_be_bytes());
+        },
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xce);
+            out.extend_from_slice(&(val as u32).to_be_bytes());
+        },
+        0x1_0000_0000.. => {
+            out.push(0xcf);
+            out.extend_from_slice(&(val as u64).to_be_bytes());
+        },
+        -0x80..=-33 => out.extend_from_slice(&[0xd0, val as u8]),
+        -0x8000..=-0x81 => {
+            out.push(0xd1);
+            out.extend_from_slice(&(val as i16).to_be_bytes());
+        },
+        -0x8000_0000..=-0x8001 => {
+            out.push(0xd2);
+            out.extend_from_slice(&(val as i32).to_be_bytes());
+        },
+        _ => {
+            out.push(0xd3);
+            out.extend_from_slice(&val.to_be_bytes());
+        }
+    }
+}
+
+// Deeper input is rejected before it can overflow the stack
+const MAX_DEPTH: usize = 256;
+
+/// Decodes a single MessagePack object into a value. Binary data becomes an
+/// array of numbers, extension types are not supported.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut decoder = Decoder { bytes, pos: 0, depth: 0 };
+    let value = decoder.value()?;
+    if decoder.pos < bytes.len() {
+        return Err(format!("unexpected data after the end of the object at byte {}", decoder.pos));
+    }
+    Ok(value)
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    // Values being read, arrays and maps nest
+    depth: usize
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.pos < len {
+            return Err(format!("unexpected end of input at byte {}", self.bytes.len()));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn uint(&mut self, len: usize) -> Result<u64, String> {
+        Ok(self.take(len)?.iter().fold(0, |acc, byte| acc << 8 | *byte as u64))
+    }
+
+    // Reads a big-endian two's complement integer, sign extending it
+    fn int(&mut self, len: usize) -> Result<i64, String> {
+        let shift = 64 - 8 * len as u32;
+        Ok(((self.uint(len)? << shift) as i64) >> shift)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nesting too deep at byte {}", self.pos));
+        }
+        self.depth += 1;
+        let value = self.read_value();
+        self.depth -= 1;
+        value
+    }
+
+    fn read_value(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        let marker = self.take(1)?[0];
+        let number = |text: String| Value::Number(Number::new(text));
+
+        Ok(match marker {
+            0x00..=0x7f => Value::from(marker as i64),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize)?,
+            0x90..=0x9f => self.array((marker & 0x0f) as usize)?,
+            0xa0..=0xbf => self.str((marker & 0x1f) as usize, start)?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4..=0xc6 => {
+                let len = self.uint(1 << (marker - 0xc4))? as usize;
+                Value::Array(self.take(len)?.iter().map(|byte| Value::from(*byte as i64)).collect())
+            },
+            0xca => float(f32::from_bits(self.uint(4)? as u32) as f64, start)?,
+            0xcb => float(f64::from_bits(self.uint(8)?), start)?,
+            0xcc..=0xcf => number(self.uint(1 << (marker - 0xcc))?.to_string()),
+            0xd0..=0xd3 => number(self.int(1 << (marker - 0xd0))?.to_string()),
+            0xd9..=0xdb => {
+                let len = self.uint(1 << (marker - 0xd9))? as usize;
+                self.str(len, start)?
+            },
+            0xdc | 0xdd => {
+                let len = self.uint(2 << (marker - 0xdc))? as usize;
+                self.array(len)?
+            },
+            0xde | 0xdf => {
+                let len = self.uint(2 << (marker - 0xde))? as usize;
+                self.map(len)?
+            },
+            0xe0..=0xff => Value::from(marker as i8 as i64),
+            0xc7..=0xc9 | 0xd4..=0xd8 => return Err(format!("unsupported extension type at byte {}", start)),
+            _ => return Err(format!("invalid marker 0x{:02x} at byte {}", marker, start))
+        })
+    }
+
+    fn str(&mut self, len: usize, start: usize) -> Result<Value, String> {
+        let bytes = self.take(len)?;
+        let val = std::str::from_utf8(bytes).map_err(|_| format!("invalid UTF-8 in string at byte {}", start))?;
+        Ok(Value::from(val))
+    }
+
+    fn array(&mut self, len: usize) -> Result<Value, String> {
+        (0..len).map(|_| self.value()).collect::<Result<_, _>>().map(Value::Array)
+    }
+
+    fn map(&mut self, len: usize) -> Result<Value, String> {
+        let mut members = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            let start = self.pos;
+            // JSON keys are strings, scalar keys are written out as text
+            let key = match self.value()? {
+                Value::String(key) => key,
+                Value::Number(num) => num.to_string(),
+                Value::Bool(val) => val.to_string(),
+                Value::Null => "null".to_string(),
+                _ => return Err(format!("map keys must be scalars at byte {}", start))
+            };
+            members.push((key, self.value()?));
+        }
+        Ok(Value::Object(members))
+    }
+}
+
+fn float(val: f64, start: usize) -> Result<Value, String> {
+    if val.is_finite() {
+        Ok(Value::Number(Number::new(format!("{:?}", val))))
+    } else {
+        Err(format!("{} can not be represented in JSON at byte {}", val, start))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,64 @@
+use crate::lexer::TokenIter;
+use crate::msgpack::{decode, encode};
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn value(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+fn compact(value: &Value) -> String {
+    Serializer::compact().serialize(value)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Vec<u8> {
+    (0..text.len()).step_by(2).map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_encode() {
+    let cases = [
+        ("0", "00"), ("127", "7f"), ("128", "cc80"), ("65535", "cdffff"), ("65536", "ce00010000"),
+        ("4294967296", "cf0000000100000000"), ("18446744073709551615", "cfffffffffffffffff"),
+        ("-1", "ff"), ("-32", "e0"), ("-33", "d0df"), ("-129", "d1ff7f"), ("-2147483649", "d3ffffffff7fffffff"),
+        ("1.5", "ca3fc00000"), ("1.1", "cb3ff199999999999a"),
+        ("null", "c0"), ("[true, false]", "92c3c2"), ("{\"a\": \"b\"}", "81a161a162")
+    ];
+    for (json, expected) in cases {
+        assert_eq!(hex(&encode(&value(json)).unwrap()), expected, "{}", json);
+    }
+
+    let long = "x".repeat(40);
+    assert_eq!(hex(&encode(&Value::from(long.as_str())).unwrap()[..2]), "d928");
+    assert_eq!(hex(&encode(&Value::Array(vec![Value::Null; 16])).unwrap()[..3]), "dc0010");
+}
+
+#[test]
+fn test_round_trip() {
+    let doc = value(&std::fs::read_to_string("testdata.json").unwrap());
+    assert_eq!(decode(&encode(&doc).unwrap()).unwrap(), doc);
+
+    let numbers = value("[1, 1.0, -0.5, -9223372036854775808, 18446744073709551615]");
+    assert_eq!(compact(&decode(&encode(&numbers).unwrap()).unwrap()), "[1,1.0,-0.5,-9223372036854775808,18446744073709551615]");
+    assert_eq!(compact(&decode(&encode(&value("123456789012345678901234567890")).unwrap()).unwrap()), "1.2345678901234568e29");
+    assert_eq!(encode(&value("{\"a\": [1e400]}")).unwrap_err(), "$.a[0]: 1e400 is too large for a MessagePack float");
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(decode(&unhex("92a3666f6fc403010203")).unwrap(), value("[\"foo\", [1, 2, 3]]"));
+    assert_eq!(decode(&unhex("de0001cc01c3")).unwrap(), value("{\"1\": true}"));
+    assert_eq!(decode(&unhex("d2fffffffe")).unwrap(), value("-2"));
+    assert_eq!(decode(&unhex("92")).unwrap_err(), "unexpected end of input at byte 1");
+    assert_eq!(decode(&unhex("d6ff00000000")).unwrap_err(), "unsupported extension type at byte 0");
+    assert_eq!(decode(&unhex("c1")).unwrap_err(), "invalid marker 0xc1 at byte 0");
+    let mut nested = vec![0x91; 255];
+    nested.push(0xc0);
+    assert!(decode(&nested).is_ok());
+    assert_eq!(decode(&[0x91; 200_000]).unwrap_err(), "nesting too deep at byte 256");
+}
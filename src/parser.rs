@@ -1,38 +1,231 @@
-use crate::lexer::Token;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::code::ErrorCode;
+use crate::hint::{self, Fix, Hint};
+use crate::lexer::{LexError, Span, Token};
 use crate::lexer::JsonKind;
+use crate::path::{Path, PathElement};
+use crate::value::{Number, Value};
+
+/// Where a value starts in the source document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize
+}
+
+/// A syntax error, located both in the text and by the path of the value
+/// being parsed when it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub path: Path,
+    pub span: Span,
+    pub hint: Option<Box<Hint>>
+}
+
+impl ParseError {
+    /// The edit suggested for this error, if there is one.
+    pub fn fix(&self) -> Option<&Fix> {
+        self.hint.as_ref()?.fix.as_ref()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {} | line {}, column {}", self.message, self.path, self.line, self.column)
+    }
+}
 
 pub struct Parser<'a> {
-    lexer: Box<dyn Iterator<Item=Token> + 'a>,
+    lexer: Box<dyn Iterator<Item=Result<Token, LexError>> + 'a>,
     curr: Option<Token>,
-    peek: Option<Token>
+    peek: Option<Result<Token, LexError>>,
+    // A lexing failure stops the token stream, reported once the parser reaches it
+    failed: Option<LexError>,
+    // Just past the last token consumed, where running out of input is reported
+    end: Position,
+    // Byte offset of the current token, counted over every token seen
+    offset: usize,
+    // The last token consumed that was not whitespace, which hints refer back to
+    prev: Option<(JsonKind, Span)>,
+    path: Path,
+    positions: HashMap<Path, Position>,
+    // Only recorded when asked for, see `with_positions`
+    track_positions: bool,
+    // Validating alone drops values as soon as they are parsed
+    keep_values: bool
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(lexer: Box<dyn Iterator<Item=Token> + 'a>) -> Parser {
+    pub fn new(lexer: Box<dyn Iterator<Item=Token> + 'a>) -> Parser<'a> {
+        Parser::checked(Box::new(lexer.map(Ok)))
+    }
+
+    /// A parser over a lexer reporting its own failures, see `TokenIter::checked`.
+    pub fn checked(lexer: Box<dyn Iterator<Item=Result<Token, LexError>> + 'a>) -> Parser<'a> {
         let mut tmp = Parser {
             lexer,
             curr: None,
-            peek: None
+            peek: None,
+            failed: None,
+            end: Position { line: 1, column: 1 },
+            offset: 0,
+            prev: None,
+            path: Path::root(),
+            positions: HashMap::new(),
+            track_positions: false,
+            keep_values: true
         };
 
         // Init curr and peek tokens
-        tmp.curr = tmp.lexer.next();
         tmp.peek = tmp.lexer.next();
+        tmp.next_token();
 
         tmp
     }
 
+    /// Records where each value starts, for `positions`.
+    pub fn with_positions(mut self) -> Parser<'a> {
+        self.track_positions = true;
+        self
+    }
+
     pub fn start(&mut self) {
+        self.try_start().unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    /// Checks a run of documents, one after the other.
+    pub fn try_start(&mut self) -> Result<(), ParseError> {
         while self.curr.is_some() {
-            self.element();
+            self.element()?;
         }
+        self.finish()
+    }
+
+    /// Parses a single document into a value tree.
+    pub fn parse(&mut self) -> Value {
+        self.try_parse().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_parse(&mut self) -> Result<Value, ParseError> {
+        let value = self.element()?;
+
+        if let Some(val) = &self.curr {
+            return Err(self.error(ErrorCode::TrailingData, format!("Unexpected token {:#?} after end of document", val.kind)));
+        }
+        self.finish()?;
+
+        Ok(value)
+    }
+
+    /// Checks a single document without building its value tree.
+    pub fn validate(&mut self) {
+        self.try_validate().unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    pub fn try_validate(&mut self) -> Result<(), ParseError> {
+        self.keep_values = false;
+        self.try_parse().map(|_| ())
+    }
+
+    /// Start positions of every value seen so far, keyed by their path.
+    /// Empty unless the parser was created `with_positions`.
+    pub fn positions(&self) -> &HashMap<Path, Position> {
+        &self.positions
     }
 
     fn next_token(&mut self) {
-        self.curr = self.peek.clone();
-        self.peek = self.lexer.next();
+        if let Some(token) = &self.curr {
+            let newlines = token.text.matches('\n').count();
+            self.end = match token.text.rfind('\n') {
+                Some(idx) => Position { line: token.line + newlines, column: token.text[idx + 1..].chars().count() + 1 },
+                None => Position { line: token.line, column: token.column + token.text.chars().count() }
+            };
+
+            let span = self.span();
+            if !token.kind.is_whitespace() {
+                self.prev = Some((token.kind.clone(), span));
+            }
+            self.offset = span.end;
+        }
+
+        self.curr = match self.peek.take() {
+            Some(Ok(token)) => Some(token),
+            Some(Err(err)) => {
+                self.failed = Some(err);
+                None
+            },
+            None => None
+        };
+        if self.failed.is_none() {
+            self.peek = self.lexer.next();
+        }
     }
 
+    fn finish(&self) -> Result<(), ParseError> {
+        match self.failed {
+            Some(_) => Err(self.error(ErrorCode::UnexpectedCharacter, String::new())),
+            None => Ok(())
+        }
+    }
+
+    fn span(&self) -> Span {
+        let len = self.curr.as_ref().map_or(0, |token| token.text.len());
+        Span { start: self.offset, end: self.offset + len }
+    }
+
+    fn error(&self, code: ErrorCode, message: String) -> ParseError {
+        self.error_expecting(code, message, None)
+    }
+
+    // Errors point at the current token, or the lexing failure that cut the
+    // input short, and carry the path of the value being parsed
+    fn error_expecting(&self, code: ErrorCode, message: String, expected: Option<&JsonKind>) -> ParseError {
+        let (code, message, line, column, span) = match (&self.failed, &self.curr) {
+            (Some(err), _) => (err.code, err.message.clone(), err.line, err.column, err.span),
+            (None, Some(token)) => (code, message, token.line, token.column, self.span()),
+            (None, None) => (code, message, self.end.line, self.end.column, self.span())
+        };
+
+        ParseError { code, message, line, column, path: self.path.clone(), span, hint: self.hint(expected).map(Box::new) }
+    }
+
+    // Guesses the mistake from what was expected, what was found instead and
+    // the token before it
+    fn hint(&self, expected: Option<&JsonKind>) -> Option<Hint> {
+        if let Some(err) = &self.failed {
+            return match (expected, &err.hint) {
+                (Some(JsonKind::StringVal), Some(hint)) => Some(hint::unquoted_key(hint)),
+                (_, hint) => hint.clone()
+            };
+        }
+
+        let token = self.curr.as_ref()?;
+        match (&token.kind, &self.prev, expected) {
+            (JsonKind::Comment, _, _) => Some(hint::comment(self.span())),
+            (JsonKind::EndArray | JsonKind::EndObject, Some((JsonKind::ValueSeperator, comma)), _) =>
+                Some(hint::trailing_comma(*comma)),
+            (kind, Some((_, prev)), Some(JsonKind::EndObject)) if starts_value(kind) =>
+                Some(hint::missing_comma(prev.end, "members")),
+            (kind, Some((_, prev)), Some(JsonKind::EndArray)) if starts_value(kind) =>
+                Some(hint::missing_comma(prev.end, "elements")),
+            (_, Some((JsonKind::StringVal, key)), Some(JsonKind::NameSeperator)) =>
+                Some(hint::missing_colon(key.end)),
+            _ => None
+        }
+    }
+
+    fn found(&self) -> String {
+        match &self.curr {
+            Some(val) => format!("{:#?}", val.kind),
+            None => "end of input".to_string()
+        }
+    }
 
     fn is_kind(&self, kind: &JsonKind) -> bool {
         match &self.curr {
@@ -41,150 +234,222 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn illegal_leading_zero(&self) {
+    fn illegal_leading_zero(&self) -> Result<(), ParseError> {
         if self.is_kind(&JsonKind::Zero) && 
             (self.is_next(&JsonKind::Digit) || self.is_next(&JsonKind::Zero)) {
-                panic!("Illegal leading zero found for number | line {}", 
-                       self.curr.as_ref().unwrap().line);
+                let mut err = self.error(ErrorCode::LeadingZero, "Illegal leading zero found for number".to_string());
+                err.hint = Some(Box::new(hint::leading_zero(self.span())));
+                return Err(err);
             }
+        Ok(())
     }
 
     fn is_next(&self, kind: &JsonKind) -> bool {
         match &self.peek {
-            None => false,
-            Some(val) => val.kind == *kind
+            Some(Ok(val)) => val.kind == *kind,
+            _ => false
         }
     }
 
-    fn must_match(&mut self, kind: &JsonKind) {
+    fn must_match(&mut self, kind: &JsonKind) -> Result<Token, ParseError> {
         if !self.is_kind(kind) {
-            panic!("Expected token {:#?} did not match {:#?} | line: {}", 
-                   kind, self.curr.as_ref().unwrap().kind, self.curr.as_ref().unwrap().line);
+            return Err(self.error_expecting(ErrorCode::MismatchedToken, format!("Expected token {:#?} did not match {}", kind, self.found()), Some(kind)));
         }
 
+        let token = self.curr.clone().unwrap();
         self.next_token();
+        Ok(token)
     }
 
-    fn must_match_either(&mut self, kind1: &JsonKind, kind2: &JsonKind) {
+    fn must_match_either(&mut self, kind1: &JsonKind, kind2: &JsonKind) -> Result<Token, ParseError> {
         if !self.is_kind(kind1) && !self.is_kind(kind2) {
-            panic!("Expected token {:#?} did not match {:#?}, or {:#?} | line: {}", 
-                   kind1, kind2, self.curr.as_ref().unwrap().kind, self.curr.as_ref().unwrap().line);
+            return Err(self.error(ErrorCode::MismatchedToken, format!("Expected token {:#?} did not match {:#?}, or {}", 
+                                          kind1, kind2, self.found())));
         }
+
+        let token = self.curr.clone().unwrap();
         self.next_token();
+        Ok(token)
+    }
+
+    // Escapes are checked against the string token, which has already been consumed
+    fn unescape(&self, token: &Token) -> Result<String, ParseError> {
+        decode_escapes(&token.text).map_err(|(code, message)| ParseError {
+            code,
+            message,
+            line: token.line,
+            column: token.column,
+            path: self.path.clone(),
+            span: self.prev.as_ref().map_or(self.span(), |(_, span)| *span),
+            hint: None
+        })
     }
 
-    fn elements(&mut self) {
-        self.element();
+    fn elements(&mut self, items: &mut Vec<Value>) -> Result<(), ParseError> {
+        let mut idx = 0;
+        loop {
+            self.path.push(PathElement::Index(idx));
+            let item = self.element()?;
+            self.path.pop();
 
-        if self.is_kind(&JsonKind::ValueSeperator) {
+            if self.keep_values {
+                items.push(item);
+            }
+            idx += 1;
+
+            if !self.is_kind(&JsonKind::ValueSeperator) {
+                return Ok(());
+            }
             self.next_token();
-            self.elements();
         }
     }
 
-    fn element(&mut self) {
+    fn element(&mut self) -> Result<Value, ParseError> {
 
         self.whitespace();
 
-        if self.is_kind(&JsonKind::BeginObject) {
+        if let (Some(val), true, true) = (&self.curr, self.track_positions, self.keep_values) {
+            self.positions.insert(self.path.clone(), Position { line: val.line, column: val.column });
+        }
+
+        let value = if self.is_kind(&JsonKind::BeginObject) {
 
             self.next_token();
             self.whitespace();
 
+            let mut members = Vec::new();
             if self.is_kind(&JsonKind::EndObject) {
                 self.next_token();
             } else {
-                self.members();
-                self.must_match(&JsonKind::EndObject);
+                self.members(&mut members)?;
+                self.must_match(&JsonKind::EndObject)?;
             }
+            Value::Object(members)
 
         } else if self.is_kind(&JsonKind::BeginArray) {
 
             self.next_token();
             self.whitespace();
 
+            let mut items = Vec::new();
             if self.is_kind(&JsonKind::EndArray) {
                 self.next_token();
             } else {
-                self.elements();
-                self.must_match(&JsonKind::EndArray);
+                self.elements(&mut items)?;
+                self.must_match(&JsonKind::EndArray)?;
             }
+            Value::Array(items)
+
+        } else if self.is_kind(&JsonKind::StringVal) {
 
-        } else if self.is_kind(&JsonKind::StringVal) ||  
-            self.is_kind(&JsonKind::True) ||  
+            let token = self.must_match(&JsonKind::StringVal)?;
+            Value::String(self.unescape(&token)?)
+
+        } else if self.is_kind(&JsonKind::True) ||  
                 self.is_kind(&JsonKind::False) ||
                 self.is_kind(&JsonKind::Null) {
 
+                    let value = match self.curr.as_ref().unwrap().kind {
+                        JsonKind::True => Value::Bool(true),
+                        JsonKind::False => Value::Bool(false),
+                        _ => Value::Null
+                    };
                     self.next_token();
+                    value
 
                 } else if self.is_kind(&JsonKind::Minus) || 
                     self.is_kind(&JsonKind::Digit) || 
                         self.is_kind(&JsonKind::Zero) {
 
-                            self.number();
+                            self.number()?
 
                         } else {
-                            panic!("Unexpected token {:#?} on line {}", 
-                                   self.curr.as_ref().unwrap().kind, self.curr.as_ref().unwrap().line);
-                        }
+                            return Err(self.error(ErrorCode::UnexpectedToken, format!("Unexpected token {}", self.found())));
+                        };
 
                 self.whitespace();
+                Ok(value)
     }
 
-    fn members(&mut self) {
-        self.member();
-        if self.is_kind(&JsonKind::ValueSeperator) {
+    fn members(&mut self, members: &mut Vec<(String, Value)>) -> Result<(), ParseError> {
+        loop {
+            let member = self.member()?;
+            if self.keep_values {
+                members.push(member);
+            }
+
+            if !self.is_kind(&JsonKind::ValueSeperator) {
+                return Ok(());
+            }
             self.next_token();
-            self.members();
         }
     }
 
-    fn member(&mut self) {
+    fn member(&mut self) -> Result<(String, Value), ParseError> {
         self.whitespace();
-        self.must_match(&JsonKind::StringVal);
+        let key = self.must_match(&JsonKind::StringVal)?;
+        let key = self.unescape(&key)?;
+
+        // The key is known from here on, so a missing colon already points at it
+        self.path.push(PathElement::Key(key.clone()));
         self.whitespace();
-        self.must_match(&JsonKind::NameSeperator);
-        self.element();
+        self.must_match(&JsonKind::NameSeperator)?;
+        let value = self.element()?;
+        self.path.pop();
+        Ok((key, value))
     }
 
-    fn number(&mut self) {
-        self.integer();
-        self.fraction();
-        self.exponent();
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let mut text = String::new();
+        self.integer(&mut text)?;
+        self.fraction(&mut text)?;
+        self.exponent(&mut text)?;
+        Ok(Value::Number(Number::new(text)))
     }
 
-    fn integer(&mut self) {
-        self.illegal_leading_zero();
+    fn integer(&mut self, text: &mut String) -> Result<(), ParseError> {
+        self.illegal_leading_zero()?;
 
         if self.is_kind(&JsonKind::Minus) {
-            self.next_token();
-            self.illegal_leading_zero();
-            self.must_match_either(&JsonKind::Digit, &JsonKind::Zero);
+            text.push_str(&self.must_match(&JsonKind::Minus)?.text);
+            self.illegal_leading_zero()?;
+            text.push_str(&self.must_match_either(&JsonKind::Digit, &JsonKind::Zero)?.text);
         } 
 
-        self.digits();
+        self.digits(text);
+        Ok(())
     }
 
-    fn fraction(&mut self) {
+    fn fraction(&mut self, text: &mut String) -> Result<(), ParseError> {
         if self.is_kind(&JsonKind::DecimalPoint) {
-            self.next_token();
-            self.digits();
+            text.push_str(&self.must_match(&JsonKind::DecimalPoint)?.text);
+            // At least one digit, `1.` is not a number
+            text.push_str(&self.must_match_either(&JsonKind::Digit, &JsonKind::Zero)?.text);
+            self.digits(text);
         }
+        Ok(())
     }
 
-    fn exponent(&mut self) {
+    fn exponent(&mut self, text: &mut String) -> Result<(), ParseError> {
         if self.is_kind(&JsonKind::E) {
-            self.next_token();
+            text.push_str(&self.must_match(&JsonKind::E)?.text);
             if self.is_kind(&JsonKind::Plus) || self.is_kind(&JsonKind::Minus) {
+                text.push_str(&self.curr.as_ref().unwrap().text);
                 self.next_token();
             }
+            text.push_str(&self.must_match_either(&JsonKind::Digit, &JsonKind::Zero)?.text);
+            self.digits(text);
         }
+        Ok(())
     }
 
-    fn digits(&mut self) {
+    fn digits(&mut self, text: &mut String) {
         while let Some(val) = &self.curr {
             match val.kind {
-                JsonKind::Digit | JsonKind::Zero => self.next_token(),
+                JsonKind::Digit | JsonKind::Zero => {
+                    text.push_str(&val.text);
+                    self.next_token();
+                },
                 _ => break,
             }
         }
@@ -192,14 +457,78 @@ impl<'a> Parser<'a> {
 
     fn whitespace(&mut self) {
         while let Some(val) = &self.curr {
-            match val.kind { 
-                JsonKind::Space | 
-                    JsonKind::HorizontalTab | 
-                    JsonKind::LineFeed | 
-                    JsonKind::CarriageReturn => self.next_token(),
-                _ => break,
+            if !val.kind.is_whitespace() {
+                break;
             }
+            self.next_token();
+        }
+    }
+}
+
+fn starts_value(kind: &JsonKind) -> bool {
+    matches!(kind, JsonKind::BeginObject | JsonKind::BeginArray | JsonKind::StringVal | JsonKind::True |
+             JsonKind::False | JsonKind::Null | JsonKind::Minus | JsonKind::Digit | JsonKind::Zero)
+}
+
+/// Strips the surrounding quotes from a string token and resolves its escapes.
+pub fn unescape(raw: &str) -> String {
+    decode_escapes(raw).unwrap_or_else(|(_, err)| panic!("{}", err))
+}
+
+/// Like `unescape`, but returns invalid escapes as errors.
+pub(crate) fn decode_escapes(raw: &str) -> Result<String, (ErrorCode, String)> {
+    let inner = raw.strip_prefix('"')
+        .and_then(|val| val.strip_suffix('"'))
+        .unwrap_or(raw);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
         }
+
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some('/') => out.push('/'),
+            Some('b') => out.push('\u{08}'),
+            Some('f') => out.push('\u{0c}'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let high = hex_escape(&mut chars)?;
+                let code = match low_surrogate(chars.as_str()) {
+                    Some(low) if (0xD800..0xDC00).contains(&high) => {
+                        chars.nth(5);
+                        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                    },
+                    // A lone surrogate, whatever follows is decoded on its own
+                    _ => high
+                };
+                out.push(std::char::from_u32(code).unwrap_or('\u{FFFD}'));
+            },
+            Some(other) => return Err((ErrorCode::InvalidEscape, format!("Invalid escape sequence \\{} in string {}", other, raw))),
+            None => return Err((ErrorCode::InvalidEscape, format!("Invalid escape sequence at end of string {}", raw)))
+        }
+    }
+
+    Ok(out)
+}
+
+// The low half of a surrogate pair escaped at the start of `rest`
+fn low_surrogate(rest: &str) -> Option<u32> {
+    let hex = rest.strip_prefix("\\u")?.get(..4)?;
+    u32::from_str_radix(hex, 16).ok().filter(|low| (0xDC00..0xE000).contains(low))
+}
+
+fn hex_escape(chars: &mut std::str::Chars) -> Result<u32, (ErrorCode, String)> {
+    let hex: String = chars.take(4).collect();
+    match u32::from_str_radix(&hex, 16) {
+        Ok(code) if hex.len() == 4 => Ok(code),
+        _ => Err((ErrorCode::InvalidUnicodeEscape, format!("Invalid unicode escape \\u{}", hex)))
     }
 }
 
@@ -1,6 +1,9 @@
 use std::collections::VecDeque;
-use crate::parser::Parser;
-use crate::lexer::{Token, JsonKind};
+use crate::code::ErrorCode;
+use crate::parser::{ParseError, Parser, unescape};
+use crate::path::Path;
+use crate::lexer::{Token, TokenIter, JsonKind};
+use crate::value::Value;
 
 struct TestLexer {
     items: VecDeque<Token>
@@ -158,3 +161,86 @@ fn test_null_element() {
 }
 
 // END: ELEMENT TESTING
+
+// START: VALUE TESTING
+#[test]
+fn test_parse_values() {
+    let data = "{\"name\": \"Coletta \\u00d6rtiz\", \"score\": -4.1e+2, \"pets\": [\"Ziggy\", null, true]}";
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+
+    let value = parser.parse();
+
+    assert_eq!(value.get("name"), Some(&Value::from("Coletta Örtiz")));
+    assert_eq!(value.get("score").unwrap().as_f64(), Some(-410.0));
+    assert_eq!(value.get("pets"), Some(&Value::Array(vec![
+        Value::from("Ziggy"),
+        Value::Null,
+        Value::Bool(true),
+    ])));
+}
+
+#[test]
+#[should_panic(expected = "after end of document")]
+fn test_parse_trailing_value() {
+    let mut parser = Parser::new(Box::new(TokenIter::new("[1] [2]")));
+
+    parser.parse();
+}
+
+fn parse_error(data: &str) -> ParseError {
+    Parser::checked(Box::new(TokenIter::new(data).checked())).try_parse().unwrap_err()
+}
+
+#[test]
+fn test_error_paths() {
+    let error = parse_error("{\"ctRoot\": [{}, {\"address\": {\"town\": \"Luton\" \"postcode\": 1}}]}");
+    assert_eq!(error.message, "Expected token EndObject did not match StringVal");
+    assert_eq!(error.path, Path::root().key("ctRoot").index(1).key("address"));
+    assert_eq!(error.to_string(), "Expected token EndObject did not match StringVal at $.ctRoot[1].address | line 1, column 46");
+
+    assert_eq!(parse_error("{\"a\": [1, 01]}").to_string(), "Illegal leading zero found for number at $.a[1] | line 1, column 11");
+    assert_eq!(parse_error("{\"a\" 1}").path, Path::root().key("a"));
+    assert_eq!(parse_error("[\"\\x\"]").message, "Invalid escape sequence \\x in string \"\\x\"");
+}
+
+#[test]
+fn test_error_number_without_digits() {
+    for (data, column) in [("[1., 2]", 4), ("[2e]", 4), ("[3e+, 1]", 5), ("-1.e5", 4)] {
+        let error = parse_error(data);
+        assert_eq!(error.code, ErrorCode::MismatchedToken, "{}", data);
+        assert_eq!(error.column, column, "{}", data);
+    }
+    assert!(Parser::checked(Box::new(TokenIter::new("[1.0, 2e0, 3E+1, 4e-01]").checked())).try_validate().is_ok());
+}
+
+#[test]
+fn test_error_lexer_failure() {
+    let error = parse_error("{\"a\": [true,\n  True]}");
+    assert_eq!(error.to_string(), "Unknown keyword detected: True at $.a[1] | line 2, column 3");
+
+    let error = parse_error("{\"a\": \"open");
+    assert_eq!((error.message.as_str(), error.path), ("Nonterminating string found", Path::root().key("a")));
+}
+
+#[test]
+fn test_error_end_of_input() {
+    let error = parse_error("{\"a\": [1,\n 2");
+    assert_eq!(error.to_string(), "Expected token EndArray did not match end of input at $.a | line 2, column 3");
+    assert_eq!(parse_error("").message, "Unexpected token end of input");
+}
+
+#[test]
+#[should_panic(expected = "at $.a[0]")]
+fn test_parse_panics_with_path() {
+    Parser::new(Box::new(TokenIter::new("{\"a\": [-]}"))).parse();
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("\"a\\\\b\\/c\\n\""), "a\\b/c\n");
+    assert_eq!(unescape("\"\\ud83d\\ude00\""), "😀");
+    assert_eq!(unescape("\"\\ud83d\\u0041\""), "\u{FFFD}A");
+    assert_eq!(unescape("\"\\ud83d\\ud83d\\ude00\""), "\u{FFFD}😀");
+    assert_eq!(unescape("\"\\ude00x\""), "\u{FFFD}x");
+}
+// END: VALUE TESTING
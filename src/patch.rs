@@ -0,0 +1,289 @@
+use std::fmt;
+
+use crate::diff::hunks;
+use crate::pointer::{array_index, Pointer};
+use crate::value::Value;
+
+/// A JSON Patch document (RFC 6902).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch(Vec<Operation>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Add { path: Pointer, value: Value },
+    Remove { path: Pointer },
+    Replace { path: Pointer, value: Value },
+    Move { from: Pointer, path: Pointer },
+    Copy { from: Pointer, path: Pointer },
+    Test { path: Pointer, value: Value }
+}
+
+/// Failure while reading or applying a patch, pointing at the operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+    pub index: usize,
+    pub message: String
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operation {}: {}", self.index, self.message)
+    }
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test"
+        }
+    }
+
+    fn from_value(value: &Value) -> Result<Operation, String> {
+        let pointer = |name: &str| match value.get(name) {
+            Some(Value::String(val)) => Pointer::parse(val),
+            Some(_) => Err(format!("\"{}\" must be a string", name)),
+            None => Err(format!("missing \"{}\"", name))
+        };
+        let member = |name: &str| value.get(name).cloned()
+            .ok_or_else(|| format!("missing \"{}\"", name));
+
+        match value.get("op") {
+            Some(Value::String(op)) => match op.as_str() {
+                "add" => Ok(Operation::Add { path: pointer("path")?, value: member("value")? }),
+                "remove" => Ok(Operation::Remove { path: pointer("path")? }),
+                "replace" => Ok(Operation::Replace { path: pointer("path")?, value: member("value")? }),
+                "move" => Ok(Operation::Move { from: pointer("from")?, path: pointer("path")? }),
+                "copy" => Ok(Operation::Copy { from: pointer("from")?, path: pointer("path")? }),
+                "test" => Ok(Operation::Test { path: pointer("path")?, value: member("value")? }),
+                other => Err(format!("unknown op \"{}\"", other))
+            },
+            Some(_) => Err("\"op\" must be a string".to_string()),
+            None => Err("missing \"op\"".to_string())
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let mut members = vec![("op".to_string(), Value::from(self.name()))];
+
+        match self {
+            Operation::Move { from, .. } | Operation::Copy { from, .. } => {
+                members.push(("from".to_string(), Value::String(from.to_string())));
+            },
+            _ => {}
+        }
+
+        match self {
+            Operation::Add { path, .. } | Operation::Remove { path } | Operation::Replace { path, .. } |
+                Operation::Move { path, .. } | Operation::Copy { path, .. } | Operation::Test { path, .. } => {
+                    members.push(("path".to_string(), Value::String(path.to_string())));
+                }
+        }
+
+        match self {
+            Operation::Add { value, .. } | Operation::Replace { value, .. } | Operation::Test { value, .. } => {
+                members.push(("value".to_string(), value.clone()));
+            },
+            _ => {}
+        }
+
+        Value::Object(members)
+    }
+
+    fn apply(&self, doc: &mut Value) -> Result<(), String> {
+        match self {
+            Operation::Add { path, value } => add(doc, path, value.clone()),
+            Operation::Remove { path } => remove(doc, path).map(|_| ()),
+            Operation::Replace { path, value } => {
+                let target = path.resolve_mut(doc)
+                    .ok_or_else(|| format!("path \"{}\" does not exist", path))?;
+                *target = value.clone();
+                Ok(())
+            },
+            Operation::Move { from, path } => {
+                if path != from && path.starts_with(from) {
+                    return Err(format!("can not move \"{}\" into its own child \"{}\"", from, path));
+                }
+                let value = remove(doc, from)?;
+                add(doc, path, value)
+            },
+            Operation::Copy { from, path } => {
+                let value = from.resolve(doc)
+                    .ok_or_else(|| format!("path \"{}\" does not exist", from))?
+                    .clone();
+                add(doc, path, value)
+            },
+            Operation::Test { path, value } => {
+                match path.resolve(doc) {
+                    Some(actual) if actual == value => Ok(()),
+                    Some(_) => Err(format!("value at \"{}\" does not match", path)),
+                    None => Err(format!("path \"{}\" does not exist", path))
+                }
+            }
+        }
+    }
+}
+
+fn add(doc: &mut Value, path: &Pointer, value: Value) -> Result<(), String> {
+    let (parent, last) = match path.split_last() {
+        Some(val) => val,
+        None => {
+            *doc = value;
+            return Ok(());
+        }
+    };
+
+    match parent.resolve_mut(doc) {
+        Some(Value::Array(items)) => {
+            let idx = if last == "-" {
+                items.len()
+            } else {
+                array_index(last).filter(|idx| *idx <= items.len())
+                    .ok_or_else(|| format!("invalid array index \"{}\" in \"{}\"", last, path))?
+            };
+            items.insert(idx, value);
+            Ok(())
+        },
+        Some(target @ Value::Object(_)) => {
+            target.insert(last, value);
+            Ok(())
+        },
+        Some(_) => Err(format!("parent of \"{}\" is not a container", path)),
+        None => Err(format!("parent of \"{}\" does not exist", path))
+    }
+}
+
+fn remove(doc: &mut Value, path: &Pointer) -> Result<Value, String> {
+    let missing = || format!("path \"{}\" does not exist", path);
+    let (parent, last) = match path.split_last() {
+        Some(val) => val,
+        None => return Ok(std::mem::replace(doc, Value::Null))
+    };
+
+    match parent.resolve_mut(doc) {
+        Some(Value::Array(items)) => {
+            let idx = array_index(last).filter(|idx| *idx < items.len()).ok_or_else(missing)?;
+            Ok(items.remove(idx))
+        },
+        Some(target @ Value::Object(_)) => target.remove(last).ok_or_else(missing),
+        _ => Err(missing())
+    }
+}
+
+impl Patch {
+    pub fn new(operations: Vec<Operation>) -> Patch {
+        Patch(operations)
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.0
+    }
+
+    /// Reads a patch from its JSON form, an array of operation objects.
+    pub fn from_value(value: &Value) -> Result<Patch, PatchError> {
+        let items = match value {
+            Value::Array(items) => items,
+            _ => return Err(PatchError { index: 0, message: "patch must be an array".to_string() })
+        };
+
+        let mut operations = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let operation = Operation::from_value(item)
+                .map_err(|message| PatchError { index, message })?;
+            operations.push(operation);
+        }
+
+        Ok(Patch(operations))
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Array(self.0.iter().map(Operation::to_value).collect())
+    }
+
+    /// Applies every operation in order. The input is left untouched and
+    /// nothing is returned unless all operations succeed.
+    pub fn apply(&self, doc: &Value) -> Result<Value, PatchError> {
+        let mut result = doc.clone();
+
+        for (index, operation) in self.0.iter().enumerate() {
+            operation.apply(&mut result).map_err(|message| PatchError {
+                index,
+                message: format!("{} failed: {}", operation.name(), message)
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    /// Generates a patch turning `from` into `to`.
+    pub fn diff(from: &Value, to: &Value) -> Patch {
+        let mut operations = Vec::new();
+        diff_values(&Pointer::root(), from, to, &mut operations);
+        Patch(operations)
+    }
+}
+
+fn diff_values(path: &Pointer, from: &Value, to: &Value, out: &mut Vec<Operation>) {
+    if from == to {
+        return;
+    }
+
+    match (from, to) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, _) in old {
+                if to.get(key).is_none() {
+                    out.push(Operation::Remove { path: path.child(key) });
+                }
+            }
+            for (key, val) in new {
+                match from.get(key) {
+                    Some(old_val) => diff_values(&path.child(key), old_val, val, out),
+                    None => out.push(Operation::Add { path: path.child(key), value: val.clone() })
+                }
+            }
+        },
+        (Value::Array(old), Value::Array(new)) => diff_arrays(path, old, new, out),
+        _ => out.push(Operation::Replace { path: path.clone(), value: to.clone() })
+    }
+}
+
+// Walks the aligned hunks of both arrays, tracking where each edit lands in
+// the partially patched array.
+fn diff_arrays(path: &Pointer, old: &[Value], new: &[Value], out: &mut Vec<Operation>) {
+    let mut idx = 0;
+    let mut old_idx = 0;
+
+    for hunk in hunks(old, new) {
+        let kept = match (hunk.deleted.first(), hunk.inserted.first()) {
+            (Some(i), _) => i - old_idx,
+            (None, Some(j)) => j - idx,
+            (None, None) => 0
+        };
+        idx += kept;
+        old_idx += kept;
+
+        // Pairs of deletions and insertions are changes in place
+        let changes = hunk.deleted.len().min(hunk.inserted.len());
+        for j in &hunk.inserted[..changes] {
+            diff_values(&path.child(&idx.to_string()), &old[old_idx], &new[*j], out);
+            idx += 1;
+            old_idx += 1;
+        }
+        for _ in changes..hunk.deleted.len() {
+            out.push(Operation::Remove { path: path.child(&idx.to_string()) });
+            old_idx += 1;
+        }
+        for j in &hunk.inserted[changes..] {
+            let token = if old_idx == old.len() { "-".to_string() } else { idx.to_string() };
+            out.push(Operation::Add { path: path.child(&token), value: new[*j].clone() });
+            idx += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
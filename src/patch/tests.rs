@@ -0,0 +1,94 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::patch::Patch;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+fn apply(doc: &str, patch: &str) -> Result<Value, String> {
+    let patch = Patch::from_value(&parse(patch)).map_err(|err| err.to_string())?;
+    patch.apply(&parse(doc)).map_err(|err| err.to_string())
+}
+
+#[test]
+fn test_rfc_examples() {
+    let table = [
+        ("{\"foo\": \"bar\"}", "[{\"op\": \"add\", \"path\": \"/baz\", \"value\": \"qux\"}]",
+         "{\"baz\": \"qux\", \"foo\": \"bar\"}"),
+        ("{\"foo\": [\"bar\", \"baz\"]}", "[{\"op\": \"add\", \"path\": \"/foo/1\", \"value\": \"qux\"}]",
+         "{\"foo\": [\"bar\", \"qux\", \"baz\"]}"),
+        ("{\"baz\": \"qux\", \"foo\": \"bar\"}", "[{\"op\": \"remove\", \"path\": \"/baz\"}]",
+         "{\"foo\": \"bar\"}"),
+        ("{\"baz\": \"qux\", \"foo\": \"bar\"}", "[{\"op\": \"replace\", \"path\": \"/baz\", \"value\": \"boo\"}]",
+         "{\"baz\": \"boo\", \"foo\": \"bar\"}"),
+        ("{\"foo\": {\"bar\": \"baz\", \"waldo\": \"fred\"}, \"qux\": {\"corge\": \"grault\"}}",
+         "[{\"op\": \"move\", \"from\": \"/foo/waldo\", \"path\": \"/qux/thud\"}]",
+         "{\"foo\": {\"bar\": \"baz\"}, \"qux\": {\"corge\": \"grault\", \"thud\": \"fred\"}}"),
+        ("{\"foo\": [\"all\", \"grass\", \"cows\", \"eat\"]}", "[{\"op\": \"move\", \"from\": \"/foo/1\", \"path\": \"/foo/3\"}]",
+         "{\"foo\": [\"all\", \"cows\", \"eat\", \"grass\"]}"),
+        ("{\"foo\": [\"bar\"]}", "[{\"op\": \"add\", \"path\": \"/foo/-\", \"value\": [\"abc\", \"def\"]}]",
+         "{\"foo\": [\"bar\", [\"abc\", \"def\"]]}"),
+        ("{\"foo\": {\"a\": 1}}", "[{\"op\": \"copy\", \"from\": \"/foo\", \"path\": \"/bar\"}, {\"op\": \"test\", \"path\": \"/bar/a\", \"value\": 1.0}]",
+         "{\"foo\": {\"a\": 1}, \"bar\": {\"a\": 1}}"),
+        ("{\"foo\": 1}", "[{\"op\": \"add\", \"path\": \"\", \"value\": [1]}]", "[1]"),
+    ];
+
+    for (doc, patch, expected) in table.iter() {
+        assert_eq!(apply(doc, patch), Ok(parse(expected)), "patch {}", patch);
+    }
+}
+
+#[test]
+fn test_errors_report_operation_index() {
+    let doc = "{\"foo\": [\"bar\"], \"baz\": \"qux\"}";
+    let table = [
+        ("[{\"op\": \"test\", \"path\": \"/baz\", \"value\": \"qux\"}, {\"op\": \"test\", \"path\": \"/baz\", \"value\": \"bar\"}]", "operation 1"),
+        ("[{\"op\": \"add\", \"path\": \"/baz/bat/x\", \"value\": 1}]", "operation 0"),
+        ("[{\"op\": \"remove\", \"path\": \"/foo\"}, {\"op\": \"remove\", \"path\": \"/foo\"}]", "operation 1"),
+        ("[{\"op\": \"add\", \"path\": \"/foo/2\", \"value\": 1}]", "operation 0"),
+        ("[{\"op\": \"move\", \"from\": \"/foo\", \"path\": \"/foo/0\"}]", "operation 0"),
+        ("[{\"op\": \"remove\", \"path\": \"/baz\"}, {\"op\": \"jump\", \"path\": \"/foo\"}]", "operation 1"),
+        ("[{\"op\": \"add\", \"value\": 1}]", "operation 0"),
+    ];
+
+    for (patch, expected) in table.iter() {
+        let err = apply(doc, patch).unwrap_err();
+        assert!(err.starts_with(expected), "patch {} failed with {}", patch, err);
+    }
+}
+
+#[test]
+fn test_apply_is_atomic() {
+    let doc = parse("{\"a\": 1}");
+    let patch = Patch::from_value(&parse("[{\"op\": \"add\", \"path\": \"/b\", \"value\": 2}, {\"op\": \"remove\", \"path\": \"/c\"}]")).unwrap();
+
+    assert!(patch.apply(&doc).is_err());
+    assert_eq!(doc, parse("{\"a\": 1}"));
+}
+
+#[test]
+fn test_diff_round_trip() {
+    let table = [
+        ("{\"a\": 1, \"b\": [1, 2, 3]}", "{\"a\": 1, \"b\": [1, 2, 3]}", 0),
+        ("{\"a\": 1, \"b\": 2}", "{\"a\": 2, \"c\": 3}", 3),
+        ("[1, 2, 3, 4]", "[1, 3, 4, 5]", 2),
+        ("[\"x\", {\"n\": 1}]", "[\"x\", {\"n\": 2}]", 1),
+        ("[1, 2]", "[]", 2),
+        ("[]", "[1, 2]", 2),
+        ("{\"a\": [1]}", "{\"a\": {\"b\": 1}}", 1),
+        ("[1, 2, 3]", "[4, 5]", 3),
+    ];
+
+    for (from, to, len) in table.iter() {
+        let (from, to) = (parse(from), parse(to));
+        let patch = Patch::diff(&from, &to);
+        assert_eq!(patch.operations().len(), *len, "diff {:?}", patch.to_value());
+        assert_eq!(patch.apply(&from), Ok(to.clone()));
+
+        let reparsed = Patch::from_value(&patch.to_value()).unwrap();
+        assert_eq!(reparsed, patch);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+/// A single step from a value to one of its children.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathElement {
+    Key(String),
+    Index(usize)
+}
+
+/// Location of a value inside a document, starting from the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Path(Vec<PathElement>);
+
+impl Path {
+    pub fn root() -> Path {
+        Path(Vec::new())
+    }
+
+    pub fn elements(&self) -> &[PathElement] {
+        &self.0
+    }
+
+    pub fn push(&mut self, element: PathElement) {
+        self.0.push(element);
+    }
+
+    pub fn pop(&mut self) -> Option<PathElement> {
+        self.0.pop()
+    }
+
+    pub fn child(&self, element: PathElement) -> Path {
+        let mut path = self.clone();
+        path.push(element);
+        path
+    }
+
+    pub fn key(&self, key: &str) -> Path {
+        self.child(PathElement::Key(key.to_string()))
+    }
+
+    pub fn index(&self, idx: usize) -> Path {
+        self.child(PathElement::Index(idx))
+    }
+
+    /// The normalized path form from RFC 9535, e.g. `$['ctRoot'][0]['name']`.
+    pub fn normalized(&self) -> String {
+        let mut out = String::from("$");
+
+        for element in &self.0 {
+            match element {
+                PathElement::Index(idx) => out.push_str(&format!("[{}]", idx)),
+                PathElement::Key(key) => {
+                    out.push_str("['");
+                    for c in key.chars() {
+                        match c {
+                            '\'' => out.push_str("\\'"),
+                            '\\' => out.push_str("\\\\"),
+                            '\u{08}' => out.push_str("\\b"),
+                            '\u{0c}' => out.push_str("\\f"),
+                            '\n' => out.push_str("\\n"),
+                            '\r' => out.push_str("\\r"),
+                            '\t' => out.push_str("\\t"),
+                            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                            c => out.push(c)
+                        }
+                    }
+                    out.push_str("']");
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Displays the shorter dotted form, e.g. `$.ctRoot[12].address.town`.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+
+        for element in &self.0 {
+            match element {
+                PathElement::Index(idx) => write!(f, "[{}]", idx)?,
+                PathElement::Key(key) if is_identifier(key) => write!(f, ".{}", key)?,
+                PathElement::Key(key) => {
+                    let mut quoted = String::new();
+                    crate::serializer::write_string(&mut quoted, key);
+                    write!(f, "[{}]", quoted)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        },
+        _ => false
+    }
+}
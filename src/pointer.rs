@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::path::{Path, PathElement};
+use crate::value::Value;
+
+/// A JSON Pointer (RFC 6901), e.g. `/ctRoot/0/name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pointer(Vec<String>);
+
+impl Pointer {
+    pub fn root() -> Pointer {
+        Pointer(Vec::new())
+    }
+
+    pub fn parse(pointer: &str) -> Result<Pointer, String> {
+        if pointer.is_empty() {
+            return Ok(Pointer::root());
+        }
+
+        if !pointer.starts_with('/') {
+            return Err(format!("JSON pointer \"{}\" must start with '/'", pointer));
+        }
+
+        let mut tokens = Vec::new();
+        for token in pointer[1..].split('/') {
+            let mut out = String::new();
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                match (c, chars.clone().next()) {
+                    ('~', Some('0')) => {
+                        out.push('~');
+                        chars.next();
+                    },
+                    ('~', Some('1')) => {
+                        out.push('/');
+                        chars.next();
+                    },
+                    ('~', _) => return Err(format!("Invalid escape in JSON pointer \"{}\"", pointer)),
+                    (c, _) => out.push(c)
+                }
+            }
+            tokens.push(out);
+        }
+
+        Ok(Pointer(tokens))
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn child(&self, token: &str) -> Pointer {
+        let mut tokens = self.0.clone();
+        tokens.push(token.to_string());
+        Pointer(tokens)
+    }
+
+    /// Splits off the last reference token, e.g. `/a/b` into `/a` and `b`.
+    pub fn split_last(&self) -> Option<(Pointer, &str)> {
+        let (last, parent) = self.0.split_last()?;
+        Some((Pointer(parent.to_vec()), last))
+    }
+
+    pub fn starts_with(&self, other: &Pointer) -> bool {
+        self.0.starts_with(&other.0)
+    }
+
+    pub fn resolve<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        let mut curr = value;
+        for token in &self.0 {
+            curr = match curr {
+                Value::Object(_) => curr.get(token)?,
+                Value::Array(items) => items.get(array_index(token)?)?,
+                _ => return None
+            };
+        }
+        Some(curr)
+    }
+
+    pub fn resolve_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        let mut curr = value;
+        for token in &self.0 {
+            curr = match curr {
+                Value::Object(_) => curr.get_mut(token)?,
+                Value::Array(items) => items.get_mut(array_index(token)?)?,
+                _ => return None
+            };
+        }
+        Some(curr)
+    }
+}
+
+/// Array indices must be plain decimal numbers without leading zeros.
+pub fn array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_digit()) ||
+        (token.len() > 1 && token.starts_with('0')) {
+            return None;
+        }
+    token.parse().ok()
+}
+
+impl From<&Path> for Pointer {
+    fn from(path: &Path) -> Pointer {
+        Pointer(path.elements().iter().map(|element| match element {
+            PathElement::Key(key) => key.clone(),
+            PathElement::Index(idx) => idx.to_string()
+        }).collect())
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in &self.0 {
+            write!(f, "/{}", token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,42 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::path::Path;
+use crate::pointer::Pointer;
+use crate::value::Value;
+
+#[test]
+fn test_rfc_examples() {
+    let data = "{\"foo\": [\"bar\", \"baz\"], \"\": 0, \"a/b\": 1, \"m~n\": 8, \" \": 7}";
+    let value = Parser::new(Box::new(TokenIter::new(data))).parse();
+
+    let table = [
+        ("", None),
+        ("/foo/0", Some(Value::from("bar"))),
+        ("/", Some(Value::from(0))),
+        ("/a~1b", Some(Value::from(1))),
+        ("/m~0n", Some(Value::from(8))),
+        ("/ ", Some(Value::from(7))),
+        ("/foo/2", None),
+        ("/foo/01", None),
+        ("/foo/-", None),
+    ];
+
+    for (pointer, expected) in table.iter() {
+        let resolved = Pointer::parse(pointer).unwrap().resolve(&value).cloned();
+        match expected {
+            Some(expected) => assert_eq!(resolved.as_ref(), Some(expected), "pointer {}", pointer),
+            None if pointer.is_empty() => assert_eq!(resolved, Some(value.clone())),
+            None => assert_eq!(resolved, None, "pointer {}", pointer)
+        }
+    }
+}
+
+#[test]
+fn test_display_escapes() {
+    let path = Path::root().key("a/b").index(3).key("m~n");
+
+    assert_eq!(Pointer::from(&path).to_string(), "/a~1b/3/m~0n");
+    assert_eq!(Pointer::parse("/a~1b/3/m~0n").unwrap(), Pointer::from(&path));
+    assert!(Pointer::parse("a").is_err());
+    assert!(Pointer::parse("/~2").is_err());
+}
@@ -0,0 +1,935 @@
+use regex::Regex;
+
+use crate::path::{Path, PathElement};
+use crate::value::{Number, Value};
+
+// RFC 9535 limits integers in queries to the exact IEEE 754 range
+const MAX_INT: i64 = (1 << 53) - 1;
+
+/// A compiled JSONPath query (RFC 9535).
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+    query: Query
+}
+
+/// A value selected by a query together with its location.
+#[derive(Debug, Clone)]
+pub struct Node<'v> {
+    pub path: Path,
+    pub value: &'v Value
+}
+
+#[derive(Debug, Clone)]
+struct Query {
+    relative: bool,
+    segments: Vec<Segment>
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>)
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Expr)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Comparison(Comparable, CompOp, Comparable),
+    Exists(Query),
+    Test(FunctionExpr)
+}
+
+#[derive(Debug, Clone)]
+enum Comparable {
+    Literal(Value),
+    Query(Query),
+    Function(FunctionExpr)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value
+}
+
+#[derive(Debug, Clone)]
+struct FunctionExpr {
+    function: Function,
+    args: Vec<Comparable>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParamType {
+    Value,
+    Nodes
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        match name {
+            "length" => Some(Function::Length),
+            "count" => Some(Function::Count),
+            "match" => Some(Function::Match),
+            "search" => Some(Function::Search),
+            "value" => Some(Function::Value),
+            _ => None
+        }
+    }
+
+    fn params(&self) -> &'static [ParamType] {
+        match self {
+            Function::Length => &[ParamType::Value],
+            Function::Count | Function::Value => &[ParamType::Nodes],
+            Function::Match | Function::Search => &[ParamType::Value, ParamType::Value]
+        }
+    }
+
+    fn returns_logical(&self) -> bool {
+        matches!(self, Function::Match | Function::Search)
+    }
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<JsonPath, String> {
+        let mut parser = QueryParser {
+            chars: query.chars().collect(),
+            pos: 0
+        };
+
+        let query = parser.query()?;
+
+        if query.relative {
+            return Err("Query must start with '$'".to_string());
+        }
+
+        if parser.pos < parser.chars.len() {
+            return Err(format!("Unexpected character '{}' at position {}",
+                               parser.chars[parser.pos], parser.pos));
+        }
+
+        Ok(JsonPath { query })
+    }
+
+    /// Selects every node matching the query, in document order.
+    pub fn query<'v>(&self, root: &'v Value) -> Vec<Node<'v>> {
+        eval_query(&self.query, root, root)
+    }
+}
+
+struct QueryParser {
+    chars: Vec<char>,
+    pos: usize
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    fn error<T>(&self, msg: &str) -> Result<T, String> {
+        Err(format!("{} at position {}", msg, self.pos))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() != Some(c) {
+            return self.error(&format!("Expected '{}'", c));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn blank(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, String> {
+        let relative = match self.peek() {
+            Some('$') => false,
+            Some('@') => true,
+            _ => return self.error("Expected '$' or '@'")
+        };
+        self.pos += 1;
+
+        let mut segments = Vec::new();
+        loop {
+            let start = self.pos;
+            self.blank();
+
+            match self.peek() {
+                Some('[') => segments.push(Segment::Child(self.bracketed()?)),
+                Some('.') if self.peek_at(1) == Some('.') => {
+                    self.pos += 2;
+                    let selectors = match self.peek() {
+                        Some('[') => self.bracketed()?,
+                        Some('*') => {
+                            self.pos += 1;
+                            vec![Selector::Wildcard]
+                        },
+                        _ => vec![Selector::Name(self.shorthand()?)]
+                    };
+                    segments.push(Segment::Descendant(selectors));
+                },
+                Some('.') => {
+                    self.pos += 1;
+                    if self.peek() == Some('*') {
+                        self.pos += 1;
+                        segments.push(Segment::Child(vec![Selector::Wildcard]));
+                    } else {
+                        segments.push(Segment::Child(vec![Selector::Name(self.shorthand()?)]));
+                    }
+                },
+                _ => {
+                    self.pos = start;
+                    break;
+                }
+            }
+        }
+
+        Ok(Query { relative, segments })
+    }
+
+    fn shorthand(&mut self) -> Result<String, String> {
+        let is_first = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+
+        match self.peek() {
+            Some(c) if is_first(c) => {},
+            _ => return self.error("Expected member name")
+        }
+
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if is_first(c) || c.is_ascii_digit() {
+                name.push(c);
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        Ok(name)
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, String> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+
+        loop {
+            self.blank();
+            selectors.push(self.selector()?);
+            self.blank();
+
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(selectors);
+                },
+                _ => return self.error("Expected ',' or ']'")
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, String> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.string_literal()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            },
+            Some('?') => {
+                self.pos += 1;
+                self.blank();
+                Ok(Selector::Filter(self.logical_or()?))
+            },
+            Some(':') => self.slice(None),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.integer()?;
+                self.blank();
+                if self.peek() == Some(':') {
+                    self.slice(Some(start))
+                } else {
+                    Ok(Selector::Index(start))
+                }
+            },
+            _ => self.error("Invalid selector")
+        }
+    }
+
+    fn slice(&mut self, start: Option<i64>) -> Result<Selector, String> {
+        self.expect(':')?;
+        self.blank();
+
+        let end = self.optional_integer()?;
+        self.blank();
+
+        let mut step = None;
+        if self.peek() == Some(':') {
+            self.pos += 1;
+            self.blank();
+            step = self.optional_integer()?;
+        }
+
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, String> {
+        match self.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Some(self.integer()?)),
+            _ => Ok(None)
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        let start = self.pos;
+        let negative = self.peek() == Some('-');
+        if negative {
+            self.pos += 1;
+        }
+
+        let mut digits = String::new();
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            digits.push(c);
+            self.pos += 1;
+        }
+
+        if digits.is_empty() || (digits.starts_with('0') && (digits.len() > 1 || negative)) {
+            self.pos = start;
+            return self.error("Invalid integer");
+        }
+
+        match digits.parse::<i64>() {
+            Ok(val) if val <= MAX_INT => Ok(if negative { -val } else { val }),
+            _ => {
+                self.pos = start;
+                self.error("Integer out of range")
+            }
+        }
+    }
+
+    fn string_literal(&mut self) -> Result<String, String> {
+        let quote = self.peek().unwrap();
+        self.pos += 1;
+        let mut out = String::new();
+
+        loop {
+            match self.peek() {
+                None => return self.error("Nonterminating string literal"),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(out);
+                },
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('b') => out.push('\u{08}'),
+                        Some('f') => out.push('\u{0c}'),
+                        Some('n') => out.push('\n'),
+                        Some('r') => out.push('\r'),
+                        Some('t') => out.push('\t'),
+                        Some('/') => out.push('/'),
+                        Some('\\') => out.push('\\'),
+                        Some(c) if c == quote => out.push(c),
+                        Some('u') => {
+                            self.pos += 1;
+                            out.push(self.unicode_escape()?);
+                            continue;
+                        },
+                        _ => return self.error("Invalid escape sequence")
+                    }
+                    self.pos += 1;
+                },
+                Some(c) if (c as u32) < 0x20 => return self.error("Control character in string literal"),
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.starts_with("\\u") {
+                return self.error("Unpaired surrogate in string literal");
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.error("Invalid low surrogate in string literal");
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        match std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.error("Invalid unicode escape")
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+        if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return self.error("Invalid unicode escape");
+        }
+        self.pos += 4;
+        Ok(u32::from_str_radix(&hex, 16).unwrap())
+    }
+
+    fn logical_or(&mut self) -> Result<Expr, String> {
+        let mut exprs = vec![self.logical_and()?];
+
+        loop {
+            let start = self.pos;
+            self.blank();
+            if self.starts_with("||") {
+                self.pos += 2;
+                self.blank();
+                exprs.push(self.logical_and()?);
+            } else {
+                self.pos = start;
+                break;
+            }
+        }
+
+        Ok(if exprs.len() == 1 { exprs.pop().unwrap() } else { Expr::Or(exprs) })
+    }
+
+    fn logical_and(&mut self) -> Result<Expr, String> {
+        let mut exprs = vec![self.basic()?];
+
+        loop {
+            let start = self.pos;
+            self.blank();
+            if self.starts_with("&&") {
+                self.pos += 2;
+                self.blank();
+                exprs.push(self.basic()?);
+            } else {
+                self.pos = start;
+                break;
+            }
+        }
+
+        Ok(if exprs.len() == 1 { exprs.pop().unwrap() } else { Expr::And(exprs) })
+    }
+
+    fn basic(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some('!') {
+            self.pos += 1;
+            self.blank();
+            let expr = if self.peek() == Some('(') {
+                self.paren()?
+            } else {
+                self.test()?
+            };
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+
+        if self.peek() == Some('(') {
+            return self.paren();
+        }
+
+        let left = self.comparable()?;
+        let start = self.pos;
+        self.blank();
+
+        let op = if self.starts_with("==") {
+            Some((CompOp::Eq, 2))
+        } else if self.starts_with("!=") {
+            Some((CompOp::Ne, 2))
+        } else if self.starts_with("<=") {
+            Some((CompOp::Le, 2))
+        } else if self.starts_with(">=") {
+            Some((CompOp::Ge, 2))
+        } else if self.starts_with("<") {
+            Some((CompOp::Lt, 1))
+        } else if self.starts_with(">") {
+            Some((CompOp::Gt, 1))
+        } else {
+            None
+        };
+
+        match op {
+            Some((op, len)) => {
+                self.pos += len;
+                self.blank();
+                let right = self.comparable()?;
+                check_comparable(&left)?;
+                check_comparable(&right)?;
+                Ok(Expr::Comparison(left, op, right))
+            },
+            None => {
+                self.pos = start;
+                as_test(left)
+            }
+        }
+    }
+
+    fn paren(&mut self) -> Result<Expr, String> {
+        self.expect('(')?;
+        self.blank();
+        let expr = self.logical_or()?;
+        self.blank();
+        self.expect(')')?;
+        Ok(expr)
+    }
+
+    fn test(&mut self) -> Result<Expr, String> {
+        let operand = self.comparable()?;
+        as_test(operand)
+    }
+
+    fn comparable(&mut self) -> Result<Comparable, String> {
+        match self.peek() {
+            Some('$') | Some('@') => Ok(Comparable::Query(self.query()?)),
+            Some('\'') | Some('"') => Ok(Comparable::Literal(Value::String(self.string_literal()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Comparable::Literal(self.number()?)),
+            Some(c) if c.is_ascii_lowercase() => {
+                let mut name = String::new();
+                while let Some(c) = self.peek() {
+                    if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' {
+                        name.push(c);
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+
+                if self.peek() == Some('(') {
+                    return Ok(Comparable::Function(self.function(&name)?));
+                }
+
+                match name.as_str() {
+                    "true" => Ok(Comparable::Literal(Value::Bool(true))),
+                    "false" => Ok(Comparable::Literal(Value::Bool(false))),
+                    "null" => Ok(Comparable::Literal(Value::Null)),
+                    _ => self.error(&format!("Unknown keyword '{}'", name))
+                }
+            },
+            _ => self.error("Expected filter expression")
+        }
+    }
+
+    fn function(&mut self, name: &str) -> Result<FunctionExpr, String> {
+        let function = match Function::from_name(name) {
+            Some(val) => val,
+            None => return self.error(&format!("Unknown function '{}'", name))
+        };
+
+        self.expect('(')?;
+        self.blank();
+
+        let mut args = Vec::new();
+        if self.peek() != Some(')') {
+            loop {
+                args.push(self.comparable()?);
+                self.blank();
+                if self.peek() == Some(',') {
+                    self.pos += 1;
+                    self.blank();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect(')')?;
+
+        let params = function.params();
+        if params.len() != args.len() {
+            return self.error(&format!("Function '{}' expects {} argument(s)", name, params.len()));
+        }
+
+        for (param, arg) in params.iter().zip(args.iter()) {
+            let valid = match (param, arg) {
+                (ParamType::Value, Comparable::Literal(_)) => true,
+                (ParamType::Value, Comparable::Query(query)) => is_singular(query),
+                (ParamType::Value, Comparable::Function(inner)) => !inner.function.returns_logical(),
+                (ParamType::Nodes, Comparable::Query(_)) => true,
+                (ParamType::Nodes, _) => false
+            };
+
+            if !valid {
+                return self.error(&format!("Invalid argument type for function '{}'", name));
+            }
+        }
+
+        Ok(FunctionExpr { function, args })
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut text = String::new();
+        let start = self.pos;
+
+        if self.peek() == Some('-') {
+            text.push('-');
+            self.pos += 1;
+        }
+
+        let int_start = self.pos;
+        self.push_digits(&mut text);
+        let int_len = self.pos - int_start;
+        if int_len == 0 || (int_len > 1 && self.chars[int_start] == '0') {
+            self.pos = start;
+            return self.error("Invalid number literal");
+        }
+
+        if self.peek() == Some('.') {
+            text.push('.');
+            self.pos += 1;
+            if self.push_digits(&mut text) == 0 {
+                return self.error("Invalid number literal");
+            }
+        }
+
+        if let Some('e') | Some('E') = self.peek() {
+            text.push('e');
+            self.pos += 1;
+            if let Some(c @ '+') | Some(c @ '-') = self.peek() {
+                text.push(c);
+                self.pos += 1;
+            }
+            if self.push_digits(&mut text) == 0 {
+                return self.error("Invalid number literal");
+            }
+        }
+
+        Ok(Value::Number(Number::new(text)))
+    }
+
+    fn push_digits(&mut self, text: &mut String) -> usize {
+        let mut count = 0;
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            text.push(c);
+            self.pos += 1;
+            count += 1;
+        }
+        count
+    }
+}
+
+fn is_singular(query: &Query) -> bool {
+    query.segments.iter().all(|segment| match segment {
+        Segment::Child(selectors) => selectors.len() == 1 &&
+            matches!(selectors[0], Selector::Name(_) | Selector::Index(_)),
+        Segment::Descendant(_) => false
+    })
+}
+
+fn check_comparable(operand: &Comparable) -> Result<(), String> {
+    match operand {
+        Comparable::Query(query) if !is_singular(query) => {
+            Err("Only singular queries can be compared".to_string())
+        },
+        Comparable::Function(func) if func.function.returns_logical() => {
+            Err("Function result of logical type can not be compared".to_string())
+        },
+        _ => Ok(())
+    }
+}
+
+fn as_test(operand: Comparable) -> Result<Expr, String> {
+    match operand {
+        Comparable::Query(query) => Ok(Expr::Exists(query)),
+        Comparable::Function(func) if func.function.returns_logical() => Ok(Expr::Test(func)),
+        Comparable::Function(_) => Err("Function result of value type must be compared".to_string()),
+        Comparable::Literal(_) => Err("Literal must be compared".to_string())
+    }
+}
+
+fn eval_query<'v>(query: &Query, root: &'v Value, current: &'v Value) -> Vec<Node<'v>> {
+    let start = if query.relative { current } else { root };
+    let mut nodes = vec![Node { path: Path::root(), value: start }];
+
+    for segment in &query.segments {
+        let mut next = Vec::new();
+
+        for node in &nodes {
+            match segment {
+                Segment::Child(selectors) => {
+                    for selector in selectors {
+                        select(selector, node, root, &mut next);
+                    }
+                },
+                Segment::Descendant(selectors) => {
+                    let mut descendants = Vec::new();
+                    descend(node.path.clone(), node.value, &mut descendants);
+                    for descendant in &descendants {
+                        for selector in selectors {
+                            select(selector, descendant, root, &mut next);
+                        }
+                    }
+                }
+            }
+        }
+
+        nodes = next;
+    }
+
+    nodes
+}
+
+fn descend<'v>(path: Path, value: &'v Value, out: &mut Vec<Node<'v>>) {
+    out.push(Node { path: path.clone(), value });
+
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                descend(path.index(i), item, out);
+            }
+        },
+        Value::Object(members) => {
+            for (key, val) in members {
+                descend(path.key(key), val, out);
+            }
+        },
+        _ => {}
+    }
+}
+
+fn select<'v>(selector: &Selector, node: &Node<'v>, root: &'v Value, out: &mut Vec<Node<'v>>) {
+    match (selector, node.value) {
+        (Selector::Name(name), Value::Object(_)) => {
+            if let Some(val) = node.value.get(name) {
+                out.push(Node { path: node.path.key(name), value: val });
+            }
+        },
+        (Selector::Wildcard, _) | (Selector::Filter(_), _) => {
+            for (element, child) in children(node.value) {
+                let keep = match selector {
+                    Selector::Filter(expr) => eval_expr(expr, root, child),
+                    _ => true
+                };
+                if keep {
+                    out.push(Node { path: node.path.child(element), value: child });
+                }
+            }
+        },
+        (Selector::Index(idx), Value::Array(items)) => {
+            let len = items.len() as i64;
+            let idx = if *idx < 0 { len + idx } else { *idx };
+            if idx >= 0 && idx < len {
+                out.push(Node { path: node.path.index(idx as usize), value: &items[idx as usize] });
+            }
+        },
+        (Selector::Slice(start, end, step), Value::Array(items)) => {
+            for idx in slice_indices(items.len() as i64, *start, *end, step.unwrap_or(1)) {
+                out.push(Node { path: node.path.index(idx), value: &items[idx] });
+            }
+        },
+        _ => {}
+    }
+}
+
+fn children(value: &Value) -> Vec<(PathElement, &Value)> {
+    match value {
+        Value::Array(items) => items.iter().enumerate()
+            .map(|(i, item)| (PathElement::Index(i), item))
+            .collect(),
+        Value::Object(members) => members.iter()
+            .map(|(key, val)| (PathElement::Key(key.clone()), val))
+            .collect(),
+        _ => Vec::new()
+    }
+}
+
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let normalize = |idx: i64| if idx >= 0 { idx } else { len + idx };
+    let mut indices = Vec::new();
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).max(0).min(len);
+        let upper = normalize(end.unwrap_or(len)).max(0).min(len);
+        let mut idx = lower;
+        while idx < upper {
+            indices.push(idx as usize);
+            idx += step;
+        }
+    } else if step < 0 {
+        let upper = start.map(normalize).unwrap_or(len - 1).max(-1).min(len - 1);
+        let lower = end.map(normalize).unwrap_or(-1).max(-1).min(len - 1);
+        let mut idx = upper;
+        while lower < idx {
+            indices.push(idx as usize);
+            idx += step;
+        }
+    }
+
+    indices
+}
+
+fn eval_expr(expr: &Expr, root: &Value, current: &Value) -> bool {
+    match expr {
+        Expr::Or(exprs) => exprs.iter().any(|expr| eval_expr(expr, root, current)),
+        Expr::And(exprs) => exprs.iter().all(|expr| eval_expr(expr, root, current)),
+        Expr::Not(expr) => !eval_expr(expr, root, current),
+        Expr::Exists(query) => !eval_query(query, root, current).is_empty(),
+        Expr::Test(func) => eval_logical(func, root, current),
+        Expr::Comparison(left, op, right) => {
+            let left = eval_comparable(left, root, current);
+            let right = eval_comparable(right, root, current);
+            compare(left.as_ref(), *op, right.as_ref())
+        }
+    }
+}
+
+// Yields `None` for the special result "Nothing"
+fn eval_comparable(operand: &Comparable, root: &Value, current: &Value) -> Option<Value> {
+    match operand {
+        Comparable::Literal(val) => Some(val.clone()),
+        Comparable::Query(query) => {
+            let nodes = eval_query(query, root, current);
+            match nodes.as_slice() {
+                [node] => Some(node.value.clone()),
+                _ => None
+            }
+        },
+        Comparable::Function(func) => eval_value(func, root, current)
+    }
+}
+
+fn eval_value(func: &FunctionExpr, root: &Value, current: &Value) -> Option<Value> {
+    match func.function {
+        Function::Length => {
+            let count = match eval_comparable(&func.args[0], root, current)? {
+                Value::String(val) => val.chars().count(),
+                Value::Array(items) => items.len(),
+                Value::Object(members) => members.len(),
+                _ => return None
+            };
+            Some(Value::Number(Number::from(count)))
+        },
+        Function::Count => {
+            let count = match &func.args[0] {
+                Comparable::Query(query) => eval_query(query, root, current).len(),
+                _ => 0
+            };
+            Some(Value::Number(Number::from(count)))
+        },
+        Function::Value => match &func.args[0] {
+            Comparable::Query(query) => match eval_query(query, root, current).as_slice() {
+                [node] => Some(node.value.clone()),
+                _ => None
+            },
+            _ => None
+        },
+        Function::Match | Function::Search => None
+    }
+}
+
+fn eval_logical(func: &FunctionExpr, root: &Value, current: &Value) -> bool {
+    let text = eval_comparable(&func.args[0], root, current);
+    let pattern = eval_comparable(&func.args[1], root, current);
+
+    let (text, pattern) = match (text, pattern) {
+        (Some(Value::String(text)), Some(Value::String(pattern))) => (text, pattern),
+        _ => return false
+    };
+
+    let pattern = translate_iregexp(&pattern);
+    let pattern = if func.function == Function::Match {
+        format!("^(?:{})$", pattern)
+    } else {
+        pattern
+    };
+
+    match Regex::new(&pattern) {
+        Ok(re) => re.is_match(&text),
+        Err(_) => false
+    }
+}
+
+// I-Regexp's '.' excludes both line terminators, the regex crate's only '\n'
+fn translate_iregexp(pattern: &str) -> String {
+    let mut out = String::new();
+    let mut chars = pattern.chars();
+    let mut in_class = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                out.push(c);
+                if let Some(next) = chars.next() {
+                    out.push(next);
+                }
+            },
+            '[' => {
+                in_class = true;
+                out.push(c);
+            },
+            ']' => {
+                in_class = false;
+                out.push(c);
+            },
+            '.' if !in_class => out.push_str("[^\\n\\r]"),
+            c => out.push(c)
+        }
+    }
+
+    out
+}
+
+fn compare(left: Option<&Value>, op: CompOp, right: Option<&Value>) -> bool {
+    match op {
+        CompOp::Eq => left == right,
+        CompOp::Ne => left != right,
+        CompOp::Lt => less_than(left, right),
+        CompOp::Gt => less_than(right, left),
+        CompOp::Le => less_than(left, right) || left == right,
+        CompOp::Ge => less_than(right, left) || left == right
+    }
+}
+
+fn less_than(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a.as_f64() < b.as_f64(),
+        (Some(Value::String(a)), Some(Value::String(b))) => a < b,
+        _ => false
+    }
+}
+
+#[cfg(test)]
+mod tests;
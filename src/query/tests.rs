@@ -0,0 +1,118 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::query::JsonPath;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+fn paths(query: &str, data: &str) -> Vec<String> {
+    let value = parse(data);
+    JsonPath::parse(query).unwrap()
+        .query(&value)
+        .iter()
+        .map(|node| node.path.normalized())
+        .collect()
+}
+
+const STORE: &str = "{
+    \"store\": {
+        \"book\": [
+            { \"category\": \"reference\", \"author\": \"Nigel Rees\", \"title\": \"Sayings of the Century\", \"price\": 8.95 },
+            { \"category\": \"fiction\", \"author\": \"Evelyn Waugh\", \"title\": \"Sword of Honour\", \"price\": 12.99 },
+            { \"category\": \"fiction\", \"author\": \"Herman Melville\", \"title\": \"Moby Dick\", \"isbn\": \"0-553-21311-3\", \"price\": 8.99 },
+            { \"category\": \"fiction\", \"author\": \"J. R. R. Tolkien\", \"title\": \"The Lord of the Rings\", \"isbn\": \"0-395-19395-8\", \"price\": 22.99 }
+        ],
+        \"bicycle\": { \"color\": \"red\", \"price\": 399 }
+    }
+}";
+
+#[test]
+fn test_child_segments() {
+    assert_eq!(paths("$.store.bicycle.color", STORE), vec!["$['store']['bicycle']['color']"]);
+    assert_eq!(paths("$['store'][\"bicycle\"]", STORE), vec!["$['store']['bicycle']"]);
+    assert_eq!(paths("$.store.missing", STORE).len(), 0);
+}
+
+#[test]
+fn test_descendant_segments() {
+    assert_eq!(paths("$..author", STORE).len(), 4);
+    assert_eq!(paths("$..price", STORE), vec![
+        "$['store']['book'][0]['price']",
+        "$['store']['book'][1]['price']",
+        "$['store']['book'][2]['price']",
+        "$['store']['book'][3]['price']",
+        "$['store']['bicycle']['price']",
+    ]);
+    assert_eq!(paths("$..[0]", "[[1, 2], [3]]"), vec!["$[0]", "$[0][0]", "$[1][0]"]);
+}
+
+#[test]
+fn test_wildcard_and_index() {
+    assert_eq!(paths("$.store.*", STORE), vec!["$['store']['book']", "$['store']['bicycle']"]);
+    assert_eq!(paths("$.store.book[-1]", STORE), vec!["$['store']['book'][3]"]);
+    assert_eq!(paths("$.store.book[0, 2]", STORE), vec!["$['store']['book'][0]", "$['store']['book'][2]"]);
+    assert_eq!(paths("$[4]", "[1, 2]").len(), 0);
+}
+
+#[test]
+fn test_slices() {
+    let data = "[0, 1, 2, 3, 4, 5, 6]";
+    assert_eq!(paths("$[1:3]", data), vec!["$[1]", "$[2]"]);
+    assert_eq!(paths("$[5:]", data), vec!["$[5]", "$[6]"]);
+    assert_eq!(paths("$[::3]", data), vec!["$[0]", "$[3]", "$[6]"]);
+    assert_eq!(paths("$[::-3]", data), vec!["$[6]", "$[3]", "$[0]"]);
+    assert_eq!(paths("$[-2:]", data), vec!["$[5]", "$[6]"]);
+    assert_eq!(paths("$[1:5:0]", data).len(), 0);
+}
+
+#[test]
+fn test_filters() {
+    assert_eq!(paths("$.store.book[?@.isbn]", STORE).len(), 2);
+    assert_eq!(paths("$.store.book[?@.price < 10].title", STORE), vec![
+        "$['store']['book'][0]['title']",
+        "$['store']['book'][2]['title']",
+    ]);
+    assert_eq!(paths("$..book[?@.category == 'fiction' && !(@.price > 20)]", STORE).len(), 2);
+    assert_eq!(paths("$..book[?@.price == $.store.bicycle.price || @.author == 'Nigel Rees']", STORE).len(), 1);
+    assert_eq!(paths("$[?@ == null]", "[null, 0, false]"), vec!["$[0]"]);
+    assert_eq!(paths("$[?@.a == @.b]", "[{}, {\"a\": 1}, {\"a\": 1, \"b\": 1.0}]"), vec!["$[0]", "$[2]"]);
+}
+
+#[test]
+fn test_functions() {
+    assert_eq!(paths("$.store.book[?length(@.title) > 15]", STORE).len(), 2);
+    assert_eq!(paths("$[?count(@.*) == 2]", "[[1, 2], [1], {\"a\": 1, \"b\": 2}]"), vec!["$[0]", "$[2]"]);
+    assert_eq!(paths("$.store.book[?match(@.author, 'J.*')]", STORE), vec!["$['store']['book'][3]"]);
+    assert_eq!(paths("$.store.book[?search(@.title, 'of')]", STORE).len(), 3);
+    assert_eq!(paths("$[?value(@..color) == 'red']", STORE), vec!["$['store']"]);
+}
+
+#[test]
+fn test_normalized_path_escaping() {
+    assert_eq!(paths("$[\"it's\"]", "{\"it's\": 1}"), vec!["$['it\\'s']"]);
+    assert_eq!(paths("$.*", "{\"a\\nb\": 1}"), vec!["$['a\\nb']"]);
+}
+
+#[test]
+fn test_invalid_queries() {
+    let table = vec![
+        "store",
+        "$.",
+        "$[01]",
+        "$[-0]",
+        "$[?@.a == @.*]",
+        "$[?length(@.*) == 1]",
+        "$[?count(@) == 'x' ||]",
+        "$[?match(@.a, 'b') == true]",
+        "$[?@.a == 1",
+        "$[?foo(@)]",
+        "$ ",
+    ];
+
+    for query in table {
+        assert!(JsonPath::parse(query).is_err(), "query {} should not parse", query);
+    }
+}
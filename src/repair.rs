@@ -0,0 +1,452 @@
+use std::fmt;
+
+use crate::value::{Number, Value};
+
+/// A change made while repairing a document, at the place it was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Reads broken or truncated JSON as far as it makes sense, fixing what it
+/// can on the way: comments, trailing and missing commas, unquoted keys,
+/// single quotes, Python and JavaScript literals, raw newlines in strings and
+/// anything left open at the end of the input. Valid JSON comes back without
+/// repairs.
+pub fn repair(contents: &str) -> (Value, Vec<Repair>) {
+    let mut repairer = Repairer {
+        chars: contents.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        repairs: Vec::new()
+    };
+
+    let value = repairer.document();
+    (value, repairer.repairs)
+}
+
+struct Repairer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    repairs: Vec<Repair>
+}
+
+impl Repairer {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn note(&mut self, (line, column): (usize, usize), message: String) {
+        self.repairs.push(Repair { line, column, message });
+    }
+
+    fn here(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    fn document(&mut self) -> Value {
+        if self.peek() == Some('\u{feff}') {
+            self.note(self.here(), "removed byte order mark".to_string());
+            self.bump();
+        }
+        self.trivia();
+
+        // Chatty output wraps the document in prose or a code fence
+        if self.peek().is_some_and(|c| !matches!(c, '{' | '[' | '"' | '\'')) {
+            if let Some(offset) = self.chars[self.pos..].iter().position(|c| *c == '{' || *c == '[') {
+                let at = self.here();
+                (0..offset).for_each(|_| { self.bump(); });
+                self.note(at, "skipped text before the document".to_string());
+            }
+        }
+
+        let value = self.value();
+        self.trivia();
+        if self.pos < self.chars.len() {
+            self.note(self.here(), "removed text after the document".to_string());
+        }
+        value
+    }
+
+    // Skips whitespace and removes comments, both `//`, `#` and `/* */`
+    fn trivia(&mut self) {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                },
+                (Some('/'), Some('/')) | (Some('#'), _) => {
+                    self.note(self.here(), "removed comment".to_string());
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                },
+                (Some('/'), Some('*')) => {
+                    self.note(self.here(), "removed comment".to_string());
+                    self.bump();
+                    self.bump();
+                    while self.peek().is_some() && !(self.peek() == Some('*') && self.peek_at(1) == Some('/')) {
+                        self.bump();
+                    }
+                    self.bump();
+                    self.bump();
+                },
+                _ => return
+            }
+        }
+    }
+
+    fn value(&mut self) -> Value {
+        loop {
+            self.trivia();
+            match self.peek() {
+                Some('{') => return self.object(),
+                Some('[') => return self.array(),
+                Some(quote @ ('"' | '\'')) => return Value::String(self.string(quote)),
+                Some(c) if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() => return self.number(),
+                Some(c) if starts_word(c) => return self.word(),
+                None | Some(',' | ':' | '}' | ']') => {
+                    self.note(self.here(), "inserted null for a missing value".to_string());
+                    return Value::Null;
+                },
+                Some(c) => {
+                    self.note(self.here(), format!("removed unexpected character {:?}", c));
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn object(&mut self) -> Value {
+        let open = self.here();
+        self.bump();
+
+        let mut members = Vec::new();
+        loop {
+            self.trivia();
+            let key = match self.peek() {
+                None => {
+                    self.note(open, "closed unterminated object".to_string());
+                    break;
+                },
+                Some('}') => {
+                    self.bump();
+                    break;
+                },
+                // Left for the array it most likely closes
+                Some(']') => {
+                    self.note(self.here(), "inserted missing } before ]".to_string());
+                    break;
+                },
+                Some(',') => {
+                    self.note(self.here(), "removed extra comma".to_string());
+                    self.bump();
+                    continue;
+                },
+                Some(quote @ ('"' | '\'')) => self.string(quote),
+                Some(c) if starts_word(c) || c.is_ascii_digit() => {
+                    let at = self.here();
+                    let key = self.identifier();
+                    self.note(at, format!("quoted key {}", key));
+                    key
+                },
+                Some(c) => {
+                    self.note(self.here(), format!("removed unexpected character {:?}", c));
+                    self.bump();
+                    continue;
+                }
+            };
+
+            self.trivia();
+            match self.peek() {
+                Some(':') => {
+                    self.bump();
+                },
+                Some('=') => {
+                    self.note(self.here(), "replaced = with :".to_string());
+                    self.bump();
+                },
+                _ => self.note(self.here(), "inserted missing colon".to_string())
+            }
+            let value = self.value();
+            members.push((key, value));
+            self.separator('}');
+        }
+
+        Value::Object(members)
+    }
+
+    fn array(&mut self) -> Value {
+        let open = self.here();
+        self.bump();
+
+        let mut items = Vec::new();
+        loop {
+            self.trivia();
+            match self.peek() {
+                None => {
+                    self.note(open, "closed unterminated array".to_string());
+                    break;
+                },
+                Some(']') => {
+                    self.bump();
+                    break;
+                },
+                Some('}') => {
+                    self.note(self.here(), "inserted missing ] before }".to_string());
+                    break;
+                },
+                Some(',') => {
+                    self.note(self.here(), "removed extra comma".to_string());
+                    self.bump();
+                    continue;
+                },
+                // Most likely a key written into an array, the value is kept
+                Some(':') => {
+                    self.note(self.here(), "removed unexpected character ':'".to_string());
+                    self.bump();
+                    continue;
+                },
+                _ => {}
+            }
+
+            let start = self.pos;
+            items.push(self.value());
+            self.separator(']');
+            // A value that consumed nothing would be read again forever
+            if self.pos == start {
+                if let Some(c) = self.peek() {
+                    items.pop();
+                    self.note(self.here(), format!("removed unexpected character {:?}", c));
+                    self.bump();
+                }
+            }
+        }
+
+        Value::Array(items)
+    }
+
+    // Reads what comes after an entry, which should be a comma unless the
+    // container ends
+    fn separator(&mut self, close: char) {
+        self.trivia();
+        match self.peek() {
+            Some(',') => {
+                let comma = self.here();
+                self.bump();
+                self.trivia();
+                if self.peek().is_none() || self.peek() == Some(close) {
+                    self.note(comma, "removed trailing comma".to_string());
+                }
+            },
+            None | Some('}' | ']') => {},
+            // Removed by the array itself
+            Some(':') if close == ']' => {},
+            Some(_) => self.note(self.here(), "inserted missing comma".to_string())
+        }
+    }
+
+    fn string(&mut self, quote: char) -> String {
+        let open = self.here();
+        if quote == '\'' {
+            self.note(open, "replaced single quotes with double quotes".to_string());
+        }
+        self.bump();
+
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    self.note(open, "closed unterminated string".to_string());
+                    break;
+                },
+                Some(c) if c == quote => {
+                    self.bump();
+                    break;
+                },
+                Some('\\') => {
+                    self.bump();
+                    self.escape(&mut out);
+                },
+                Some(c @ ('\n' | '\r' | '\t')) => {
+                    self.note(self.here(), format!("escaped {:?} in string", c));
+                    out.push(c);
+                    self.bump();
+                },
+                Some(c) => {
+                    out.push(c);
+                    self.bump();
+                }
+            }
+        }
+        out
+    }
+
+    fn escape(&mut self, out: &mut String) {
+        let at = self.here();
+        let decoded = match self.bump() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{08}',
+            Some('f') => '\u{0c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('\'') => '\'',
+            Some('u') => match self.hex() {
+                Some(high @ 0xD800..=0xDBFF) => match self.low_surrogate() {
+                    Some(low) => {
+                        (0..6).for_each(|_| { self.bump(); });
+                        std::char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap_or('\u{FFFD}')
+                    },
+                    None => '\u{FFFD}'
+                },
+                Some(code) => std::char::from_u32(code).unwrap_or('\u{FFFD}'),
+                None => {
+                    self.note(at, "kept invalid unicode escape as text".to_string());
+                    out.push_str("\\u");
+                    return;
+                }
+            },
+            Some(other) => {
+                self.note(at, format!("kept invalid escape \\{} as text", other));
+                out.push('\\');
+                other
+            },
+            None => return
+        };
+        out.push(decoded);
+    }
+
+    // The low half of a surrogate pair escaped at the current position
+    fn low_surrogate(&self) -> Option<u32> {
+        if self.peek() != Some('\\') || self.peek_at(1) != Some('u') {
+            return None;
+        }
+        let hex: String = (2..6).filter_map(|offset| self.peek_at(offset)).collect();
+        u32::from_str_radix(&hex, 16).ok().filter(|low| hex.len() == 4 && (0xDC00..0xE000).contains(low))
+    }
+
+    fn hex(&mut self) -> Option<u32> {
+        let hex: String = (0..4).filter_map(|offset| self.peek_at(offset)).collect();
+        let code = u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == 4)?;
+        (0..4).for_each(|_| { self.bump(); });
+        Some(code)
+    }
+
+    fn number(&mut self) -> Value {
+        let at = self.here();
+        let mut text = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+            text.push(c);
+            self.bump();
+        }
+        if Number::is_valid(&text) {
+            return Value::Number(Number::new(text));
+        }
+        // A signed word like `-Infinity`
+        if (text == "-" || text == "+") && self.peek().is_some_and(starts_word) {
+            return match self.word() {
+                Value::String(word) => Value::String(format!("{}{}", text, word)),
+                other => other
+            };
+        }
+
+        let normalized = normalize_number(&text);
+        if Number::is_valid(&normalized) {
+            self.note(at, format!("rewrote number {} as {}", text, normalized));
+            Value::Number(Number::new(normalized))
+        } else {
+            self.note(at, format!("quoted invalid number {}", text));
+            Value::String(text)
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_' || *c == '$') {
+            word.push(c);
+            self.bump();
+        }
+        word
+    }
+
+    fn word(&mut self) -> Value {
+        let at = self.here();
+        let word = self.identifier();
+        let (value, json) = match word.as_str() {
+            "true" | "false" | "null" => return match word.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::Null
+            },
+            "True" | "TRUE" => (Value::Bool(true), "true"),
+            "False" | "FALSE" => (Value::Bool(false), "false"),
+            "None" | "NULL" | "nil" | "undefined" | "NaN" | "Infinity" => (Value::Null, "null"),
+            _ => {
+                self.note(at, format!("quoted bare word {}", word));
+                return Value::String(word);
+            }
+        };
+
+        self.note(at, format!("replaced {} with {}", word, json));
+        value
+    }
+}
+
+fn starts_word(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+// Fixes the usual slips, `+1`, `007`, `.5`, `1.` and exponents cut short,
+// anything else is left
+fn normalize_number(text: &str) -> String {
+    let text = text.trim_end_matches(['e', 'E', '+', '-']);
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", text.strip_prefix('+').unwrap_or(text))
+    };
+    let split = digits.find(['.', 'e', 'E']).unwrap_or(digits.len());
+    let (integer, rest) = digits.split_at(split);
+
+    let integer = match integer.trim_start_matches('0') {
+        "" => "0",
+        trimmed => trimmed
+    };
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) if fraction.is_empty() || fraction.starts_with(['e', 'E']) => format!(".0{}", fraction),
+        _ => rest.to_string()
+    };
+    format!("{}{}{}", sign, integer, rest)
+}
+
+#[cfg(test)]
+mod tests;
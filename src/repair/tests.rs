@@ -0,0 +1,81 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::repair::{repair, Repair};
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn value(data: &str) -> Value {
+    Parser::new(Box::new(TokenIter::new(data))).parse()
+}
+
+fn repaired(data: &str) -> String {
+    Serializer::compact().serialize(&repair(data).0)
+}
+
+fn messages(data: &str) -> Vec<String> {
+    repair(data).1.into_iter().map(|applied| applied.message).collect()
+}
+
+#[test]
+fn test_valid_input_unchanged() {
+    let contents = std::fs::read_to_string("testdata.json").unwrap();
+    let (fixed, repairs) = repair(&contents);
+
+    assert_eq!(fixed, value(&contents));
+    assert!(repairs.is_empty());
+}
+
+#[test]
+fn test_commas() {
+    assert_eq!(repaired("[1, 2, ]"), "[1,2]");
+    assert_eq!(repaired("{\"a\": 1 \"b\": [1 2]}"), "{\"a\":1,\"b\":[1,2]}");
+    assert_eq!(repaired("[1,, 2]"), "[1,2]");
+    assert_eq!(messages("[1, 2,\n]"), vec!["removed trailing comma"]);
+    assert_eq!(repair("{\"a\": 1\n \"b\": 2}").1, vec![Repair { line: 2, column: 2, message: "inserted missing comma".to_string() }]);
+}
+
+#[test]
+fn test_keys_and_quotes() {
+    assert_eq!(repaired("{name: 'it\\'s', $id: 1, 2: \"x\"}"), "{\"name\":\"it's\",\"$id\":1,\"2\":\"x\"}");
+    assert_eq!(repaired("{'a' = 'say \"hi\"'}"), "{\"a\":\"say \\\"hi\\\"\"}");
+    assert_eq!(repaired("{\"a\" 1}"), "{\"a\":1}");
+}
+
+#[test]
+fn test_literals_and_numbers() {
+    assert_eq!(repaired("[True, FALSE, None, undefined, nil, NaN, -Infinity]"), "[true,false,null,null,null,null,null]");
+    assert_eq!(repaired("[+1, 007, .5, -.5, 1., 2e, 0x1F]"), "[1,7,0.5,-0.5,1.0,2,0,\"x1F\"]");
+    assert_eq!(messages("[bare]"), vec!["quoted bare word bare"]);
+}
+
+#[test]
+fn test_strings() {
+    assert_eq!(repaired("[\"line1\nline2\", \"tab\there\"]"), "[\"line1\\nline2\",\"tab\\there\"]");
+    assert_eq!(repaired("[\"\\q \\u00e9 \\ud83d\\ude00\"]"), "[\"\\\\q é 😀\"]");
+    assert_eq!(repaired("[\"\\ud83d\\u0041\"]"), "[\"\u{FFFD}A\"]");
+}
+
+#[test]
+fn test_truncated() {
+    assert_eq!(repaired("{\"a\": [1, {\"b\": \"tru"), "{\"a\":[1,{\"b\":\"tru\"}]}");
+    assert_eq!(repaired("{\"a\": [1, 2,"), "{\"a\":[1,2]}");
+    assert_eq!(repaired("{\"a\": "), "{\"a\":null}");
+    assert_eq!(repaired("{\"a\": [1, 2}"), "{\"a\":[1,2]}");
+    assert_eq!(messages("[{\"a\": 1]"), vec!["inserted missing } before ]"]);
+    assert_eq!(repaired(""), "null");
+}
+
+#[test]
+fn test_wrapped_documents() {
+    assert_eq!(repaired("\u{feff}// config\n{\"a\": 1 /* one */, # two\n\"b\": 2}"), "{\"a\":1,\"b\":2}");
+    assert_eq!(repaired("Sure, here it is:\n```json\n{\"a\": 1}\n```"), "{\"a\":1}");
+    assert_eq!(messages("[1] [2]"), vec!["removed text after the document"]);
+}
+
+#[test]
+fn test_colon_in_array() {
+    assert_eq!(repaired("[\"a\": 1]"), "[\"a\",1]");
+    assert_eq!(messages("[\"a\": 1]"), vec!["removed unexpected character ':'"]);
+    assert_eq!(repaired("[: 1 :]"), "[1]");
+    assert_eq!(repaired("{\"a\": [1, 2: 3], \"b\": 4}"), "{\"a\":[1,2,3],\"b\":4}");
+}
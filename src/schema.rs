@@ -0,0 +1,566 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::path::{Path, PathElement};
+use crate::pointer::Pointer;
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+// Guards against `$ref` cycles that never descend into the instance, the
+// count restarts whenever validation moves on to a child of the instance
+const MAX_REF_DEPTH: usize = 64;
+
+/// A JSON Schema (draft 2020-12) ready to validate documents.
+pub struct Schema {
+    root: Value,
+    patterns: RefCell<HashMap<String, Option<Regex>>>
+}
+
+/// A failed assertion. `schema_path` points at the keyword in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub instance_path: Path,
+    pub schema_path: Pointer,
+    pub message: String
+}
+
+impl Schema {
+    pub fn new(root: Value) -> Result<Schema, String> {
+        match root {
+            Value::Object(_) | Value::Bool(_) => Ok(Schema {
+                root,
+                patterns: RefCell::new(HashMap::new())
+            }),
+            other => Err(format!("Schema must be an object or a boolean, not {}", other.type_name()))
+        }
+    }
+
+    /// Checks `instance` against the schema, collecting every violation.
+    pub fn validate(&self, instance: &Value) -> Vec<Violation> {
+        self.check(&self.root, instance, &Path::root(), &Pointer::root(), 0)
+    }
+
+    /// Every subschema that may apply to the value at `path`, following
+    /// `$ref` and all branches of `allOf`, `anyOf` and `oneOf`. Nothing is
+    /// evaluated, which suits completion in a half written document.
+    pub fn subschemas(&self, path: &Path) -> Vec<&Value> {
+        let mut schemas = self.expand(vec![&self.root]);
+        for element in path.elements() {
+            let children = schemas.iter().filter_map(|schema| match element {
+                PathElement::Key(key) => schema.get("properties")
+                    .and_then(|properties| properties.get(key))
+                    .or_else(|| schema.get("additionalProperties")),
+                PathElement::Index(idx) => schema.get("prefixItems")
+                    .and_then(|items| items.index(*idx))
+                    .or_else(|| schema.get("items"))
+            }).collect();
+            schemas = self.expand(children);
+        }
+        schemas
+    }
+
+    // Adds whatever the schemas refer to, visiting each schema once so that
+    // reference cycles end
+    fn expand<'s>(&'s self, mut pending: Vec<&'s Value>) -> Vec<&'s Value> {
+        let mut out: Vec<&Value> = Vec::new();
+        while let Some(schema) = pending.pop() {
+            if !matches!(schema, Value::Object(_)) || out.iter().any(|seen| std::ptr::eq(*seen, schema)) {
+                continue;
+            }
+            if let Some(target) = schema.get("$ref").and_then(Value::as_str).and_then(|reference| self.resolve(reference)) {
+                pending.push(target);
+            }
+            for keyword in ["allOf", "anyOf", "oneOf"] {
+                if let Some(Value::Array(branches)) = schema.get(keyword) {
+                    pending.extend(branches.iter().rev());
+                }
+            }
+            out.push(schema);
+        }
+        out
+    }
+
+    fn is_valid(&self, schema: &Value, instance: &Value, depth: usize) -> bool {
+        self.check(schema, instance, &Path::root(), &Pointer::root(), depth).is_empty()
+    }
+
+    fn check(&self, schema: &Value, instance: &Value, path: &Path, at: &Pointer, depth: usize) -> Vec<Violation> {
+        let mut out = Vec::new();
+        let violation = |keyword: &str, message: String| Violation {
+            instance_path: path.clone(),
+            schema_path: at.child(keyword),
+            message
+        };
+
+        let keywords = match schema {
+            Value::Bool(true) => return out,
+            Value::Bool(false) => {
+                out.push(Violation {
+                    instance_path: path.clone(),
+                    schema_path: at.clone(),
+                    message: "No value is allowed here".to_string()
+                });
+                return out;
+            },
+            Value::Object(members) => members,
+            _ => return out
+        };
+
+        for (keyword, arg) in keywords {
+            match (keyword.as_str(), arg) {
+                ("$ref", Value::String(reference)) => {
+                    if depth >= MAX_REF_DEPTH {
+                        out.push(violation(keyword, format!("Reference {} nests too deeply", reference)));
+                        continue;
+                    }
+                    match self.resolve(reference) {
+                        Some(target) => out.extend(self.check(target, instance, path, &at.child(keyword), depth + 1)),
+                        None => out.push(violation(keyword, format!("Can not resolve reference {}", reference)))
+                    }
+                },
+                ("type", _) => {
+                    let types: Vec<&str> = match arg {
+                        Value::String(name) => vec![name],
+                        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                        _ => continue
+                    };
+                    if !types.iter().any(|name| is_type(instance, name)) {
+                        out.push(violation(keyword, format!("Expected {} but found {}", types.join(" or "), instance.type_name())));
+                    }
+                },
+                ("enum", Value::Array(options)) if !options.contains(instance) => {
+                    let options: Vec<String> = options.iter().map(|val| Serializer::compact().serialize(val)).collect();
+                    out.push(violation(keyword, format!("Value must be one of {}", options.join(", "))));
+                },
+                ("const", expected) if instance != expected => {
+                    out.push(violation(keyword, format!("Value must be {}", Serializer::compact().serialize(expected))));
+                },
+                ("allOf", Value::Array(schemas)) => {
+                    for (i, sub) in schemas.iter().enumerate() {
+                        out.extend(self.check(sub, instance, path, &at.child(keyword).child(&i.to_string()), depth));
+                    }
+                },
+                ("anyOf", Value::Array(schemas)) if !schemas.iter().any(|sub| self.is_valid(sub, instance, depth)) => {
+                    out.push(violation(keyword, "Value does not match any of the schemas in anyOf".to_string()));
+                },
+                ("oneOf", Value::Array(schemas)) => {
+                    let matches = schemas.iter().filter(|sub| self.is_valid(sub, instance, depth)).count();
+                    if matches != 1 {
+                        out.push(violation(keyword, format!("Value must match exactly one schema in oneOf, but matched {}", matches)));
+                    }
+                },
+                ("not", sub) if self.is_valid(sub, instance, depth) => {
+                    out.push(violation(keyword, "Value must not match the schema in not".to_string()));
+                },
+                ("if", sub) => {
+                    let branch = if self.is_valid(sub, instance, depth) { "then" } else { "else" };
+                    if let Some(branch_schema) = schema.get(branch) {
+                        out.extend(self.check(branch_schema, instance, path, &at.child(branch), depth));
+                    }
+                },
+                _ => {}
+            }
+
+            match instance {
+                Value::Number(num) => self.check_number(keyword, arg, num.as_f64(), &violation, &mut out),
+                Value::String(val) => self.check_string(keyword, arg, val, &violation, &mut out),
+                Value::Array(items) => self.check_array(keyword, arg, schema, items, path, at, &mut out),
+                Value::Object(_) => self.check_object(keyword, arg, schema, instance, path, at, &mut out),
+                _ => {}
+            }
+        }
+
+        out
+    }
+
+    fn check_number(&self, keyword: &str, arg: &Value, num: f64,
+                    violation: &dyn Fn(&str, String) -> Violation, out: &mut Vec<Violation>) {
+        let limit = match arg.as_f64() {
+            Some(limit) => limit,
+            None => return
+        };
+
+        let failed = match keyword {
+            "minimum" => num < limit,
+            "maximum" => num > limit,
+            "exclusiveMinimum" => num <= limit,
+            "exclusiveMaximum" => num >= limit,
+            "multipleOf" => limit > 0.0 && ((num / limit) - (num / limit).round()).abs() > 1e-9,
+            _ => return
+        };
+
+        if failed {
+            let message = match keyword {
+                "minimum" => format!("{} is less than the minimum of {}", num, limit),
+                "maximum" => format!("{} is greater than the maximum of {}", num, limit),
+                "exclusiveMinimum" => format!("{} must be greater than {}", num, limit),
+                "exclusiveMaximum" => format!("{} must be less than {}", num, limit),
+                _ => format!("{} is not a multiple of {}", num, limit)
+            };
+            out.push(violation(keyword, message));
+        }
+    }
+
+    fn check_string(&self, keyword: &str, arg: &Value, val: &str,
+                    violation: &dyn Fn(&str, String) -> Violation, out: &mut Vec<Violation>) {
+        let len = val.chars().count();
+
+        match (keyword, arg) {
+            ("minLength", Value::Number(limit)) if (len as f64) < limit.as_f64() => {
+                out.push(violation(keyword, format!("String is shorter than {} characters", limit)));
+            },
+            ("maxLength", Value::Number(limit)) if (len as f64) > limit.as_f64() => {
+                out.push(violation(keyword, format!("String is longer than {} characters", limit)));
+            },
+            ("pattern", Value::String(pattern)) => {
+                let mut patterns = self.patterns.borrow_mut();
+                let regex = patterns.entry(pattern.clone()).or_insert_with(|| Regex::new(pattern).ok());
+                match regex {
+                    Some(regex) if !regex.is_match(val) => {
+                        out.push(violation(keyword, format!("String does not match pattern {}", pattern)));
+                    },
+                    None => out.push(violation(keyword, format!("Invalid pattern {}", pattern))),
+                    _ => {}
+                }
+            },
+            ("format", Value::String(format)) if !check_format(format, val) => {
+                out.push(violation(keyword, format!("String is not a valid {}", format)));
+            },
+            _ => {}
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_array(&self, keyword: &str, arg: &Value, schema: &Value, items: &[Value],
+                   path: &Path, at: &Pointer, out: &mut Vec<Violation>) {
+        let here = at.child(keyword);
+
+        match (keyword, arg) {
+            ("prefixItems", Value::Array(schemas)) => {
+                for (i, (sub, item)) in schemas.iter().zip(items.iter()).enumerate() {
+                    out.extend(self.check(sub, item, &path.index(i), &here.child(&i.to_string()), 0));
+                }
+            },
+            ("items", sub) => {
+                let skip = match schema.get("prefixItems") {
+                    Some(Value::Array(prefix)) => prefix.len(),
+                    _ => 0
+                };
+                for (i, item) in items.iter().enumerate().skip(skip) {
+                    out.extend(self.check(sub, item, &path.index(i), &here, 0));
+                }
+            },
+            ("minItems", Value::Number(limit)) if (items.len() as f64) < limit.as_f64() => {
+                out.push(Violation {
+                    instance_path: path.clone(),
+                    schema_path: here,
+                    message: format!("Array has fewer than {} items", limit)
+                });
+            },
+            ("maxItems", Value::Number(limit)) if (items.len() as f64) > limit.as_f64() => {
+                out.push(Violation {
+                    instance_path: path.clone(),
+                    schema_path: here,
+                    message: format!("Array has more than {} items", limit)
+                });
+            },
+            ("uniqueItems", Value::Bool(true)) => {
+                for (i, item) in items.iter().enumerate() {
+                    if items[..i].contains(item) {
+                        out.push(Violation {
+                            instance_path: path.index(i),
+                            schema_path: here.clone(),
+                            message: "Array items must be unique".to_string()
+                        });
+                    }
+                }
+            },
+            ("contains", sub) => {
+                let found = items.iter().filter(|item| self.is_valid(sub, item, 0)).count();
+                let min = schema.get("minContains").and_then(Value::as_f64).unwrap_or(1.0);
+                let max = schema.get("maxContains").and_then(Value::as_f64);
+                if (found as f64) < min || max.is_some_and(|max| found as f64 > max) {
+                    out.push(Violation {
+                        instance_path: path.clone(),
+                        schema_path: here,
+                        message: format!("Array contains {} matching items", found)
+                    });
+                }
+            },
+            _ => {}
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_object(&self, keyword: &str, arg: &Value, schema: &Value, instance: &Value,
+                    path: &Path, at: &Pointer, out: &mut Vec<Violation>) {
+        let here = at.child(keyword);
+        let members = match instance {
+            Value::Object(members) => members,
+            _ => return
+        };
+
+        match (keyword, arg) {
+            ("properties", Value::Object(properties)) => {
+                for (name, sub) in properties {
+                    if let Some(val) = instance.get(name) {
+                        out.extend(self.check(sub, val, &path.key(name), &here.child(name), 0));
+                    }
+                }
+            },
+            ("patternProperties", Value::Object(patterns)) => {
+                for (pattern, sub) in patterns {
+                    if let Ok(regex) = Regex::new(pattern) {
+                        for (name, val) in members.iter().filter(|(name, _)| regex.is_match(name)) {
+                            out.extend(self.check(sub, val, &path.key(name), &here.child(pattern), 0));
+                        }
+                    }
+                }
+            },
+            ("additionalProperties", sub) => {
+                let patterns: Vec<Regex> = match schema.get("patternProperties") {
+                    Some(Value::Object(patterns)) => patterns.iter()
+                        .filter_map(|(pattern, _)| Regex::new(pattern).ok())
+                        .collect(),
+                    _ => Vec::new()
+                };
+                for (name, val) in members {
+                    let declared = schema.get("properties").and_then(|props| props.get(name)).is_some() ||
+                        patterns.iter().any(|regex| regex.is_match(name));
+                    if declared {
+                        continue;
+                    }
+                    if let Value::Bool(false) = sub {
+                        out.push(Violation {
+                            instance_path: path.key(name),
+                            schema_path: here.clone(),
+                            message: format!("Property {} is not allowed", name)
+                        });
+                    } else {
+                        out.extend(self.check(sub, val, &path.key(name), &here, 0));
+                    }
+                }
+            },
+            ("required", Value::Array(names)) => {
+                for name in names.iter().filter_map(Value::as_str) {
+                    if instance.get(name).is_none() {
+                        out.push(Violation {
+                            instance_path: path.clone(),
+                            schema_path: here.clone(),
+                            message: format!("Missing required property {}", name)
+                        });
+                    }
+                }
+            },
+            ("minProperties", Value::Number(limit)) if (members.len() as f64) < limit.as_f64() => {
+                out.push(Violation {
+                    instance_path: path.clone(),
+                    schema_path: here,
+                    message: format!("Object has fewer than {} properties", limit)
+                });
+            },
+            ("maxProperties", Value::Number(limit)) if (members.len() as f64) > limit.as_f64() => {
+                out.push(Violation {
+                    instance_path: path.clone(),
+                    schema_path: here,
+                    message: format!("Object has more than {} properties", limit)
+                });
+            },
+            _ => {}
+        }
+    }
+
+    /// Resolves a local reference: `#`, a JSON pointer fragment or an `$anchor`.
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        let fragment = reference.strip_prefix('#')?;
+
+        if fragment.is_empty() {
+            Some(&self.root)
+        } else if fragment.starts_with('/') {
+            Pointer::parse(&percent_decode(fragment)).ok()?.resolve(&self.root)
+        } else {
+            find_anchor(&self.root, fragment)
+        }
+    }
+}
+
+fn find_anchor<'v>(schema: &'v Value, anchor: &str) -> Option<&'v Value> {
+    match schema {
+        Value::Object(members) => {
+            if schema.get("$anchor").and_then(Value::as_str) == Some(anchor) {
+                return Some(schema);
+            }
+            members.iter().find_map(|(_, val)| find_anchor(val, anchor))
+        },
+        Value::Array(items) => items.iter().find_map(|item| find_anchor(item, anchor)),
+        _ => None
+    }
+}
+
+pub(crate) fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::new();
+    let raw = text.as_bytes();
+    let mut i = 0;
+
+    while i < raw.len() {
+        if raw[i] == b'%' && i + 2 < raw.len() {
+            let hex = std::str::from_utf8(&raw[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                bytes.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        bytes.push(raw[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn is_type(instance: &Value, name: &str) -> bool {
+    match (name, instance) {
+        ("null", Value::Null) => true,
+        ("boolean", Value::Bool(_)) => true,
+        ("number", Value::Number(_)) => true,
+        ("integer", Value::Number(num)) => num.as_f64().fract() == 0.0,
+        ("string", Value::String(_)) => true,
+        ("array", Value::Array(_)) => true,
+        ("object", Value::Object(_)) => true,
+        _ => false
+    }
+}
+
+/// Checks a string against one of the well known `format` names. Unknown
+/// formats always pass.
+pub fn check_format(format: &str, val: &str) -> bool {
+    match format {
+        "date" => is_date(val),
+        "time" => is_time(val),
+        "date-time" => {
+            let mut parts = val.splitn(2, ['T', 't']);
+            match (parts.next(), parts.next()) {
+                (Some(date), Some(time)) => is_date(date) && is_time(time),
+                _ => false
+            }
+        },
+        "email" => {
+            let mut parts = val.rsplitn(2, '@');
+            match (parts.next(), parts.next()) {
+                (Some(domain), Some(local)) => !local.is_empty() &&
+                    !local.contains('@') &&
+                    domain.contains('.') &&
+                    !domain.starts_with('.') &&
+                    !domain.ends_with('.') &&
+                    !val.contains(char::is_whitespace),
+                _ => false
+            }
+        },
+        "uri" => {
+            match val.find(':') {
+                Some(idx) if idx > 0 => {
+                    let scheme = &val[..idx];
+                    scheme.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+                        scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.') &&
+                        idx + 1 < val.len() &&
+                        !val.contains(char::is_whitespace)
+                },
+                _ => false
+            }
+        },
+        "ipv4" => {
+            let parts: Vec<&str> = val.split('.').collect();
+            parts.len() == 4 && parts.iter().all(|part| {
+                !part.is_empty() && part.len() <= 3 &&
+                    part.chars().all(|c| c.is_ascii_digit()) &&
+                    !(part.len() > 1 && part.starts_with('0')) &&
+                    part.parse::<u16>().is_ok_and(|num| num <= 255)
+            })
+        },
+        "uuid" => {
+            let groups: Vec<&str> = val.split('-').collect();
+            groups.len() == 5 &&
+                groups.iter().zip([8, 4, 4, 4, 12].iter()).all(|(group, len)| {
+                    group.len() == *len && group.chars().all(|c| c.is_ascii_hexdigit())
+                })
+        },
+        _ => true
+    }
+}
+
+fn digits(val: &str, len: usize) -> Option<u32> {
+    if val.len() == len && val.chars().all(|c| c.is_ascii_digit()) {
+        val.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_date(val: &str) -> bool {
+    let parts: Vec<&str> = val.split('-').collect();
+    if parts.len() != 3 {
+        return false;
+    }
+
+    let (year, month, day) = match (digits(parts[0], 4), digits(parts[1], 2), digits(parts[2], 2)) {
+        (Some(year), Some(month), Some(day)) => (year, month, day),
+        _ => return false
+    };
+
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false
+    };
+
+    day >= 1 && day <= days
+}
+
+fn is_time(val: &str) -> bool {
+    // Split off the offset, either `Z` or `+hh:mm` / `-hh:mm`
+    let split = val.len().checked_sub(6).filter(|idx| *idx > 0 && val.is_char_boundary(*idx));
+    let (time, offset) = if let Some(time) = val.strip_suffix(|c| c == 'Z' || c == 'z') {
+        (time, None)
+    } else if let Some(idx) = split.filter(|idx| val[*idx..].starts_with(['+', '-'])) {
+        (&val[..idx], Some(&val[idx + 1..]))
+    } else {
+        return false;
+    };
+
+    if let Some(offset) = offset {
+        let parts: Vec<&str> = offset.split(':').collect();
+        match (parts.first().and_then(|h| digits(h, 2)), parts.get(1).and_then(|m| digits(m, 2))) {
+            (Some(hour), Some(minute)) if parts.len() == 2 && hour < 24 && minute < 60 => {},
+            _ => return false
+        }
+    }
+
+    let (time, fraction) = match time.find('.') {
+        Some(idx) => (&time[..idx], Some(&time[idx + 1..])),
+        None => (time, None)
+    };
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return false;
+        }
+    }
+
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() != 3 {
+        return false;
+    }
+
+    match (digits(parts[0], 2), digits(parts[1], 2), digits(parts[2], 2)) {
+        // Allow a leap second
+        (Some(hour), Some(minute), Some(second)) => hour < 24 && minute < 60 && second <= 60,
+        _ => false
+    }
+}
+
+#[cfg(test)]
+mod tests;
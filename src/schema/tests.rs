@@ -0,0 +1,155 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::path::Path;
+use crate::schema::{check_format, Schema};
+use crate::serializer::Serializer;
+use crate::value::Value;
+
+fn parse(data: &str) -> Value {
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    parser.parse()
+}
+
+fn violations(schema: &str, instance: &str) -> Vec<(String, String)> {
+    Schema::new(parse(schema)).unwrap()
+        .validate(&parse(instance))
+        .iter()
+        .map(|violation| (violation.instance_path.to_string(), violation.schema_path.to_string()))
+        .collect()
+}
+
+const PERSON: &str = "{
+    \"type\": \"object\",
+    \"required\": [\"name\", \"dob\"],
+    \"properties\": {
+        \"name\": { \"type\": \"string\", \"minLength\": 1 },
+        \"dob\": { \"type\": \"string\", \"format\": \"date\" },
+        \"score\": { \"type\": \"number\", \"minimum\": 0, \"exclusiveMaximum\": 5 },
+        \"pets\": { \"type\": \"array\", \"items\": { \"$ref\": \"#/$defs/pet\" }, \"maxItems\": 2 },
+        \"email\": { \"type\": \"string\", \"format\": \"email\", \"pattern\": \"\\\\.com$\" }
+    },
+    \"additionalProperties\": false,
+    \"$defs\": {
+        \"pet\": { \"type\": \"string\", \"enum\": [\"Ziggy\", \"Sasha\"] }
+    }
+}";
+
+#[test]
+fn test_valid_document() {
+    let instance = "{\"name\": \"Coletta Ortiz\", \"dob\": \"2016-10-16\", \"score\": 4.1, \"pets\": [\"Ziggy\", \"Sasha\"], \"email\": \"hsiu@magnificent.com\"}";
+
+    assert!(violations(PERSON, instance).is_empty());
+}
+
+#[test]
+fn test_violation_locations() {
+    let instance = "{\"name\": \"\", \"dob\": \"2016-02-30\", \"score\": 5, \"pets\": [\"Rex\"], \"extra\": 1, \"email\": \"a@b.org\"}";
+
+    assert_eq!(violations(PERSON, instance), vec![
+        ("$.name".to_string(), "/properties/name/minLength".to_string()),
+        ("$.dob".to_string(), "/properties/dob/format".to_string()),
+        ("$.score".to_string(), "/properties/score/exclusiveMaximum".to_string()),
+        ("$.pets[0]".to_string(), "/properties/pets/items/$ref/enum".to_string()),
+        ("$.email".to_string(), "/properties/email/pattern".to_string()),
+        ("$.extra".to_string(), "/additionalProperties".to_string()),
+    ]);
+}
+
+#[test]
+fn test_required_and_types() {
+    assert_eq!(violations(PERSON, "{\"name\": 1}"), vec![
+        ("$".to_string(), "/required".to_string()),
+        ("$.name".to_string(), "/properties/name/type".to_string()),
+    ]);
+    assert!(violations("{\"type\": \"integer\"}", "1.0").is_empty());
+    assert_eq!(violations("{\"type\": [\"string\", \"null\"]}", "1").len(), 1);
+    assert_eq!(violations("{\"const\": {\"a\": [1]}}", "{\"a\": [1.0]}").len(), 0);
+}
+
+#[test]
+fn test_combinators() {
+    let schema = "{\"anyOf\": [{\"type\": \"string\"}, {\"type\": \"number\"}]}";
+    assert!(violations(schema, "1").is_empty());
+    assert_eq!(violations(schema, "null"), vec![("$".to_string(), "/anyOf".to_string())]);
+
+    let schema = "{\"oneOf\": [{\"minimum\": 1}, {\"maximum\": 10}]}";
+    assert!(violations(schema, "0").is_empty());
+    assert_eq!(violations(schema, "5"), vec![("$".to_string(), "/oneOf".to_string())]);
+
+    let schema = "{\"allOf\": [{\"required\": [\"a\"]}, {\"not\": {\"required\": [\"b\"]}}]}";
+    assert_eq!(violations(schema, "{\"b\": 1}"), vec![
+        ("$".to_string(), "/allOf/0/required".to_string()),
+        ("$".to_string(), "/allOf/1/not".to_string()),
+    ]);
+}
+
+#[test]
+fn test_references() {
+    let schema = "{\"$defs\": {\"node\": {\"$anchor\": \"node\", \"type\": \"object\", \"properties\": {\"next\": {\"$ref\": \"#node\"}}}}, \"$ref\": \"#/$defs/node\"}";
+    assert!(violations(schema, "{\"next\": {\"next\": {}}}").is_empty());
+    assert_eq!(violations(schema, "{\"next\": {\"next\": 1}}"), vec![
+        ("$.next.next".to_string(), "/$ref/properties/next/$ref/properties/next/$ref/type".to_string()),
+    ]);
+
+    // Each reference that descends into the instance starts a fresh count
+    let tree = "{\"$defs\": {\"n\": {\"type\": \"object\", \"properties\": {\"c\": {\"$ref\": \"#/$defs/n\"}}}}, \"$ref\": \"#/$defs/n\"}";
+    assert!(violations(tree, &format!("{}{{}}{}", "{\"c\": ".repeat(200), "}".repeat(200))).is_empty());
+    assert_eq!(violations(tree, &format!("{}1{}", "{\"c\": ".repeat(100), "}".repeat(100))).len(), 1);
+
+    assert_eq!(violations("{\"$ref\": \"#\"}", "1").len(), 1);
+    assert_eq!(violations("{\"$ref\": \"other.json\"}", "1").len(), 1);
+}
+
+#[test]
+fn test_boolean_schemas() {
+    assert!(violations("true", "1").is_empty());
+    assert_eq!(violations("false", "1"), vec![("$".to_string(), "".to_string())]);
+    assert!(Schema::new(parse("1")).is_err());
+}
+
+#[test]
+fn test_formats() {
+    let table = [
+        ("date", "2016-10-16", true),
+        ("date", "2000-02-29", true),
+        ("date", "1900-02-29", false),
+        ("date", "16-10-2016", false),
+        ("date-time", "2016-10-16T08:30:00.5+01:00", true),
+        ("date-time", "2016-10-16 08:30:00Z", false),
+        ("time", "23:59:60Z", true),
+        ("time", "€a€", false),
+        ("time", "12:00:00€+01:00", false),
+        ("date-time", "2020-01-01T€a€", false),
+        ("email", "hsiu.gaytan3350@magnificent.com", true),
+        ("email", "not an email", false),
+        ("uri", "http://www.coordinate.com", true),
+        ("uri", "www.coordinate.com", false),
+        ("ipv4", "192.168.0.1", true),
+        ("ipv4", "256.1.1.1", false),
+        ("uuid", "07fc61f4-bfe7-4ec0-a9d2-413f9f906fd2", true),
+        ("unknown", "anything", true),
+    ];
+
+    for (format, val, expected) in table.iter() {
+        assert_eq!(check_format(format, val), *expected, "{} as {}", val, format);
+    }
+}
+
+#[test]
+fn test_subschemas() {
+    let schema = Schema::new(parse(PERSON)).unwrap();
+    let descriptions = |path: &Path| -> Vec<String> {
+        schema.subschemas(path).iter().map(|sub| Serializer::compact().serialize(sub)).collect()
+    };
+
+    assert_eq!(schema.subschemas(&Path::root()).len(), 1);
+    assert_eq!(descriptions(&Path::root().key("name")), vec!["{\"type\":\"string\",\"minLength\":1}"]);
+    // Through `items` and the `$ref` it holds
+    assert_eq!(schema.subschemas(&Path::root().key("pets").index(0)).len(), 2);
+    // `additionalProperties: false` allows nothing to complete
+    assert!(schema.subschemas(&Path::root().key("other")).is_empty());
+
+    let cyclic = Schema::new(parse("{\"$defs\": {\"a\": {\"$ref\": \"#\"}}, \"anyOf\": [{\"$ref\": \"#/$defs/a\"}, {\"properties\": {\"x\": {}}}]}")).unwrap();
+    assert_eq!(cyclic.subschemas(&Path::root()).len(), 4);
+    assert_eq!(cyclic.subschemas(&Path::root().key("x")).len(), 1);
+}
@@ -0,0 +1,689 @@
+use std::io;
+
+use serde::ser::{self, Serialize};
+
+use crate::de::Error;
+use crate::serializer::write_string;
+use crate::value::{Number, Value};
+
+/// Converts any serializable value into a value tree.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    value.serialize(ValueSerializer)
+}
+
+/// Serializes a value as compact JSON text.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let mut serializer = Serializer::new(Vec::new());
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(serializer.into_inner()).expect("JSON text is UTF-8"))
+}
+
+/// Serializes a value as JSON text indented by two spaces.
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let mut serializer = Serializer::pretty(Vec::new());
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(serializer.into_inner()).expect("JSON text is UTF-8"))
+}
+
+/// Serializes a value as compact JSON text into `writer`.
+pub fn to_writer<W: io::Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<(), Error> {
+    value.serialize(&mut Serializer::new(writer))
+}
+
+/// A serde serializer writing JSON text straight into `writer`, laid out
+/// the same way as our own `Serializer` lays out a value tree.
+pub struct Serializer<W> {
+    writer: W,
+    indent: Option<&'static str>,
+    depth: usize
+}
+
+impl<W: io::Write> Serializer<W> {
+    /// Output without any insignificant whitespace.
+    pub fn new(writer: W) -> Serializer<W> {
+        Serializer { writer, indent: None, depth: 0 }
+    }
+
+    /// Pretty printed output, indented with two spaces per level.
+    pub fn pretty(writer: W) -> Serializer<W> {
+        Serializer { writer, indent: Some("  "), depth: 0 }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), Error> {
+        self.writer.write_all(text.as_bytes()).map_err(ser::Error::custom)
+    }
+
+    fn newline(&mut self) -> Result<(), Error> {
+        if let Some(indent) = self.indent {
+            self.write("\n")?;
+            for _ in 0..self.depth {
+                self.write(indent)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, bracket: &str) -> Result<(), Error> {
+        self.write(bracket)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    // Empty containers close on the same line, like `[]`
+    fn close(&mut self, bracket: &str, empty: bool) -> Result<(), Error> {
+        self.depth -= 1;
+        if !empty {
+            self.newline()?;
+        }
+        self.write(bracket)
+    }
+
+    fn item(&mut self, first: bool) -> Result<(), Error> {
+        if !first {
+            self.write(",")?;
+        }
+        self.newline()
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), Error> {
+        let mut out = String::new();
+        write_string(&mut out, key);
+        out.push(':');
+        if self.indent.is_some() {
+            out.push(' ');
+        }
+        self.write(&out)
+    }
+
+    // Variants are written as an object with the variant name as its key
+    fn variant(&mut self, variant: &str) -> Result<(), Error> {
+        self.open("{")?;
+        self.item(true)?;
+        self.key(variant)
+    }
+
+    fn compound(&mut self, bracket: &str, variant: Option<&str>) -> Result<Compound<'_, W>, Error> {
+        if let Some(variant) = variant {
+            self.variant(variant)?;
+        }
+        self.open(bracket)?;
+        Ok(Compound { serializer: self, first: true, variant: variant.is_some() })
+    }
+}
+
+impl<'a, W: io::Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, val: bool) -> Result<(), Error> {
+        self.write(if val { "true" } else { "false" })
+    }
+
+    fn serialize_i8(self, val: i8) -> Result<(), Error> {
+        self.serialize_i64(val as i64)
+    }
+
+    fn serialize_i16(self, val: i16) -> Result<(), Error> {
+        self.serialize_i64(val as i64)
+    }
+
+    fn serialize_i32(self, val: i32) -> Result<(), Error> {
+        self.serialize_i64(val as i64)
+    }
+
+    fn serialize_i64(self, val: i64) -> Result<(), Error> {
+        self.write(&val.to_string())
+    }
+
+    fn serialize_i128(self, val: i128) -> Result<(), Error> {
+        self.write(&val.to_string())
+    }
+
+    fn serialize_u8(self, val: u8) -> Result<(), Error> {
+        self.serialize_u64(val as u64)
+    }
+
+    fn serialize_u16(self, val: u16) -> Result<(), Error> {
+        self.serialize_u64(val as u64)
+    }
+
+    fn serialize_u32(self, val: u32) -> Result<(), Error> {
+        self.serialize_u64(val as u64)
+    }
+
+    fn serialize_u64(self, val: u64) -> Result<(), Error> {
+        self.write(&val.to_string())
+    }
+
+    fn serialize_u128(self, val: u128) -> Result<(), Error> {
+        self.write(&val.to_string())
+    }
+
+    fn serialize_f32(self, val: f32) -> Result<(), Error> {
+        self.serialize_f64(val as f64)
+    }
+
+    // JSON has no NaN or infinity, like most encoders they become null
+    fn serialize_f64(self, val: f64) -> Result<(), Error> {
+        if val.is_finite() {
+            self.write(&format!("{:?}", val))
+        } else {
+            self.write("null")
+        }
+    }
+
+    fn serialize_char(self, val: char) -> Result<(), Error> {
+        self.serialize_str(val.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, val: &str) -> Result<(), Error> {
+        let mut out = String::with_capacity(val.len() + 2);
+        write_string(&mut out, val);
+        self.write(&out)
+    }
+
+    fn serialize_bytes(self, val: &[u8]) -> Result<(), Error> {
+        let mut seq = self.compound("[", None)?;
+        for byte in val {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.write("null")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.write("null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.write("null")
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32,
+                                                       variant: &'static str, value: &T) -> Result<(), Error> {
+        self.variant(variant)?;
+        value.serialize(&mut *self)?;
+        self.close("}", false)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        self.compound("[", None)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, W>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str,
+                               _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.compound("[", Some(variant))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        self.compound("{", None)
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str,
+                                _len: usize) -> Result<Compound<'a, W>, Error> {
+        self.compound("{", Some(variant))
+    }
+}
+
+/// Writes the items of an array or the members of an object, closing the
+/// object around it for tuple and struct variants.
+pub struct Compound<'a, W> {
+    serializer: &'a mut Serializer<W>,
+    first: bool,
+    variant: bool
+}
+
+impl<W: io::Write> Compound<'_, W> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serializer.item(self.first)?;
+        self.first = false;
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), Error> {
+        self.serializer.item(self.first)?;
+        self.first = false;
+        self.serializer.key(name)?;
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn finish(self, bracket: &str) -> Result<(), Error> {
+        self.serializer.close(bracket, self.first)?;
+        if self.variant {
+            self.serializer.close("}", false)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: io::Write> ser::SerializeSeq for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("]")
+    }
+}
+
+impl<W: io::Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = self::key(to_value(key)?)?;
+        self.serializer.item(self.first)?;
+        self.first = false;
+        self.serializer.key(&key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("}")
+    }
+}
+
+impl<W: io::Write> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), Error> {
+        self.field(name, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("}")
+    }
+}
+
+impl<W: io::Write> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), Error> {
+        self.field(name, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish("}")
+    }
+}
+
+/// A serde serializer producing a `Value`, for callers that want the tree
+/// rather than text.
+pub struct ValueSerializer;
+
+fn number(text: String) -> Value {
+    Value::Number(Number::new(text))
+}
+
+// Map keys must come out as strings, numbers and booleans are converted
+fn key(value: Value) -> Result<String, Error> {
+    match value {
+        Value::String(val) => Ok(val),
+        Value::Number(num) => Ok(num.as_str().to_string()),
+        Value::Bool(val) => Ok(val.to_string()),
+        other => Err(ser::Error::custom(format!("map key must be a string, found {}", other.type_name())))
+    }
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, val: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(val))
+    }
+
+    fn serialize_i8(self, val: i8) -> Result<Value, Error> {
+        self.serialize_i64(val as i64)
+    }
+
+    fn serialize_i16(self, val: i16) -> Result<Value, Error> {
+        self.serialize_i64(val as i64)
+    }
+
+    fn serialize_i32(self, val: i32) -> Result<Value, Error> {
+        self.serialize_i64(val as i64)
+    }
+
+    fn serialize_i64(self, val: i64) -> Result<Value, Error> {
+        Ok(number(val.to_string()))
+    }
+
+    fn serialize_i128(self, val: i128) -> Result<Value, Error> {
+        Ok(number(val.to_string()))
+    }
+
+    fn serialize_u8(self, val: u8) -> Result<Value, Error> {
+        self.serialize_u64(val as u64)
+    }
+
+    fn serialize_u16(self, val: u16) -> Result<Value, Error> {
+        self.serialize_u64(val as u64)
+    }
+
+    fn serialize_u32(self, val: u32) -> Result<Value, Error> {
+        self.serialize_u64(val as u64)
+    }
+
+    fn serialize_u64(self, val: u64) -> Result<Value, Error> {
+        Ok(number(val.to_string()))
+    }
+
+    fn serialize_u128(self, val: u128) -> Result<Value, Error> {
+        Ok(number(val.to_string()))
+    }
+
+    fn serialize_f32(self, val: f32) -> Result<Value, Error> {
+        self.serialize_f64(val as f64)
+    }
+
+    // JSON has no NaN or infinity, like most encoders they become null
+    fn serialize_f64(self, val: f64) -> Result<Value, Error> {
+        if val.is_finite() {
+            Ok(number(format!("{:?}", val)))
+        } else {
+            Ok(Value::Null)
+        }
+    }
+
+    fn serialize_char(self, val: char) -> Result<Value, Error> {
+        Ok(Value::String(val.to_string()))
+    }
+
+    fn serialize_str(self, val: &str) -> Result<Value, Error> {
+        Ok(Value::from(val))
+    }
+
+    fn serialize_bytes(self, val: &[u8]) -> Result<Value, Error> {
+        Ok(Value::Array(val.iter().map(|byte| number(byte.to_string())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Value, Error> {
+        Ok(Value::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32,
+                                                       variant: &'static str, value: &T) -> Result<Value, Error> {
+        Ok(Value::Object(vec![(variant.to_string(), to_value(value)?)]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray { variant: None, items: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str,
+                               len: usize) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray { variant: Some(variant), items: Vec::with_capacity(len) })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject { variant: None, members: Vec::new(), key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str,
+                                _len: usize) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject { variant: Some(variant), members: Vec::new(), key: None })
+    }
+}
+
+/// Collects array items, wrapped in an object for tuple variants.
+pub struct SerializeArray {
+    variant: Option<&'static str>,
+    items: Vec<Value>
+}
+
+impl SerializeArray {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let array = Value::Array(self.items);
+        Ok(match self.variant {
+            Some(variant) => Value::Object(vec![(variant.to_string(), array)]),
+            None => array
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+/// Collects object members, wrapped in an object for struct variants.
+pub struct SerializeObject {
+    variant: Option<&'static str>,
+    members: Vec<(String, Value)>,
+    key: Option<String>
+}
+
+impl SerializeObject {
+    fn finish(self) -> Result<Value, Error> {
+        let object = Value::Object(self.members);
+        Ok(match self.variant {
+            Some(variant) => Value::Object(vec![(variant.to_string(), object)]),
+            None => object
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(self::key(to_value(key)?)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().ok_or_else(|| ser::Error::custom("map value serialized before its key"))?;
+        self.members.push((key, to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), Error> {
+        self.members.push((name.to_string(), to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), Error> {
+        self.members.push((name.to_string(), to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
@@ -0,0 +1,116 @@
+use crate::value::Value;
+
+pub struct Serializer {
+    indent: Option<String>
+}
+
+impl Serializer {
+    /// Pretty printed output, indented with two spaces per level.
+    pub fn new() -> Serializer {
+        Serializer {
+            indent: Some("  ".to_string())
+        }
+    }
+
+    /// Output without any insignificant whitespace.
+    pub fn compact() -> Serializer {
+        Serializer {
+            indent: None
+        }
+    }
+
+    pub fn indent(mut self, indent: &str) -> Serializer {
+        self.indent = Some(indent.to_string());
+        self
+    }
+
+    pub fn serialize(&self, value: &Value) -> String {
+        let mut out = String::new();
+        self.write_value(&mut out, value, 0);
+        out
+    }
+
+    fn write_value(&self, out: &mut String, value: &Value, depth: usize) {
+        match value {
+            Value::Null => out.push_str("null"),
+            Value::Bool(val) => out.push_str(if *val { "true" } else { "false" }),
+            Value::Number(val) => out.push_str(val.as_str()),
+            Value::String(val) => write_string(out, val),
+            Value::Array(items) => {
+                if items.is_empty() {
+                    out.push_str("[]");
+                    return;
+                }
+
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.newline(out, depth + 1);
+                    self.write_value(out, item, depth + 1);
+                }
+                self.newline(out, depth);
+                out.push(']');
+            },
+            Value::Object(members) => {
+                if members.is_empty() {
+                    out.push_str("{}");
+                    return;
+                }
+
+                out.push('{');
+                for (i, (key, val)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.newline(out, depth + 1);
+                    write_string(out, key);
+                    out.push(':');
+                    if self.indent.is_some() {
+                        out.push(' ');
+                    }
+                    self.write_value(out, val, depth + 1);
+                }
+                self.newline(out, depth);
+                out.push('}');
+            }
+        }
+    }
+
+    fn newline(&self, out: &mut String, depth: usize) {
+        if let Some(indent) = &self.indent {
+            out.push('\n');
+            for _ in 0..depth {
+                out.push_str(indent);
+            }
+        }
+    }
+}
+
+impl Default for Serializer {
+    fn default() -> Serializer {
+        Serializer::new()
+    }
+}
+
+pub fn write_string(out: &mut String, val: &str) {
+    out.push('"');
+    for c in val.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,35 @@
+use crate::lexer::TokenIter;
+use crate::parser::Parser;
+use crate::serializer::Serializer;
+
+#[test]
+fn test_compact_round_trip() {
+    let data = "{ \"a\" : [1, -2.5e+3, true, null], \"b\\\"c\": { } , \"d\": [ ] }";
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    let value = parser.parse();
+
+    assert_eq!(Serializer::compact().serialize(&value), "{\"a\":[1,-2.5e+3,true,null],\"b\\\"c\":{},\"d\":[]}");
+}
+
+#[test]
+fn test_pretty_output() {
+    let data = "{\"a\": [1, {\"b\": \"tab\\there\"}]}";
+    let mut parser = Parser::new(Box::new(TokenIter::new(data)));
+    let value = parser.parse();
+
+    assert_eq!(Serializer::new().serialize(&value), "{
+  \"a\": [
+    1,
+    {
+      \"b\": \"tab\\there\"
+    }
+  ]
+}");
+}
+
+#[test]
+fn test_escapes_control_characters() {
+    let value = crate::value::Value::from("\u{01}\u{08}\\");
+
+    assert_eq!(Serializer::compact().serialize(&value), "\"\\u0001\\b\\\\\"");
+}
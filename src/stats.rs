@@ -0,0 +1,262 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::lexer::{JsonKind, Token, TokenIter};
+use crate::parser::{decode_escapes, ParseError, Parser};
+use crate::path::Path;
+use crate::serializer::Serializer;
+use crate::value::{Number, Value};
+
+// How many of the largest arrays and strings are kept
+const LARGEST: usize = 5;
+
+/// Statistics about a document. Lengths count items for arrays and
+/// characters for strings, sizes are in bytes of the source text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub bytes: usize,
+    // The deepest nesting of objects and arrays, 0 for a lone scalar
+    pub max_depth: usize,
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    pub keys: usize,
+    // Keys repeated within the same object
+    pub duplicate_keys: usize,
+    // Most frequent first, ties by name
+    pub key_frequency: Vec<(String, usize)>,
+    pub largest_arrays: Vec<(Path, usize)>,
+    pub largest_strings: Vec<(Path, usize)>,
+    // Each child of the root container, in document order
+    pub subtrees: Vec<(Path, usize)>
+}
+
+/// Collects statistics in a single pass over the tokens while the parser
+/// validates them, without building a value tree.
+pub fn stats(contents: &str) -> Result<Stats, ParseError> {
+    let collector = RefCell::new(Collector::default());
+
+    let tokens = TokenIter::new(contents).checked().inspect(|token| {
+        if let Ok(token) = token {
+            collector.borrow_mut().token(token);
+        }
+    });
+    Parser::checked(Box::new(tokens)).try_validate()?;
+
+    Ok(collector.into_inner().finish())
+}
+
+impl Stats {
+    pub fn to_value(&self) -> Value {
+        let number = |val: usize| Value::Number(Number::from(val));
+        let located = |entries: &[(Path, usize)]| Value::Array(entries.iter()
+            .map(|(path, size)| Value::Object(vec![("path".to_string(), Value::from(path.to_string().as_str())), ("size".to_string(), number(*size))]))
+            .collect());
+
+        Value::Object(vec![
+            ("bytes".to_string(), number(self.bytes)),
+            ("maxDepth".to_string(), number(self.max_depth)),
+            ("types".to_string(), Value::Object(vec![
+                ("object".to_string(), number(self.objects)),
+                ("array".to_string(), number(self.arrays)),
+                ("string".to_string(), number(self.strings)),
+                ("number".to_string(), number(self.numbers)),
+                ("boolean".to_string(), number(self.booleans)),
+                ("null".to_string(), number(self.nulls))
+            ])),
+            ("keys".to_string(), number(self.keys)),
+            ("duplicateKeys".to_string(), number(self.duplicate_keys)),
+            ("keyFrequency".to_string(), Value::Object(self.key_frequency.iter()
+                .map(|(key, count)| (key.clone(), number(*count)))
+                .collect())),
+            ("largestArrays".to_string(), located(&self.largest_arrays)),
+            ("largestStrings".to_string(), located(&self.largest_strings)),
+            ("subtrees".to_string(), located(&self.subtrees))
+        ])
+    }
+
+    /// A report for people, with the `top` most frequent keys as a histogram.
+    pub fn report(&self, top: usize) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("Size: {} bytes\n", self.bytes));
+        out.push_str(&format!("Max depth: {}\n", self.max_depth));
+        let values = self.objects + self.arrays + self.strings + self.numbers + self.booleans + self.nulls;
+        out.push_str(&format!("Values: {} ({} objects, {} arrays, {} strings, {} numbers, {} booleans, {} nulls)\n",
+                              values, self.objects, self.arrays, self.strings, self.numbers, self.booleans, self.nulls));
+        out.push_str(&format!("Keys: {} ({} distinct, {} duplicate)\n", self.keys, self.key_frequency.len(), self.duplicate_keys));
+
+        let keys: Vec<(String, usize)> = self.key_frequency.iter().take(top)
+            .map(|(key, count)| (Serializer::compact().serialize(&Value::from(key.as_str())), *count))
+            .collect();
+        if let Some((_, most)) = keys.first() {
+            out.push_str("\nKey frequency:\n");
+            let width = keys.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+            for (key, count) in &keys {
+                let bar = "█".repeat((count * 30).div_ceil(*most));
+                out.push_str(&format!("  {:width$}  {:>6}  {}\n", key, count, bar, width = width));
+            }
+        }
+
+        let mut section = |title: &str, entries: &[(Path, usize)], unit: &str| {
+            if entries.is_empty() {
+                return;
+            }
+            out.push_str(&format!("\n{}:\n", title));
+            let paths: Vec<String> = entries.iter().map(|(path, _)| path.to_string()).collect();
+            let width = paths.iter().map(|path| path.chars().count()).max().unwrap_or(0);
+            for (path, (_, size)) in paths.iter().zip(entries) {
+                out.push_str(&format!("  {:width$}  {} {}\n", path, size, unit, width = width));
+            }
+        };
+        section("Largest arrays", &self.largest_arrays, "items");
+        section("Largest strings", &self.largest_strings, "chars");
+        section("Top-level sizes", &self.subtrees, "bytes");
+
+        out
+    }
+}
+
+struct Frame {
+    path: Path,
+    object: bool,
+    items: usize,
+    keys: HashSet<String>,
+    // The key of the member whose value comes next
+    key: Option<String>
+}
+
+#[derive(Default)]
+struct Collector {
+    stats: Stats,
+    frames: Vec<Frame>,
+    key_frequency: HashMap<String, usize>,
+    // Bytes seen so far, tokens cover the whole source text
+    offset: usize,
+    // Numbers arrive a character at a time
+    in_number: bool,
+    // Where the open child of the root container starts and ends so far
+    subtree: Option<(Path, usize, usize)>
+}
+
+impl Collector {
+    fn token(&mut self, token: &Token) {
+        let start = self.offset;
+        self.offset += token.text.len();
+        let in_number = self.in_number;
+        self.in_number = false;
+
+        match token.kind {
+            JsonKind::BeginObject | JsonKind::BeginArray => {
+                let path = self.value(start);
+                let object = token.kind == JsonKind::BeginObject;
+                if object {
+                    self.stats.objects += 1;
+                } else {
+                    self.stats.arrays += 1;
+                }
+                self.frames.push(Frame { path, object, items: 0, keys: HashSet::new(), key: None });
+                self.stats.max_depth = self.stats.max_depth.max(self.frames.len());
+            },
+            JsonKind::EndObject | JsonKind::EndArray => {
+                self.end_subtree();
+                if let Some(frame) = self.frames.pop() {
+                    if !frame.object {
+                        insert_largest(&mut self.stats.largest_arrays, frame.path, frame.items);
+                    }
+                }
+            },
+            JsonKind::ValueSeperator => self.end_subtree(),
+            JsonKind::StringVal => match self.frames.last_mut() {
+                Some(frame) if frame.object && frame.key.is_none() => {
+                    // Bad escapes are the parser's to report, the key just holds the place
+                    let key = decode_escapes(&token.text).unwrap_or_else(|_| token.text.clone());
+                    self.stats.keys += 1;
+                    if !frame.keys.insert(key.clone()) {
+                        self.stats.duplicate_keys += 1;
+                    }
+                    *self.key_frequency.entry(key.clone()).or_default() += 1;
+                    frame.key = Some(key);
+                },
+                _ => {
+                    let path = self.value(start);
+                    self.stats.strings += 1;
+                    if let Ok(decoded) = decode_escapes(&token.text) {
+                        insert_largest(&mut self.stats.largest_strings, path, decoded.chars().count());
+                    }
+                }
+            },
+            JsonKind::True | JsonKind::False => {
+                self.value(start);
+                self.stats.booleans += 1;
+            },
+            JsonKind::Null => {
+                self.value(start);
+                self.stats.nulls += 1;
+            },
+            JsonKind::Plus | JsonKind::Minus | JsonKind::Digit | JsonKind::Zero | JsonKind::DecimalPoint | JsonKind::E => {
+                if !in_number {
+                    self.value(start);
+                    self.stats.numbers += 1;
+                }
+                self.in_number = true;
+            },
+            _ => {}
+        }
+
+        // Trivia inside the subtree counts once more text follows it
+        if let Some((_, _, end)) = &mut self.subtree {
+            if !token.kind.is_trivia() {
+                *end = self.offset;
+            }
+        }
+    }
+
+    // A value starts at `start`, returns its path
+    fn value(&mut self, start: usize) -> Path {
+        let depth = self.frames.len();
+        let path = match self.frames.last_mut() {
+            Some(frame) if frame.object => frame.path.key(&frame.key.take().unwrap_or_default()),
+            Some(frame) => {
+                frame.items += 1;
+                frame.path.index(frame.items - 1)
+            },
+            None => Path::root()
+        };
+        if depth == 1 {
+            self.subtree = Some((path.clone(), start, start));
+        }
+        path
+    }
+
+    // Called on separators and closing brackets, which end a root child
+    fn end_subtree(&mut self) {
+        if self.frames.len() == 1 {
+            if let Some((path, start, end)) = self.subtree.take() {
+                self.stats.subtrees.push((path, end - start));
+            }
+        }
+    }
+
+    fn finish(mut self) -> Stats {
+        self.stats.bytes = self.offset;
+        let mut key_frequency: Vec<(String, usize)> = self.key_frequency.into_iter().collect();
+        key_frequency.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        self.stats.key_frequency = key_frequency;
+        self.stats
+    }
+}
+
+// Keeps the largest entries, earlier ones first among equals
+fn insert_largest(largest: &mut Vec<(Path, usize)>, path: Path, size: usize) {
+    let idx = largest.iter().position(|(_, other)| *other < size).unwrap_or(largest.len());
+    if idx < LARGEST {
+        largest.insert(idx, (path, size));
+        largest.truncate(LARGEST);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,61 @@
+use crate::path::Path;
+use crate::stats::stats;
+
+fn paths(entries: &[(Path, usize)]) -> Vec<(String, usize)> {
+    entries.iter().map(|(path, size)| (path.to_string(), *size)).collect()
+}
+
+#[test]
+fn test_stats() {
+    let data = "{\"id\": 1, \"tags\": [\"a\", \"bé\\n\"],\n \"items\": [{\"id\": -2.5e3, \"ok\": true}, {\"id\": null, \"id\": false}]}";
+    let stats = stats(data).unwrap();
+    assert_eq!(stats.bytes, data.len());
+    assert_eq!(stats.max_depth, 3);
+    assert_eq!((stats.objects, stats.arrays, stats.strings, stats.numbers, stats.booleans, stats.nulls), (3, 2, 2, 2, 2, 1));
+    assert_eq!(stats.keys, 7);
+    assert_eq!(stats.duplicate_keys, 1);
+    assert_eq!(stats.key_frequency, vec![("id".to_string(), 4), ("items".to_string(), 1), ("ok".to_string(), 1), ("tags".to_string(), 1)]);
+    assert_eq!(paths(&stats.largest_arrays), vec![("$.tags".to_string(), 2), ("$.items".to_string(), 2)]);
+    assert_eq!(paths(&stats.largest_strings), vec![("$.tags[1]".to_string(), 3), ("$.tags[0]".to_string(), 1)]);
+    assert_eq!(paths(&stats.subtrees), vec![("$.id".to_string(), 1), ("$.tags".to_string(), 14),
+                                            ("$.items".to_string(), 55)]);
+}
+
+#[test]
+fn test_scalars_and_arrays() {
+    let scalar = stats("  \"x\"  ").unwrap();
+    assert_eq!(scalar.max_depth, 0);
+    assert_eq!(scalar.strings, 1);
+    assert!(scalar.subtrees.is_empty());
+
+    let arrays = stats("[ 10 , [] , [[1, 2, 3]] ]").unwrap();
+    assert_eq!(arrays.numbers, 4);
+    assert_eq!(paths(&arrays.subtrees), vec![("$[0]".to_string(), 2), ("$[1]".to_string(), 2), ("$[2]".to_string(), 11)]);
+    assert_eq!(paths(&arrays.largest_arrays), vec![("$[2][0]".to_string(), 3), ("$".to_string(), 3),
+                                                  ("$[2]".to_string(), 1), ("$[1]".to_string(), 0)]);
+}
+
+#[test]
+fn test_invalid() {
+    let err = stats("{\"a\": [1,]}").unwrap_err();
+    assert_eq!(err.code.as_str(), "E0007");
+    assert_eq!(stats("[\"\\uZZZZ\"]").unwrap_err().code.as_str(), "E0012");
+    assert_eq!(stats("{\"\\q\": 1}").unwrap_err().code.as_str(), "E0011");
+}
+
+#[test]
+fn test_report() {
+    let report = stats("{\"a\": [\"x\"], \"b\": {\"a\": 1}}").unwrap().report(10);
+    assert!(report.contains("Keys: 3 (2 distinct, 0 duplicate)\n"), "{}", report);
+    assert!(report.contains("\nKey frequency:\n  \"a\"       2  ██████████████████████████████\n  \"b\"       1  ███████████████\n"), "{}", report);
+    assert!(report.contains("\nTop-level sizes:\n  $.a  5 bytes\n  $.b  8 bytes\n"), "{}", report);
+}
+
+#[test]
+fn test_to_value() {
+    let value = stats("[\"ab\"]").unwrap().to_value();
+    assert_eq!(crate::serializer::Serializer::compact().serialize(&value),
+               "{\"bytes\":6,\"maxDepth\":1,\"types\":{\"object\":0,\"array\":1,\"string\":1,\"number\":0,\"boolean\":0,\"null\":0},\
+               \"keys\":0,\"duplicateKeys\":0,\"keyFrequency\":{},\"largestArrays\":[{\"path\":\"$\",\"size\":1}],\
+               \"largestStrings\":[{\"path\":\"$[0]\",\"size\":2}],\"subtrees\":[{\"path\":\"$[0]\",\"size\":4}]}");
+}
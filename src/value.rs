@@ -0,0 +1,213 @@
+use std::fmt;
+
+/// A JSON number kept in its original lexical form so that nothing is lost
+/// between parsing and writing it back out.
+#[derive(Debug, Clone)]
+pub struct Number(String);
+
+impl Number {
+    pub fn new(text: String) -> Number {
+        Number(text)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.0.parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.0.parse().ok()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        !self.0.contains(['.', 'e', 'E'])
+    }
+
+    /// Whether text follows JSON's number grammar, so `007` or `1.` do not.
+    pub fn is_valid(text: &str) -> bool {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        let (mantissa, exponent) = match digits.find(['e', 'E']) {
+            Some(idx) => (&digits[..idx], Some(&digits[idx + 1..])),
+            None => (digits, None)
+        };
+        let (integer, fraction) = match mantissa.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (mantissa, None)
+        };
+        let all_digits = |val: &str| !val.is_empty() && val.chars().all(|c| c.is_ascii_digit());
+
+        all_digits(integer) && !(integer.len() > 1 && integer.starts_with('0')) &&
+            fraction.is_none_or(all_digits) &&
+            exponent.is_none_or(|val| all_digits(val.strip_prefix(['+', '-']).unwrap_or(val)))
+    }
+}
+
+impl From<i64> for Number {
+    fn from(val: i64) -> Number {
+        Number(val.to_string())
+    }
+}
+
+impl From<usize> for Number {
+    fn from(val: usize) -> Number {
+        Number(val.to_string())
+    }
+}
+
+/// JSON has no infinities, they are clamped to the largest finite values,
+/// and NaN becomes 0.
+impl From<f64> for Number {
+    fn from(val: f64) -> Number {
+        let val = if val.is_nan() { 0.0 } else { val.clamp(f64::MIN, f64::MAX) };
+        if val.is_finite() && val.fract() == 0.0 && val.abs() < 1e15 {
+            Number(format!("{}", val as i64))
+        } else {
+            Number(format!("{}", val))
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.as_f64() == other.as_f64()
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<Value>),
+    // Members are kept in document order
+    Object(Vec<(String, Value)>)
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, val)| val),
+            _ => None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self {
+            Value::Object(members) => members.iter_mut().rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, val)| val),
+            _ => None
+        }
+    }
+
+    /// Sets `key` on an object, replacing an existing member in place or
+    /// appending a new one. Returns the previous value.
+    pub fn insert(&mut self, key: &str, value: Value) -> Option<Value> {
+        if let Some(val) = self.get_mut(key) {
+            return Some(std::mem::replace(val, value));
+        }
+
+        if let Value::Object(members) = self {
+            members.push((key.to_string(), value));
+        }
+        None
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        match self {
+            Value::Object(members) => {
+                let idx = members.iter().rposition(|(name, _)| name == key)?;
+                Some(members.remove(idx).1)
+            },
+            _ => None
+        }
+    }
+
+    pub fn index(&self, idx: usize) -> Option<&Value> {
+        match self {
+            Value::Array(items) => items.get(idx),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(val) => Some(val),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(val) => Some(val.as_f64()),
+            _ => None
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object"
+        }
+    }
+}
+
+// Objects compare as unordered sets of members and numbers compare by value,
+// which is what JSON considers equal.
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len() &&
+                    a.iter().all(|(key, val)| other.get(key) == Some(val)) &&
+                    b.iter().all(|(key, _)| self.get(key).is_some())
+            },
+            _ => false
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(val: &str) -> Value {
+        Value::String(val.to_string())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(val: bool) -> Value {
+        Value::Bool(val)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(val: i64) -> Value {
+        Value::Number(Number::from(val))
+    }
+}
+
+#[cfg(test)]
+mod tests;
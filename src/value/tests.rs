@@ -0,0 +1,46 @@
+use crate::value::{Number, Value};
+
+#[test]
+fn test_number_equality() {
+    assert_eq!(Number::new("1".to_string()), Number::new("1.0".to_string()));
+    assert_eq!(Number::new("10".to_string()), Number::new("1e1".to_string()));
+    assert_ne!(Number::new("9007199254740993".to_string()), Number::new("9007199254740992".to_string()));
+}
+
+#[test]
+fn test_number_from_non_finite() {
+    assert_eq!(Number::from(f64::INFINITY), Number::from(f64::MAX));
+    assert_eq!(Number::from(f64::NEG_INFINITY), Number::from(f64::MIN));
+    assert_eq!(Number::from(f64::NAN), Number::from(0i64));
+    assert!(Number::is_valid(&Number::from(f64::NEG_INFINITY).to_string()));
+}
+
+#[test]
+fn test_object_equality_ignores_order() {
+    let a = Value::Object(vec![
+        ("a".to_string(), Value::from(1)),
+        ("b".to_string(), Value::from("two")),
+    ]);
+    let b = Value::Object(vec![
+        ("b".to_string(), Value::from("two")),
+        ("a".to_string(), Value::from(1)),
+    ]);
+    let c = Value::Object(vec![
+        ("a".to_string(), Value::from(1)),
+    ]);
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(Value::Array(vec![Value::from(1), Value::from(2)]), Value::Array(vec![Value::from(2), Value::from(1)]));
+}
+
+#[test]
+fn test_get_returns_last_duplicate() {
+    let value = Value::Object(vec![
+        ("a".to_string(), Value::from(1)),
+        ("a".to_string(), Value::from(2)),
+    ]);
+
+    assert_eq!(value.get("a"), Some(&Value::from(2)));
+    assert_eq!(value.get("b"), None);
+}